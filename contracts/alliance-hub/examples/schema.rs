@@ -1,4 +1,4 @@
-use alliance_protocol::alliance_protocol::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use alliance_protocol::alliance_protocol::{ExecuteMsg, InstantiateMsg, QueryMsg, SudoMsg};
 use cosmwasm_schema::write_api;
 
 fn main() {
@@ -6,5 +6,6 @@ fn main() {
         instantiate: InstantiateMsg,
         execute: ExecuteMsg,
         query: QueryMsg,
+        sudo: SudoMsg,
     }
 }
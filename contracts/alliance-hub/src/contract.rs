@@ -3,45 +3,70 @@ use std::collections::{HashMap, HashSet};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    ensure, from_json, to_json_binary, Addr, Binary, Coin as CwCoin, CosmosMsg, Decimal, DepsMut,
-    Empty, Env, MessageInfo, Reply, Response, StdError, StdResult, Storage, SubMsg, Timestamp,
+    ensure, from_json, to_json_binary, Addr, Coin as CwCoin, CosmosMsg, Decimal, DepsMut, Env,
+    MessageInfo, Reply, Response, StdError, StdResult, Storage, SubMsg, SubMsgResult, Timestamp,
     Uint128, WasmMsg,
 };
 use cw2::{get_contract_version, set_contract_version};
 use cw20::Cw20ReceiveMsg;
 use cw_asset_v3::{Asset, AssetInfo, AssetInfoBase};
-use cw_utils::parse_instantiate_response_data;
 use semver::Version;
-use terra_proto_rs::alliance::alliance::{
-    MsgClaimDelegationRewards, MsgDelegate, MsgRedelegate, MsgUndelegate,
-};
-use terra_proto_rs::cosmos::base::v1beta1::Coin;
-use terra_proto_rs::traits::Message;
 
 // use alliance_protocol::alliance_oracle_types::QueryMsg as OracleQueryMsg;
 use alliance_protocol::alliance_oracle_types::ChainId;
 use alliance_protocol::alliance_protocol::{
-    AllianceDelegateMsg, AllianceRedelegateMsg, AllianceUndelegateMsg, AssetDistribution, Config,
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    AllianceDelegateMsg, AllianceRedelegateMsg, AllianceTokenMetadata, AllianceUndelegateMsg,
+    AssetDistribution, AssetVotingWeight, Capability, Config, Cw20HookMsg, DelegationStrategy,
+    ExecuteMsg, IbcDestination, IbcLifecycleComplete, IbcTransfer, IbcTransferKind, InstantiateMsg,
+    MigrateMsg, PauseScope, PendingRoleTransfer, RebalanceAction, Role, StakingBackend, SudoMsg,
+    DEFAULT_UNBONDING_PERIOD,
 };
 
 // use alliance_protocol::alliance_oracle_types::{AssetStaked, ChainId, EmissionsDistribution};
+use crate::delegations::{
+    delegated_validators, is_claim_excluded, plan_rebalance, record_claim_failure,
+    record_delegation, record_redelegation, record_redelegation_cooldown, record_undelegation,
+    total_bonded, validate_strategy,
+};
 use crate::error::ContractError;
-use crate::migrations::migrate_maps;
+use crate::events;
+use crate::hooks::{self, stake_changed_hooks};
+use crate::ibc_hooks::{
+    ibc_transfer_msg, origin_destination, parse_ibc_transfer_sequence, validate_ibc_destination,
+    verify_ibc_origin,
+};
+use crate::invariants::{
+    accrued_rewards, add_reward_liabilities, deduct_reward_liabilities, owed_amount,
+};
+use crate::migrations::{apply_migrate_params, run_migrations};
+use crate::permissions::{
+    ensure_capability, grant_capability, grant_role_capabilities, has_capability,
+    revoke_capability, transfer_role_capabilities,
+};
+use crate::staking::{validate_bond_denom, Staking};
 use crate::state::{
-    ASSET_REWARD_DISTRIBUTION, ASSET_REWARD_RATE, BALANCES, CONFIG, TEMP_BALANCE, TOTAL_BALANCES,
-    UNCLAIMED_REWARDS, USER_ASSET_REWARD_RATE, VALIDATORS, WHITELIST,
+    ALLIANCE_TOKEN_METADATA, ASSET_PAUSES, ASSET_REWARD_DISTRIBUTION, ASSET_REWARD_RATE, BALANCES,
+    CLAIM_FAILURES, CONFIG, DELEGATION_STRATEGY, GLOBAL_PAUSE, IBC_TRANSFERS, PENDING_CLAIMS,
+    PENDING_ROLE_TRANSFERS, REMOTE_IDENTITIES, REWARD_LIABILITIES, REWARD_UPDATES_PAUSE,
+    TEMP_BALANCE, TEMP_IBC_TRANSFER, TEMP_KEEPER, TEMP_STAKING_BALANCE, TOTAL_BALANCES,
+    UNCLAIMED_REWARDS, USER_ASSET_REWARD_RATE, VOTING_DAO, VOTING_WEIGHTS, WHITELIST,
+    WITHDRAWN_REWARDS,
 };
-use crate::token_factory::{CustomExecuteMsg, DenomUnit, Metadata, TokenExecuteMsg};
+use crate::token_factory::{CustomExecuteMsg, Metadata, TokenFactory};
+use crate::voting::validate_asset_weights;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:terra-alliance-protocol";
-const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub(crate) const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 const CREATE_REPLY_ID: u64 = 1;
-const CLAIM_REWARD_ERROR_REPLY_ID: u64 = 2;
+const CLAIM_REWARD_REPLY_ID: u64 = 2;
+const IBC_TRANSFER_REPLY_ID: u64 = 3;
+const STAKE_HOOK_REPLY_ID: u64 = 4;
+const WITHDRAWN_REWARDS_REPLY_ID: u64 = 5;
+pub(crate) const MAX_KEEPER_BOUNTY: Decimal = Decimal::percent(10);
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(mut deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
     let version: Version = CONTRACT_VERSION.parse()?;
     let storage_version: Version = get_contract_version(deps.storage)?.version.parse()?;
 
@@ -50,16 +75,27 @@ pub fn migrate(mut deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Respons
         StdError::generic_err("Invalid contract version")
     );
 
-    migrate_maps(deps.branch())?;
+    let steps = run_migrations(deps.branch(), &storage_version, &version, &msg)?;
+    apply_migrate_params(deps.branch(), &msg)?;
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    Ok(Response::default())
+    let steps = steps
+        .into_iter()
+        .map(|step| step.name)
+        .collect::<Vec<_>>()
+        .join(",");
+    Ok(Response::new().add_attributes(vec![
+        ("action", "migrate".to_string()),
+        ("from_version", storage_version.to_string()),
+        ("to_version", version.to_string()),
+        ("steps", steps),
+    ]))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response<CustomExecuteMsg>, ContractError> {
@@ -68,13 +104,13 @@ pub fn instantiate(
     let controller_address = deps.api.addr_validate(msg.controller.as_str())?;
     let oracle_address = deps.api.addr_validate(msg.oracle.as_str())?;
     let operator_address = deps.api.addr_validate(msg.operator.as_str())?;
-    let create_msg = TokenExecuteMsg::CreateDenom {
-        subdenom: msg.alliance_token_denom.to_string(),
-    };
-    let sub_msg = SubMsg::reply_on_success(
-        CosmosMsg::Custom(CustomExecuteMsg::Token(create_msg)),
-        CREATE_REPLY_ID,
-    );
+    let metadata = msg.alliance_token_metadata.unwrap_or_default();
+    validate_token_metadata(&metadata)?;
+    ALLIANCE_TOKEN_METADATA.save(deps.storage, &metadata)?;
+    let token_factory_backend = msg.token_factory_backend.unwrap_or_default();
+    let create_msg = token_factory_backend
+        .create_denom(env.contract.address.as_str(), &msg.alliance_token_denom);
+    let sub_msg = SubMsg::reply_on_success(create_msg, CREATE_REPLY_ID);
 
     // We set asset_reward_distribution here or manually via an execute method otherwise there is no distribution ratio
     // asset_reward_distribution is a list of AssetDistribution which is a struct that contains an AssetInfo and a Decimal.
@@ -97,11 +133,17 @@ pub fn instantiate(
         alliance_token_denom: "".to_string(),
         alliance_token_supply: Uint128::zero(),
         last_reward_update_timestamp: Timestamp::default(),
-        reward_denom: msg.reward_denom,
+        reward_asset: validate_reward_asset(&deps, msg.reward_asset)?,
+        token_factory_backend,
+        staking_backend: msg.staking_backend.unwrap_or_default(),
+        reward_update_interval: 0,
+        keeper_bounty: Decimal::zero(),
+        unbonding_period: msg.unbonding_period.unwrap_or(DEFAULT_UNBONDING_PERIOD),
     };
+    validate_bond_denom(deps.storage, &config)?;
     CONFIG.save(deps.storage, &config)?;
+    grant_role_capabilities(deps.storage, &config)?;
 
-    VALIDATORS.save(deps.storage, &HashSet::new())?;
     Ok(Response::new()
         .add_attributes(vec![("action", "instantiate")])
         .add_submessage(sub_msg))
@@ -113,19 +155,35 @@ pub fn execute(
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
-) -> Result<Response, ContractError> {
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
     match msg {
         // Enable support for staking and unstaking of Cw20Assets
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::WhitelistAssets(assets) => whitelist_assets(deps, info, assets),
         ExecuteMsg::RemoveAssets(assets) => remove_assets(deps, info, assets),
-        ExecuteMsg::Stake {} => {
+        ExecuteMsg::Stake { recipient, origin } => {
             if info.funds.len() != 1 {
                 return Err(ContractError::OnlySingleAssetAllowed {});
             }
             if info.funds[0].amount.is_zero() {
                 return Err(ContractError::AmountCannotBeZero {});
             }
+            let recipient = match recipient {
+                Some(recipient) => deps.api.addr_validate(&recipient)?,
+                None => info.sender.clone(),
+            };
+            if let Some(origin) = origin {
+                verify_ibc_origin(&env.contract.address, &info.sender, &origin)?;
+                // The origin identity routes funds of the position back, so it has to
+                // belong to the address the stake is credited to
+                if recipient != info.sender {
+                    return Err(ContractError::InvalidIbcOrigin(format!(
+                        "{} cannot stake for {}",
+                        info.sender, recipient
+                    )));
+                }
+                REMOTE_IDENTITIES.save(deps.storage, &info.sender, &origin)?;
+            }
             let asset = AssetInfo::native(&info.funds[0].denom);
             stake(
                 deps,
@@ -133,16 +191,65 @@ pub fn execute(
                 info.clone(),
                 asset,
                 info.funds[0].amount,
-                info.sender,
+                recipient,
             )
         }
-        ExecuteMsg::Unstake(asset) => unstake(deps, info, asset),
-        ExecuteMsg::ClaimRewards(asset) => claim_rewards(deps, info, asset),
-        ExecuteMsg::UpdateRewards {} => update_rewards(deps, env, info),
+        ExecuteMsg::Unstake(asset) => {
+            ensure_no_funds(&info)?;
+            let destination = origin_destination(deps.storage, &info.sender, &asset.info)?;
+            unstake(deps, env, info, asset, destination)
+        }
+        ExecuteMsg::ClaimRewards(asset) => {
+            ensure_no_funds(&info)?;
+            let reward_asset = CONFIG.load(deps.storage)?.reward_asset;
+            let destination = origin_destination(deps.storage, &info.sender, &reward_asset)?;
+            claim_rewards(deps, env, info, asset, destination)
+        }
+        ExecuteMsg::UnstakeOverIbc { asset, destination } => {
+            ensure_no_funds(&info)?;
+            validate_ibc_destination(&destination)?;
+            unstake(deps, env, info, asset, Some(destination))
+        }
+        ExecuteMsg::ClaimRewardsOverIbc { asset, destination } => {
+            ensure_no_funds(&info)?;
+            validate_ibc_destination(&destination)?;
+            claim_rewards(deps, env, info, asset, Some(destination))
+        }
+        ExecuteMsg::UpdateRewards {} => {
+            let config = CONFIG.load(deps.storage)?;
+            let sent_balance = match &config.reward_asset {
+                AssetInfo::Native(denom) => info
+                    .funds
+                    .iter()
+                    .find(|c| &c.denom == denom)
+                    .map(|c| c.amount)
+                    .unwrap_or_default(),
+                _ => Uint128::zero(),
+            };
+            update_rewards(deps, env, info.sender, sent_balance)
+        }
         // ualliance token delegation methods
         ExecuteMsg::AllianceDelegate(msg) => alliance_delegate(deps, env, info, msg),
         ExecuteMsg::AllianceUndelegate(msg) => alliance_undelegate(deps, env, info, msg),
         ExecuteMsg::AllianceRedelegate(msg) => alliance_redelegate(deps, env, info, msg),
+        ExecuteMsg::SetDelegationStrategy(strategy) => {
+            set_delegation_strategy(deps, info, strategy)
+        }
+        ExecuteMsg::RebalanceDelegations {} => rebalance_delegations(deps, env, info),
+        ExecuteMsg::ResetClaimFailures { validator } => reset_claim_failures(deps, info, validator),
+        ExecuteMsg::MintAllianceTokens { amount } => mint_alliance_tokens(deps, env, info, amount),
+        ExecuteMsg::BurnAllianceTokens { amount } => burn_alliance_tokens(deps, env, info, amount),
+        ExecuteMsg::UpdateRewardsConfig {
+            reward_update_interval,
+            keeper_bounty,
+        } => update_rewards_config(deps, info, reward_update_interval, keeper_bounty),
+        ExecuteMsg::UpdateRewardAsset(asset) => update_reward_asset(deps, info, asset),
+        ExecuteMsg::UpdateVotingConfig { dao, asset_weights } => {
+            update_voting_config(deps, env, info, dao, asset_weights)
+        }
+        ExecuteMsg::UpdateAllianceTokenMetadata(metadata) => {
+            update_alliance_token_metadata(deps, env, info, metadata)
+        }
         ExecuteMsg::UpdateRewardsCallback {} => update_reward_callback(deps, env, info),
         ExecuteMsg::SetAssetRewardDistribution(asset_reward_distribution) => {
             set_asset_reward_distribution(deps, info, asset_reward_distribution)
@@ -151,13 +258,27 @@ pub fn execute(
         // ExecuteMsg::RebalanceEmissions {} => rebalance_emissions(deps, env, info),
         // ExecuteMsg::RebalanceEmissionsCallback {} => rebalance_emissions_callback(deps, env, info),
         // Allow Governance to overwrite the AssetDistributions for the reward emissions
+        ExecuteMsg::ProposeRoleTransfer {
+            role,
+            new_address,
+            expires_in,
+        } => propose_role_transfer(deps, env, info, role, new_address, expires_in),
+        ExecuteMsg::AcceptRoleTransfer { role } => accept_role_transfer(deps, env, info, role),
+        ExecuteMsg::CancelRoleTransfer { role } => cancel_role_transfer(deps, info, role),
+        ExecuteMsg::Pause(scope) => pause(deps, info, scope),
+        ExecuteMsg::Unpause(scope) => unpause(deps, info, scope),
+        ExecuteMsg::GrantCapability {
+            address,
+            capability,
+        } => grant(deps, info, address, capability),
+        ExecuteMsg::RevokeCapability {
+            address,
+            capability,
+        } => revoke(deps, info, address, capability),
+        ExecuteMsg::AddHook { addr, gas_limit } => add_hook(deps, info, addr, gas_limit),
+        ExecuteMsg::RemoveHook { addr } => remove_hook(deps, info, addr),
+        ExecuteMsg::Sweep { asset, recipient } => sweep(deps, env, info, asset, recipient),
         // Generic unsupported handler returns a StdError
-        ExecuteMsg::UpdateConfig {
-            governance,
-            controller,
-            oracle,
-            operator,
-        } => update_config(deps, info, governance, controller, oracle, operator),
         _ => Err(ContractError::Std(StdError::generic_err(
             "unsupported action",
         ))),
@@ -170,7 +291,7 @@ fn receive_cw20(
     env: Env,
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
-) -> Result<Response, ContractError> {
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
     let sender = deps.api.addr_validate(&cw20_msg.sender)?;
 
     match from_json(&cw20_msg.msg)? {
@@ -181,7 +302,14 @@ fn receive_cw20(
             let asset = AssetInfo::Cw20(info.sender.clone());
             stake(deps, env, info, asset, cw20_msg.amount, sender)
         }
-        Cw20HookMsg::Unstake(asset) => unstake(deps, info, asset),
+        Cw20HookMsg::Unstake(asset) => unstake(deps, env, info, asset, None),
+        Cw20HookMsg::UpdateRewards {} => {
+            let config = CONFIG.load(deps.storage)?;
+            if config.reward_asset != AssetInfo::Cw20(info.sender.clone()) {
+                return Err(ContractError::InvalidRewardAsset(info.sender.to_string()));
+            }
+            update_rewards(deps, env, sender, cw20_msg.amount)
+        }
     }
 }
 
@@ -189,9 +317,8 @@ fn set_asset_reward_distribution(
     deps: DepsMut,
     info: MessageInfo,
     asset_reward_distribution: Vec<AssetDistribution>,
-) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-    is_authorized(&info, &config)?;
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    ensure_capability(deps.storage, &info.sender, Capability::SetDistribution)?;
 
     // Ensure the dsitributions add up to 100%
     let total_distribution = asset_reward_distribution
@@ -206,23 +333,26 @@ fn set_asset_reward_distribution(
     // Simply set the asset_reward_distribution, overwriting any previous settings.
     // This means any updates should include the full existing set of AssetDistributions and not just the newly updated one.
     ASSET_REWARD_DISTRIBUTION.save(deps.storage, &asset_reward_distribution)?;
-    Ok(Response::new().add_attributes(vec![("action", "set_asset_reward_distribution")]))
+    Ok(Response::new()
+        .add_attributes(vec![("action", "set_asset_reward_distribution")])
+        .add_event(events::distribution_updated(&asset_reward_distribution)?))
 }
 
 fn whitelist_assets(
     deps: DepsMut,
     info: MessageInfo,
     assets_request: HashMap<ChainId, Vec<AssetInfo>>,
-) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-    is_governance(&info, &config)?;
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    ensure_capability(deps.storage, &info.sender, Capability::Whitelist)?;
     let mut attrs = vec![("action".to_string(), "whitelist_assets".to_string())];
+    let mut whitelisted_events = vec![];
     for (chain_id, assets) in &assets_request {
         for asset in assets {
             WHITELIST.save(deps.storage, asset, chain_id)?;
             ASSET_REWARD_RATE.update(deps.storage, asset, |rate| -> StdResult<_> {
                 Ok(rate.unwrap_or(Decimal::zero()))
             })?;
+            whitelisted_events.push(events::asset_whitelisted(asset, chain_id));
         }
         attrs.push(("chain_id".to_string(), chain_id.to_string()));
         let assets_str = assets
@@ -233,17 +363,19 @@ fn whitelist_assets(
 
         attrs.push(("assets".to_string(), assets_str.to_string()));
     }
-    Ok(Response::new().add_attributes(attrs))
+    validate_bond_denom(deps.storage, &CONFIG.load(deps.storage)?)?;
+    Ok(Response::new()
+        .add_attributes(attrs)
+        .add_events(whitelisted_events))
 }
 
 fn remove_assets(
     deps: DepsMut,
     info: MessageInfo,
     assets: Vec<AssetInfo>,
-) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-    // Only allow the governance address to update whitelisted assets
-    is_governance(&info, &config)?;
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    // Only allow addresses with the whitelist capability to update whitelisted assets
+    ensure_capability(deps.storage, &info.sender, Capability::Whitelist)?;
     for asset in &assets {
         WHITELIST.remove(deps.storage, asset);
     }
@@ -252,35 +384,86 @@ fn remove_assets(
         .map(|asset| asset.to_string())
         .collect::<Vec<String>>()
         .join(",");
-    Ok(Response::new().add_attributes(vec![("action", "remove_assets"), ("assets", &assets_str)]))
+    Ok(Response::new()
+        .add_attributes(vec![("action", "remove_assets"), ("assets", &assets_str)])
+        .add_events(assets.iter().map(events::asset_removed)))
 }
 
 fn stake(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     asset: AssetInfoBase<Addr>,
     amount: Uint128,
     sender: Addr,
-) -> Result<Response, ContractError> {
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    ensure_not_paused(deps.storage, &PauseScope::Stake(asset.clone()))?;
     WHITELIST
         .load(deps.storage, &asset)
         .map_err(|_| ContractError::AssetNotWhitelisted {})?;
 
-    let rewards = _claim_reward(deps.storage, sender.clone(), asset.clone())?;
+    let (balance, total_balance) = add_balance(
+        deps.storage,
+        env.block.height,
+        sender.clone(),
+        &asset,
+        amount,
+    )?;
+    let hooks = stake_changed_hooks(
+        deps.storage,
+        &sender,
+        &asset,
+        balance - amount,
+        balance,
+        STAKE_HOOK_REPLY_ID,
+    )?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "stake"),
+            ("user", (sender.as_ref())),
+            ("asset", &asset.to_string()),
+            ("amount", &amount.to_string()),
+        ])
+        .add_event(events::stake(
+            &sender,
+            &asset,
+            amount,
+            balance,
+            total_balance,
+        ))
+        .add_submessages(hooks))
+}
+
+// Returns the staked balances of the user and of everyone after the update
+fn add_balance(
+    storage: &mut dyn Storage,
+    height: u64,
+    user: Addr,
+    asset: &AssetInfo,
+    amount: Uint128,
+) -> Result<(Uint128, Uint128), ContractError> {
+    let rewards = _claim_reward(storage, user.clone(), asset.clone())?;
     if !rewards.is_zero() {
         UNCLAIMED_REWARDS.update(
-            deps.storage,
-            (sender.clone(), &asset),
+            storage,
+            (user.clone(), asset),
             |balance| -> Result<_, ContractError> {
                 Ok(balance.unwrap_or(Uint128::zero()) + rewards)
             },
         )?;
     }
-
-    BALANCES.update(
-        deps.storage,
-        (sender.clone(), &asset),
+    // The rate is reset below, dropping what is left accrued on the previous balance
+    let previous_balance = BALANCES
+        .may_load(storage, (user.clone(), asset))?
+        .unwrap_or_default();
+    let dropped = accrued_rewards(storage, &user, asset, previous_balance)?;
+    deduct_reward_liabilities(storage, dropped)?;
+
+    let balance = BALANCES.update(
+        storage,
+        (user.clone(), asset),
+        height,
         |balance| -> Result<_, ContractError> {
             match balance {
                 Some(balance) => Ok(balance + amount),
@@ -288,30 +471,32 @@ fn stake(
             }
         },
     )?;
-    TOTAL_BALANCES.update(
-        deps.storage,
-        &asset,
+    let total_balance = TOTAL_BALANCES.update(
+        storage,
+        asset,
+        height,
         |balance| -> Result<_, ContractError> { Ok(balance.unwrap_or(Uint128::zero()) + amount) },
     )?;
 
     let asset_reward_rate = ASSET_REWARD_RATE
-        .load(deps.storage, &asset)
+        .load(storage, asset)
         .unwrap_or(Decimal::zero());
-    USER_ASSET_REWARD_RATE.save(deps.storage, (sender.clone(), &asset), &asset_reward_rate)?;
-
-    Ok(Response::new().add_attributes(vec![
-        ("action", "stake"),
-        ("user", (sender.as_ref())),
-        ("asset", &asset.to_string()),
-        ("amount", &amount.to_string()),
-    ]))
+    USER_ASSET_REWARD_RATE.save(storage, (user, asset), &asset_reward_rate)?;
+    Ok((balance, total_balance))
 }
 
-fn unstake(deps: DepsMut, info: MessageInfo, asset: Asset) -> Result<Response, ContractError> {
+fn unstake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Asset,
+    destination: Option<IbcDestination>,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
     let sender = info.sender.clone();
     if asset.amount.is_zero() {
         return Err(ContractError::AmountCannotBeZero {});
     }
+    ensure_not_paused(deps.storage, &PauseScope::Unstake(asset.info.clone()))?;
 
     let rewards = _claim_reward(deps.storage, sender.clone(), asset.info.clone())?;
     if !rewards.is_zero() {
@@ -323,10 +508,14 @@ fn unstake(deps: DepsMut, info: MessageInfo, asset: Asset) -> Result<Response, C
             },
         )?;
     }
+    // The rate is kept, so what is left accrued on the unstaked amount is dropped
+    let dropped = accrued_rewards(deps.storage, &sender, &asset.info, asset.amount)?;
+    deduct_reward_liabilities(deps.storage, dropped)?;
 
-    BALANCES.update(
+    let balance = BALANCES.update(
         deps.storage,
         (sender, &asset.info),
+        env.block.height,
         |balance| -> Result<_, ContractError> {
             match balance {
                 Some(balance) => {
@@ -339,9 +528,10 @@ fn unstake(deps: DepsMut, info: MessageInfo, asset: Asset) -> Result<Response, C
             }
         },
     )?;
-    TOTAL_BALANCES.update(
+    let total_balance = TOTAL_BALANCES.update(
         deps.storage,
         &asset.info,
+        env.block.height,
         |balance| -> Result<_, ContractError> {
             let balance = balance.unwrap_or(Uint128::zero());
             if balance < asset.amount {
@@ -351,23 +541,54 @@ fn unstake(deps: DepsMut, info: MessageInfo, asset: Asset) -> Result<Response, C
         },
     )?;
 
-    let msg = asset.transfer_msg(&info.sender)?;
-
-    Ok(Response::new()
+    let response = Response::new()
         .add_attributes(vec![
             ("action", "unstake"),
             ("user", info.sender.as_ref()),
             ("asset", &asset.info.to_string()),
             ("amount", &asset.amount.to_string()),
         ])
-        .add_message(msg))
+        .add_event(events::unstake(
+            &info.sender,
+            &asset.info,
+            asset.amount,
+            balance,
+            total_balance,
+        ))
+        .add_submessages(stake_changed_hooks(
+            deps.storage,
+            &info.sender,
+            &asset.info,
+            balance + asset.amount,
+            balance,
+            STAKE_HOOK_REPLY_ID,
+        )?);
+    match destination {
+        Some(destination) => send_over_ibc(
+            deps.storage,
+            &env,
+            response,
+            IbcTransfer {
+                user: info.sender,
+                asset: asset.info.clone(),
+                amount: asset.amount,
+                kind: IbcTransferKind::Unstake,
+            },
+            asset,
+            &destination,
+        ),
+        None => Ok(response.add_message(transfer_msg(&asset, &info.sender)?)),
+    }
 }
 
 fn claim_rewards(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     asset_info: AssetInfo,
-) -> Result<Response, ContractError> {
+    destination: Option<IbcDestination>,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    ensure_not_paused(deps.storage, &PauseScope::ClaimRewards(asset_info.clone()))?;
     let user = info.sender;
     let config = CONFIG.load(deps.storage)?;
     let rewards = _claim_reward(deps.storage, user.clone(), asset_info.clone())?;
@@ -376,23 +597,71 @@ fn claim_rewards(
         .unwrap_or(Uint128::zero());
     let final_rewards = rewards + unclaimed_rewards;
     UNCLAIMED_REWARDS.remove(deps.storage, (user.clone(), &asset_info));
-    let response = Response::new().add_attributes(vec![
-        ("action", "claim_rewards"),
-        ("user", user.as_ref()),
-        ("asset", &asset_info.to_string()),
-        ("reward_amount", &final_rewards.to_string()),
-    ]);
-    if !final_rewards.is_zero() {
-        let rewards_asset = Asset {
-            info: AssetInfo::Native(config.reward_denom),
-            amount: final_rewards,
-        };
-        Ok(response.add_message(rewards_asset.transfer_msg(&user)?))
-    } else {
-        Ok(response)
+    deduct_reward_liabilities(deps.storage, Decimal::from_atomics(final_rewards, 0)?)?;
+    let response = Response::new()
+        .add_attributes(vec![
+            ("action", "claim_rewards"),
+            ("user", user.as_ref()),
+            ("asset", &asset_info.to_string()),
+            ("reward_amount", &final_rewards.to_string()),
+        ])
+        .add_event(events::claim(
+            &user,
+            &asset_info,
+            &config.reward_asset,
+            final_rewards,
+        ));
+    if final_rewards.is_zero() {
+        return Ok(response);
+    }
+    let rewards_asset = Asset::new(config.reward_asset, final_rewards);
+    match destination {
+        Some(destination) => send_over_ibc(
+            deps.storage,
+            &env,
+            response,
+            IbcTransfer {
+                user,
+                asset: asset_info,
+                amount: final_rewards,
+                kind: IbcTransferKind::ClaimRewards,
+            },
+            rewards_asset,
+            &destination,
+        ),
+        None => Ok(response.add_message(transfer_msg(&rewards_asset, &user)?)),
     }
 }
 
+// The transfer is recorded under its packet sequence once the reply returns it, so the
+// funds can be refunded when ibc-hooks reports a failed or timed out packet.
+fn send_over_ibc(
+    storage: &mut dyn Storage,
+    env: &Env,
+    response: Response<CustomExecuteMsg>,
+    transfer: IbcTransfer,
+    asset: Asset,
+    destination: &IbcDestination,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let coin = match asset.info {
+        AssetInfo::Native(denom) => CwCoin::new(asset.amount.u128(), denom),
+        _ => {
+            return Err(ContractError::InvalidIbcDestination(format!(
+                "{} cannot be sent over IBC",
+                asset.info
+            )))
+        }
+    };
+    TEMP_IBC_TRANSFER.save(storage, &(destination.channel_id.clone(), transfer))?;
+    let msg = ibc_transfer_msg(env, coin, destination);
+    Ok(response
+        .add_attributes(vec![
+            ("channel_id", destination.channel_id.as_str()),
+            ("receiver", destination.receiver.as_str()),
+        ])
+        .add_submessage(SubMsg::reply_on_success(msg, IBC_TRANSFER_REPLY_ID)))
+}
+
 fn _claim_reward(
     storage: &mut dyn Storage,
     user: Addr,
@@ -403,13 +672,15 @@ fn _claim_reward(
 
     if let Ok(user_reward_rate) = user_reward_rate {
         let user_staked = BALANCES.load(storage, (user.clone(), &asset_info))?;
-        let rewards = ((asset_reward_rate - user_reward_rate)
-            * Decimal::from_atomics(user_staked, 0)?)
-        .to_uint_floor();
+        let accrued =
+            (asset_reward_rate - user_reward_rate) * Decimal::from_atomics(user_staked, 0)?;
+        let rewards = accrued.to_uint_floor();
         if rewards.is_zero() {
             Ok(Uint128::zero())
         } else {
             USER_ASSET_REWARD_RATE.save(storage, (user, &asset_info), &asset_reward_rate)?;
+            // The fraction left over is dropped along with the previous rate
+            deduct_reward_liabilities(storage, accrued - Decimal::from_atomics(rewards, 0)?)?;
             Ok(rewards)
         }
     } else {
@@ -425,33 +696,37 @@ fn alliance_delegate(
     env: Env,
     info: MessageInfo,
     msg: AllianceDelegateMsg,
-) -> Result<Response, ContractError> {
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    is_controller(&info, &config)?;
+    ensure_capability(deps.storage, &info.sender, Capability::Delegate)?;
+    ensure_not_paused(deps.storage, &PauseScope::Global {})?;
     if msg.delegations.is_empty() {
         return Err(ContractError::EmptyDelegation {});
     }
-    let mut validators = VALIDATORS.load(deps.storage)?;
-    let mut msgs: Vec<CosmosMsg<Empty>> = vec![];
+    let delegator = env.contract.address.as_str();
+    let denom = config
+        .staking_backend
+        .bond_denom(&config.alliance_token_denom);
+    let mut msgs = vec![];
+    let mut delegation_events = vec![];
     for delegation in msg.delegations {
-        let delegate_msg = MsgDelegate {
-            amount: Some(Coin {
-                denom: config.alliance_token_denom.clone(),
-                amount: delegation.amount.to_string(),
-            }),
-            delegator_address: env.contract.address.to_string(),
-            validator_address: delegation.validator.to_string(),
-        };
-        msgs.push(CosmosMsg::Stargate {
-            type_url: "/alliance.alliance.MsgDelegate".to_string(),
-            value: Binary::from(delegate_msg.encode_to_vec()),
-        });
-        validators.insert(delegation.validator);
-    }
-    VALIDATORS.save(deps.storage, &validators)?;
+        record_delegation(deps.storage, &delegation.validator, delegation.amount)?;
+        msgs.push(config.staking_backend.delegate(
+            delegator,
+            &denom,
+            &delegation.validator,
+            delegation.amount,
+        ));
+        delegation_events.push(events::delegation_changed(
+            deps.storage,
+            &RebalanceAction::Delegate(delegation),
+        )?);
+    }
+    let sub_msgs = staking_sub_msgs(deps, &env, &config, msgs)?;
     Ok(Response::new()
         .add_attributes(vec![("action", "alliance_delegate")])
-        .add_messages(msgs))
+        .add_submessages(sub_msgs)
+        .add_events(delegation_events))
 }
 
 fn alliance_undelegate(
@@ -459,31 +734,42 @@ fn alliance_undelegate(
     env: Env,
     info: MessageInfo,
     msg: AllianceUndelegateMsg,
-) -> Result<Response, ContractError> {
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    is_controller(&info, &config)?;
+    ensure_capability(deps.storage, &info.sender, Capability::Delegate)?;
+    ensure_not_paused(deps.storage, &PauseScope::Global {})?;
     if msg.undelegations.is_empty() {
         return Err(ContractError::EmptyDelegation {});
     }
+    let delegator = env.contract.address.as_str();
+    let denom = config
+        .staking_backend
+        .bond_denom(&config.alliance_token_denom);
     let mut msgs = vec![];
+    let mut delegation_events = vec![];
     for delegation in msg.undelegations {
-        let undelegate_msg = MsgUndelegate {
-            amount: Some(Coin {
-                denom: config.alliance_token_denom.clone(),
-                amount: delegation.amount.to_string(),
-            }),
-            delegator_address: env.contract.address.to_string(),
-            validator_address: delegation.validator.to_string(),
-        };
-        let msg = CosmosMsg::Stargate {
-            type_url: "/alliance.alliance.MsgUndelegate".to_string(),
-            value: Binary::from(undelegate_msg.encode_to_vec()),
-        };
-        msgs.push(msg);
+        record_undelegation(
+            deps.storage,
+            &delegation.validator,
+            delegation.amount,
+            env.block.time,
+        )?;
+        msgs.push(config.staking_backend.undelegate(
+            delegator,
+            &denom,
+            &delegation.validator,
+            delegation.amount,
+        ));
+        delegation_events.push(events::delegation_changed(
+            deps.storage,
+            &RebalanceAction::Undelegate(delegation),
+        )?);
     }
+    let sub_msgs = staking_sub_msgs(deps, &env, &config, msgs)?;
     Ok(Response::new()
         .add_attributes(vec![("action", "alliance_undelegate")])
-        .add_messages(msgs))
+        .add_submessages(sub_msgs)
+        .add_events(delegation_events))
 }
 
 fn alliance_redelegate(
@@ -491,98 +777,478 @@ fn alliance_redelegate(
     env: Env,
     info: MessageInfo,
     msg: AllianceRedelegateMsg,
-) -> Result<Response, ContractError> {
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    is_controller(&info, &config)?;
+    ensure_capability(deps.storage, &info.sender, Capability::Redelegate)?;
+    ensure_not_paused(deps.storage, &PauseScope::Global {})?;
     if msg.redelegations.is_empty() {
         return Err(ContractError::EmptyDelegation {});
     }
+    let delegator = env.contract.address.as_str();
+    let denom = config
+        .staking_backend
+        .bond_denom(&config.alliance_token_denom);
     let mut msgs = vec![];
-    let mut validators = VALIDATORS.load(deps.storage)?;
+    let mut delegation_events = vec![];
     for redelegation in msg.redelegations {
-        let src_validator = redelegation.src_validator;
-        let dst_validator = redelegation.dst_validator;
-        let redelegate_msg = MsgRedelegate {
-            amount: Some(Coin {
-                denom: config.alliance_token_denom.clone(),
-                amount: redelegation.amount.to_string(),
-            }),
-            delegator_address: env.contract.address.to_string(),
-            validator_src_address: src_validator.to_string(),
-            validator_dst_address: dst_validator.to_string(),
-        };
-        let msg = CosmosMsg::Stargate {
-            type_url: "/alliance.alliance.MsgRedelegate".to_string(),
-            value: Binary::from(redelegate_msg.encode_to_vec()),
-        };
-        msgs.push(msg);
-        validators.insert(dst_validator);
-    }
-    VALIDATORS.save(deps.storage, &validators)?;
+        record_redelegation(
+            deps.storage,
+            &redelegation.src_validator,
+            &redelegation.dst_validator,
+            redelegation.amount,
+            env.block.time,
+        )?;
+        record_redelegation_cooldown(deps.storage, &redelegation.dst_validator, env.block.time)?;
+        msgs.push(config.staking_backend.redelegate(
+            delegator,
+            &denom,
+            &redelegation.src_validator,
+            &redelegation.dst_validator,
+            redelegation.amount,
+        ));
+        delegation_events.push(events::delegation_changed(
+            deps.storage,
+            &RebalanceAction::Redelegate(redelegation),
+        )?);
+    }
+    let sub_msgs = staking_sub_msgs(deps, &env, &config, msgs)?;
     Ok(Response::new()
         .add_attributes(vec![("action", "alliance_redelegate")])
-        .add_messages(msgs))
+        .add_submessages(sub_msgs)
+        .add_events(delegation_events))
+}
+
+fn set_delegation_strategy(
+    deps: DepsMut,
+    info: MessageInfo,
+    strategy: DelegationStrategy,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    ensure_capability(deps.storage, &info.sender, Capability::Config)?;
+    validate_strategy(&strategy)?;
+    DELEGATION_STRATEGY.save(deps.storage, &strategy)?;
+    Ok(Response::new().add_attributes(vec![("action", "set_delegation_strategy")]))
 }
 
-fn update_rewards(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+fn rebalance_delegations(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
     let config = CONFIG.load(deps.storage)?;
+    // A rebalance can delegate and redelegate
+    ensure_capability(deps.storage, &info.sender, Capability::Delegate)?;
+    ensure_capability(deps.storage, &info.sender, Capability::Redelegate)?;
+    ensure_not_paused(deps.storage, &PauseScope::Global {})?;
+    let strategy = DELEGATION_STRATEGY
+        .may_load(deps.storage)?
+        .ok_or(ContractError::DelegationStrategyNotSet {})?;
+    let delegator = env.contract.address.as_str();
+    let denom = config
+        .staking_backend
+        .bond_denom(&config.alliance_token_denom);
+    let idle = deps.querier.query_balance(delegator, &denom)?.amount;
+    let actions = plan_rebalance(deps.storage, &strategy, idle, env.block.time)?;
 
-    let reward_sent_in_tx: Option<&CwCoin> =
-        info.funds.iter().find(|c| c.denom == config.reward_denom);
-    let sent_balance = if let Some(coin) = reward_sent_in_tx {
-        coin.amount
-    } else {
-        Uint128::zero()
-    };
-    let reward_asset = AssetInfo::native(config.reward_denom.clone());
-    let contract_balance =
-        reward_asset.query_balance(&deps.querier, env.contract.address.clone())?;
+    let mut msgs = vec![];
+    let mut delegation_events = vec![];
+    for action in actions {
+        match &action {
+            RebalanceAction::Delegate(delegation) => {
+                record_delegation(deps.storage, &delegation.validator, delegation.amount)?;
+                msgs.push(config.staking_backend.delegate(
+                    delegator,
+                    &denom,
+                    &delegation.validator,
+                    delegation.amount,
+                ));
+            }
+            RebalanceAction::Undelegate(delegation) => {
+                record_undelegation(
+                    deps.storage,
+                    &delegation.validator,
+                    delegation.amount,
+                    env.block.time,
+                )?;
+                msgs.push(config.staking_backend.undelegate(
+                    delegator,
+                    &denom,
+                    &delegation.validator,
+                    delegation.amount,
+                ));
+            }
+            RebalanceAction::Redelegate(redelegation) => {
+                record_redelegation(
+                    deps.storage,
+                    &redelegation.src_validator,
+                    &redelegation.dst_validator,
+                    redelegation.amount,
+                    env.block.time,
+                )?;
+                record_redelegation_cooldown(
+                    deps.storage,
+                    &redelegation.dst_validator,
+                    env.block.time,
+                )?;
+                msgs.push(config.staking_backend.redelegate(
+                    delegator,
+                    &denom,
+                    &redelegation.src_validator,
+                    &redelegation.dst_validator,
+                    redelegation.amount,
+                ));
+            }
+        }
+        delegation_events.push(events::delegation_changed(deps.storage, &action)?);
+    }
+    let actions = msgs.len().to_string();
+    let sub_msgs = staking_sub_msgs(deps, &env, &config, msgs)?;
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "rebalance_delegations"),
+            ("actions", &actions),
+        ])
+        .add_submessages(sub_msgs)
+        .add_events(delegation_events))
+}
+
+// x/distribution withdraws the rewards of a delegation whenever it changes. The reply of
+// each native staking message records the reward asset it brought in, so that the next
+// reward update distributes it instead of leaving it to be swept.
+fn staking_sub_msgs(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    msgs: Vec<CosmosMsg<CustomExecuteMsg>>,
+) -> Result<Vec<SubMsg<CustomExecuteMsg>>, ContractError> {
+    if !matches!(config.staking_backend, StakingBackend::Native { .. }) {
+        return Ok(msgs.into_iter().map(SubMsg::new).collect());
+    }
+    let balance = config
+        .reward_asset
+        .query_balance(&deps.querier, env.contract.address.clone())?;
+    TEMP_STAKING_BALANCE.save(deps.storage, &balance)?;
+    Ok(msgs
+        .into_iter()
+        .map(|msg| SubMsg::reply_on_success(msg, WITHDRAWN_REWARDS_REPLY_ID))
+        .collect())
+}
+
+// Excluded validators are never claimed again on their own, so the delegation managers
+// bring them back once the cause of the failures is solved
+fn reset_claim_failures(
+    deps: DepsMut,
+    info: MessageInfo,
+    validator: String,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    ensure_capability(deps.storage, &info.sender, Capability::Delegate)?;
+    CLAIM_FAILURES.remove(deps.storage, &validator);
+    Ok(Response::new().add_attributes(vec![
+        ("action", "reset_claim_failures"),
+        ("validator", &validator),
+    ]))
+}
+
+fn mint_alliance_tokens(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    is_governance(&info, &config)?;
+    if amount.is_zero() {
+        return Err(ContractError::AmountCannotBeZero {});
+    }
+    config.alliance_token_supply += amount;
+    CONFIG.save(deps.storage, &config)?;
+
+    let msg = config.token_factory_backend.mint(
+        env.contract.address.as_str(),
+        &config.alliance_token_denom,
+        amount,
+        env.contract.address.as_str(),
+    );
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "mint_alliance_tokens"),
+            ("amount", &amount.to_string()),
+            (
+                "alliance_token_total_supply",
+                &config.alliance_token_supply.to_string(),
+            ),
+        ])
+        .add_event(events::supply_changed(
+            "mint",
+            amount,
+            config.alliance_token_supply,
+        ))
+        .add_message(msg))
+}
+
+fn burn_alliance_tokens(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    is_governance(&info, &config)?;
+    if amount.is_zero() {
+        return Err(ContractError::AmountCannotBeZero {});
+    }
+    // Delegated and unbonding tokens are not held by the hub and must stay backed by the supply
+    let bonded = total_bonded(deps.storage, env.block.time)?;
+    config.alliance_token_supply = config
+        .alliance_token_supply
+        .checked_sub(amount)
+        .ok()
+        .filter(|supply| *supply >= bonded)
+        .ok_or_else(|| ContractError::BurnExceedsUndelegatedSupply(amount.to_string()))?;
+    CONFIG.save(deps.storage, &config)?;
+
+    let msg = config.token_factory_backend.burn(
+        env.contract.address.as_str(),
+        &config.alliance_token_denom,
+        amount,
+        env.contract.address.as_str(),
+    );
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "burn_alliance_tokens"),
+            ("amount", &amount.to_string()),
+            (
+                "alliance_token_total_supply",
+                &config.alliance_token_supply.to_string(),
+            ),
+        ])
+        .add_event(events::supply_changed(
+            "burn",
+            amount,
+            config.alliance_token_supply,
+        ))
+        .add_message(msg))
+}
+
+fn update_alliance_token_metadata(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    metadata: AllianceTokenMetadata,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    is_governance(&info, &config)?;
+    validate_token_metadata(&metadata)?;
+    ALLIANCE_TOKEN_METADATA.save(deps.storage, &metadata)?;
+
+    let msg = config.token_factory_backend.set_metadata(
+        env.contract.address.as_str(),
+        &config.alliance_token_denom,
+        Metadata::new(&config.alliance_token_denom, metadata),
+    );
+    Ok(Response::new()
+        .add_attributes(vec![("action", "update_alliance_token_metadata")])
+        .add_message(msg))
+}
+
+// `sent_balance` is the amount of reward asset sent along with the update, which is
+// already part of the hub balance and distributed with the collected rewards.
+fn update_rewards(
+    deps: DepsMut,
+    env: Env,
+    keeper: Addr,
+    sent_balance: Uint128,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    ensure_not_paused(deps.storage, &PauseScope::RewardUpdates {})?;
+    let config = CONFIG.load(deps.storage)?;
+    let next_update = next_reward_update(&config);
+    if env.block.time < next_update {
+        return Err(ContractError::RewardUpdateTooSoon(next_update));
+    }
+
+    let response = harvest_rewards(deps, env, &config, sent_balance, Some(keeper))?;
+    Ok(response.add_attributes(vec![("action", "update_rewards")]))
+}
+
+fn next_reward_update(config: &Config) -> Timestamp {
+    config
+        .last_reward_update_timestamp
+        .plus_seconds(config.reward_update_interval)
+}
+
+// Claims the rewards of every delegation and schedules `UpdateRewardsCallback`, which
+// distributes the claimed amount and pays the bounty of the `keeper`, if any.
+fn harvest_rewards(
+    deps: DepsMut,
+    env: Env,
+    config: &Config,
+    sent_balance: Uint128,
+    keeper: Option<Addr>,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let contract_balance = config
+        .reward_asset
+        .query_balance(&deps.querier, env.contract.address.clone())?;
 
     // Contract balance is guaranteed to be greater than sent balance
     // since contract balance = previous contract balance + sent balance > sent balance
     TEMP_BALANCE.save(deps.storage, &(contract_balance - sent_balance))?;
-    let validators = VALIDATORS.load(deps.storage)?;
-    let sub_msgs: Vec<SubMsg> = validators
+    match keeper {
+        Some(keeper) => TEMP_KEEPER.save(deps.storage, &keeper)?,
+        None => TEMP_KEEPER.remove(deps.storage),
+    }
+    let mut validators = vec![];
+    for validator in delegated_validators(deps.storage, env.block.time)? {
+        if !is_claim_excluded(deps.storage, &validator)? {
+            PENDING_CLAIMS.push_back(deps.storage, &validator)?;
+            validators.push(validator);
+        }
+    }
+    let denom = config
+        .staking_backend
+        .bond_denom(&config.alliance_token_denom);
+    let sub_msgs: Vec<SubMsg<CustomExecuteMsg>> = validators
         .iter()
         .map(|v| {
-            let msg = MsgClaimDelegationRewards {
-                delegator_address: env.contract.address.to_string(),
-                validator_address: v.to_string(),
-                denom: config.alliance_token_denom.clone(),
-            };
-            let msg = CosmosMsg::Stargate {
-                type_url: "/alliance.alliance.MsgClaimDelegationRewards".to_string(),
-                value: Binary::from(msg.encode_to_vec()),
-            };
-            // Reply on error here is used to ignore errors from claiming rewards with validators that we did not delegate to
-            SubMsg::reply_on_error(msg, CLAIM_REWARD_ERROR_REPLY_ID)
+            let msg =
+                config
+                    .staking_backend
+                    .claim_rewards(env.contract.address.as_str(), &denom, v);
+            // Failed claims are recorded in the reply instead of reverting the reward update
+            SubMsg::reply_always(msg, CLAIM_REWARD_REPLY_ID)
         })
         .collect();
     let msg = CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: env.contract.address.to_string(),
-        msg: to_json_binary(&ExecuteMsg::UpdateRewardsCallback {}).unwrap(),
+        msg: to_json_binary(&ExecuteMsg::UpdateRewardsCallback {})?,
         funds: vec![],
     });
 
-    Ok(Response::new()
-        .add_attributes(vec![("action", "update_rewards")])
-        .add_submessages(sub_msgs)
-        .add_message(msg))
+    Ok(Response::new().add_submessages(sub_msgs).add_message(msg))
+}
+
+fn update_rewards_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    reward_update_interval: Option<u64>,
+    keeper_bounty: Option<Decimal>,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    ensure_capability(deps.storage, &info.sender, Capability::Config)?;
+    let mut config = CONFIG.load(deps.storage)?;
+    if let Some(reward_update_interval) = reward_update_interval {
+        config.reward_update_interval = reward_update_interval;
+    }
+    if let Some(keeper_bounty) = keeper_bounty {
+        if keeper_bounty > MAX_KEEPER_BOUNTY {
+            return Err(ContractError::KeeperBountyTooHigh(MAX_KEEPER_BOUNTY));
+        }
+        config.keeper_bounty = keeper_bounty;
+    }
+    CONFIG.save(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_rewards_config"),
+        (
+            "reward_update_interval",
+            &config.reward_update_interval.to_string(),
+        ),
+        ("keeper_bounty", &config.keeper_bounty.to_string()),
+    ]))
+}
+
+// Accrued rewards are not converted, so the asset only changes once the stakers claimed
+// every whole unit owed to them and withdrawn rewards were distributed. Fractions left on
+// the positions are paid in the new asset.
+fn update_reward_asset(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: AssetInfo,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    is_governance(&info, &config)?;
+    let liabilities = REWARD_LIABILITIES
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .to_uint_floor()
+        + WITHDRAWN_REWARDS
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+    if !liabilities.is_zero() {
+        return Err(ContractError::RewardLiabilitiesOutstanding(liabilities));
+    }
+    config.reward_asset = validate_reward_asset(&deps, asset)?;
+    validate_bond_denom(deps.storage, &config)?;
+    CONFIG.save(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_reward_asset"),
+        ("reward_asset", &config.reward_asset.to_string()),
+    ]))
+}
+
+fn validate_reward_asset(deps: &DepsMut, asset: AssetInfo) -> Result<AssetInfo, ContractError> {
+    match asset {
+        AssetInfo::Native(denom) if !denom.is_empty() => Ok(AssetInfo::Native(denom)),
+        AssetInfo::Cw20(address) => Ok(AssetInfo::Cw20(deps.api.addr_validate(address.as_str())?)),
+        asset => Err(ContractError::InvalidRewardAsset(asset.to_string())),
+    }
+}
+
+fn update_voting_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    dao: Option<String>,
+    asset_weights: Option<Vec<AssetVotingWeight>>,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    is_governance(&info, &config)?;
+    let mut attrs = vec![("action".to_string(), "update_voting_config".to_string())];
+    if let Some(dao) = dao {
+        let dao = deps.api.addr_validate(&dao)?;
+        VOTING_DAO.save(deps.storage, &dao)?;
+        attrs.push(("dao".to_string(), dao.to_string()));
+    }
+    if let Some(asset_weights) = asset_weights {
+        validate_asset_weights(deps.storage, &asset_weights)?;
+        VOTING_WEIGHTS.save(deps.storage, &asset_weights, env.block.height)?;
+        for asset_weight in asset_weights {
+            attrs.push((
+                asset_weight.asset.to_string(),
+                asset_weight.weight.to_string(),
+            ));
+        }
+    }
+    Ok(Response::new().add_attributes(attrs))
 }
 
 fn update_reward_callback(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-) -> Result<Response, ContractError> {
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
     if info.sender != env.contract.address {
         return Err(ContractError::Unauthorized {});
     }
-    let config = CONFIG.load(deps.storage)?;
-    let reward_asset = AssetInfo::native(config.reward_denom);
-    let current_balance = reward_asset.query_balance(&deps.querier, env.contract.address)?;
+    let mut config = CONFIG.load(deps.storage)?;
+    let current_balance = config
+        .reward_asset
+        .query_balance(&deps.querier, env.contract.address.clone())?;
     let previous_balance = TEMP_BALANCE.load(deps.storage)?;
-    let rewards_collected = current_balance - previous_balance;
+    // Updates without a keeper, like the ones run by the chain, pay no bounty
+    let keeper = TEMP_KEEPER.may_load(deps.storage)?;
+    // Rewards withdrawn by native delegation changes since the last update are already
+    // part of the previous balance
+    let withdrawn_rewards = WITHDRAWN_REWARDS
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let collected = current_balance.checked_sub(previous_balance).map_err(|_| {
+        ContractError::RewardBalanceDecreased {
+            previous: previous_balance,
+            current: current_balance,
+        }
+    })? + withdrawn_rewards;
+    let keeper_bounty = match keeper {
+        Some(_) => collected.mul_floor(config.keeper_bounty),
+        None => Uint128::zero(),
+    };
+    let rewards_collected = collected - keeper_bounty;
 
     let asset_reward_distribution = ASSET_REWARD_DISTRIBUTION.load(deps.storage)?;
     let total_distribution = asset_reward_distribution
@@ -590,6 +1256,7 @@ fn update_reward_callback(
         .map(|a| a.distribution)
         .fold(Decimal::zero(), |acc, v| acc + v);
 
+    let mut distributed_events = vec![];
     for asset_distribution in asset_reward_distribution {
         let total_reward_distributed = Decimal::from_atomics(rewards_collected, 0)?
             * asset_distribution.distribution
@@ -603,59 +1270,342 @@ fn update_reward_callback(
             let rate_to_update =
                 total_reward_distributed / Decimal::from_atomics(total_balance, 0)?;
             if rate_to_update > Decimal::zero() {
-                ASSET_REWARD_RATE.update(
+                add_reward_liabilities(
+                    deps.storage,
+                    rate_to_update * Decimal::from_atomics(total_balance, 0)?,
+                )?;
+                let reward_index = ASSET_REWARD_RATE.update(
                     deps.storage,
                     &asset_distribution.asset,
                     |rate| -> StdResult<_> { Ok(rate.unwrap_or(Decimal::zero()) + rate_to_update) },
                 )?;
+                distributed_events.push(events::rewards_distributed(
+                    &asset_distribution.asset,
+                    &config.reward_asset,
+                    total_reward_distributed.to_uint_floor(),
+                    rate_to_update,
+                    reward_index,
+                ));
             }
         }
     }
     TEMP_BALANCE.remove(deps.storage);
+    TEMP_KEEPER.remove(deps.storage);
+    WITHDRAWN_REWARDS.remove(deps.storage);
+    config.last_reward_update_timestamp = env.block.time;
+    CONFIG.save(deps.storage, &config)?;
 
-    Ok(Response::new().add_attributes(vec![("action", "update_rewards_callback")]))
+    let response = Response::new()
+        .add_attributes(vec![("action", "update_rewards_callback")])
+        .add_events(distributed_events);
+    match keeper {
+        Some(keeper) if !keeper_bounty.is_zero() => {
+            let bounty = Asset::new(config.reward_asset, keeper_bounty);
+            Ok(response
+                .add_attributes(vec![
+                    ("keeper", keeper.as_str()),
+                    ("keeper_bounty", &keeper_bounty.to_string()),
+                ])
+                .add_event(events::keeper_bounty(&keeper, &bounty.info, keeper_bounty))
+                .add_message(transfer_msg(&bounty, &keeper)?))
+        }
+        _ => Ok(response),
+    }
 }
 
-fn update_config(
+// Roles are never swapped in a single message: the current holder (or governance)
+// proposes a new address which only takes effect once that address accepts it.
+fn propose_role_transfer(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    governance: Option<String>,
-    controller: Option<String>,
-    oracle: Option<String>,
-    operator: Option<String>,
-) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
-    ensure!(
-        info.sender == config.governance,
-        ContractError::Unauthorized {}
-    );
+    role: Role,
+    new_address: String,
+    expires_in: u64,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    can_manage_role(deps.storage, &info, &config, role)?;
+    if expires_in == 0 {
+        return Err(ContractError::InvalidRoleTransferExpiry {});
+    }
+    let new_address = deps.api.addr_validate(&new_address)?;
+    let expiry = env.block.time.plus_seconds(expires_in);
+
+    PENDING_ROLE_TRANSFERS.save(
+        deps.storage,
+        role.as_str(),
+        &PendingRoleTransfer {
+            role,
+            proposer: info.sender,
+            new_address: new_address.clone(),
+            expiry,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "propose_role_transfer"),
+            ("role", role.as_str()),
+            ("new_address", new_address.as_str()),
+            ("expiry", &expiry.seconds().to_string()),
+        ])
+        .add_event(events::role_transfer_proposed(role, &new_address, expiry)))
+}
 
-    if let Some(governance) = governance {
-        config.governance = deps.api.addr_validate(&governance)?;
+fn accept_role_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    role: Role,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let transfer = PENDING_ROLE_TRANSFERS
+        .may_load(deps.storage, role.as_str())?
+        .ok_or_else(|| ContractError::NoPendingRoleTransfer(role.to_string()))?;
+    if info.sender != transfer.new_address {
+        return Err(ContractError::Unauthorized {});
     }
+    if env.block.time > transfer.expiry {
+        return Err(ContractError::RoleTransferExpired(role.to_string()));
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let previous_address = config.role(role).clone();
+    config.set_role(role, transfer.new_address.clone());
+    CONFIG.save(deps.storage, &config)?;
+    transfer_role_capabilities(deps.storage, &config, role, &previous_address)?;
+    PENDING_ROLE_TRANSFERS.remove(deps.storage, role.as_str());
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "accept_role_transfer"),
+            ("role", role.as_str()),
+            ("previous_address", previous_address.as_str()),
+            ("new_address", transfer.new_address.as_str()),
+        ])
+        .add_event(events::role_transfer_accepted(
+            role,
+            &previous_address,
+            &transfer.new_address,
+        )))
+}
 
-    if let Some(controller) = controller {
-        config.controller = deps.api.addr_validate(&controller)?;
+fn cancel_role_transfer(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    can_manage_role(deps.storage, &info, &config, role)?;
+    if !PENDING_ROLE_TRANSFERS.has(deps.storage, role.as_str()) {
+        return Err(ContractError::NoPendingRoleTransfer(role.to_string()));
     }
+    PENDING_ROLE_TRANSFERS.remove(deps.storage, role.as_str());
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "cancel_role_transfer"),
+            ("role", role.as_str()),
+        ])
+        .add_event(events::role_transfer_cancelled(role)))
+}
+
+fn grant(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    capability: Capability,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    is_governance(&info, &config)?;
+    let address = deps.api.addr_validate(&address)?;
+    grant_capability(deps.storage, &address, capability)?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "grant_capability"),
+            ("address", address.as_str()),
+            ("capability", capability.as_str()),
+        ])
+        .add_event(events::capability_changed(&address, capability, true)))
+}
+
+fn revoke(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    capability: Capability,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    is_governance(&info, &config)?;
+    let address = deps.api.addr_validate(&address)?;
+    revoke_capability(deps.storage, &address, capability);
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "revoke_capability"),
+            ("address", address.as_str()),
+            ("capability", capability.as_str()),
+        ])
+        .add_event(events::capability_changed(&address, capability, false)))
+}
+
+fn add_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    addr: String,
+    gas_limit: u64,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    is_governance(&info, &config)?;
+    let hook = deps.api.addr_validate(&addr)?;
+    hooks::add_hook(deps.storage, &hook, gas_limit)?;
+    Ok(Response::new().add_attributes(vec![
+        ("action", "add_hook"),
+        ("hook", hook.as_str()),
+        ("gas_limit", &gas_limit.to_string()),
+    ]))
+}
+
+fn remove_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    addr: String,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    is_governance(&info, &config)?;
+    let hook = deps.api.addr_validate(&addr)?;
+    hooks::remove_hook(deps.storage, &hook)?;
+    Ok(Response::new().add_attributes(vec![("action", "remove_hook"), ("hook", hook.as_str())]))
+}
 
-    if let Some(oracle) = oracle {
-        config.oracle = deps.api.addr_validate(&oracle)?;
+// Recovers funds sent to the hub by mistake. The alliance token and the bond denom are
+// held for delegations, so they are never swept.
+fn sweep(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: AssetInfo,
+    recipient: String,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    ensure_capability(deps.storage, &info.sender, Capability::Sweep)?;
+    let config = CONFIG.load(deps.storage)?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let bond_denom = config
+        .staking_backend
+        .bond_denom(&config.alliance_token_denom);
+    if asset == AssetInfo::native(&config.alliance_token_denom)
+        || asset == AssetInfo::native(&bond_denom)
+    {
+        return Err(ContractError::CannotSweepAsset(asset.to_string()));
     }
 
-    if let Some(operator) = operator {
-        config.operator = deps.api.addr_validate(&operator)?;
+    let held = asset.query_balance(&deps.querier, &env.contract.address)?;
+    let owed = owed_amount(deps.storage, &asset)?;
+    let surplus = held.saturating_sub(owed);
+    if surplus.is_zero() {
+        return Err(ContractError::NothingToSweep(asset.to_string()));
     }
+    let swept = Asset::new(asset, surplus);
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "sweep"),
+            ("asset", &swept.info.to_string()),
+            ("amount", &surplus.to_string()),
+            ("recipient", recipient.as_str()),
+        ])
+        .add_event(events::sweep(&swept.info, surplus, &recipient))
+        .add_message(transfer_msg(&swept, &recipient)?))
+}
 
-    CONFIG.save(deps.storage, &config)?;
+// Holders of the pause capability can halt a scope of the hub, only governance can resume it
+fn pause(
+    deps: DepsMut,
+    info: MessageInfo,
+    scope: PauseScope,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    ensure_capability(deps.storage, &info.sender, Capability::Pause)?;
+    set_paused(deps.storage, &scope, true)?;
 
-    Ok(Response::new().add_attributes(vec![("action", "update_config")]))
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "pause".to_string()),
+            ("scope", scope.to_string()),
+        ])
+        .add_event(events::pause_changed(&scope, true)))
+}
+
+fn unpause(
+    deps: DepsMut,
+    info: MessageInfo,
+    scope: PauseScope,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    is_governance(&info, &config)?;
+    set_paused(deps.storage, &scope, false)?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "unpause".to_string()),
+            ("scope", scope.to_string()),
+        ])
+        .add_event(events::pause_changed(&scope, false)))
+}
+
+fn set_paused(
+    storage: &mut dyn Storage,
+    scope: &PauseScope,
+    paused: bool,
+) -> Result<(), ContractError> {
+    let asset = match scope {
+        PauseScope::Global {} => return Ok(GLOBAL_PAUSE.save(storage, &paused)?),
+        PauseScope::RewardUpdates {} => return Ok(REWARD_UPDATES_PAUSE.save(storage, &paused)?),
+        PauseScope::Stake(asset) | PauseScope::Unstake(asset) | PauseScope::ClaimRewards(asset) => {
+            asset
+        }
+    };
+    let mut actions = ASSET_PAUSES.may_load(storage, asset)?.unwrap_or_default();
+    match scope {
+        PauseScope::Stake(_) => actions.stake = paused,
+        PauseScope::Unstake(_) => actions.unstake = paused,
+        PauseScope::ClaimRewards(_) => actions.claim_rewards = paused,
+        _ => {}
+    }
+    if actions.is_empty() {
+        ASSET_PAUSES.remove(storage, asset);
+    } else {
+        ASSET_PAUSES.save(storage, asset, &actions)?;
+    }
+    Ok(())
+}
+
+// A global pause takes precedence over any other scope
+fn ensure_not_paused(storage: &dyn Storage, scope: &PauseScope) -> Result<(), ContractError> {
+    if GLOBAL_PAUSE.may_load(storage)?.unwrap_or(false) {
+        return Err(ContractError::Paused(PauseScope::Global {}.to_string()));
+    }
+    let paused = match scope {
+        PauseScope::Global {} => false,
+        PauseScope::RewardUpdates {} => REWARD_UPDATES_PAUSE.may_load(storage)?.unwrap_or(false),
+        PauseScope::Stake(asset) | PauseScope::Unstake(asset) | PauseScope::ClaimRewards(asset) => {
+            let actions = ASSET_PAUSES.may_load(storage, asset)?.unwrap_or_default();
+            match scope {
+                PauseScope::Stake(_) => actions.stake,
+                PauseScope::Unstake(_) => actions.unstake,
+                _ => actions.claim_rewards,
+            }
+        }
+    };
+    if paused {
+        return Err(ContractError::Paused(scope.to_string()));
+    }
+    Ok(())
 }
 
 // fn rebalance_emissions(
 //     deps: DepsMut,
 //     env: Env,
 //     info: MessageInfo,
-// ) -> Result<Response, ContractError> {
+// ) -> Result<Response<CustomExecuteMsg>, ContractError> {
 //     // Allow execution only from the controller account
 //     let config = CONFIG.load(deps.storage)?;
 //     is_controller(&info, &config)?;
@@ -679,7 +1629,7 @@ fn update_config(
 //     deps: DepsMut,
 //     env: Env,
 //     info: MessageInfo,
-// ) -> Result<Response, ContractError> {
+// ) -> Result<Response<CustomExecuteMsg>, ContractError> {
 //     if info.sender != env.contract.address {
 //         return Err(ContractError::Unauthorized {});
 //     }
@@ -741,6 +1691,109 @@ fn update_config(
 //     Ok(Response::new().add_attributes(attrs))
 // }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(
+    deps: DepsMut,
+    env: Env,
+    msg: SudoMsg,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    match msg {
+        SudoMsg::EndBlock {} | SudoMsg::ClockEndBlock {} => end_block(deps, env),
+        SudoMsg::IbcLifecycleComplete(complete) => ibc_lifecycle_complete(deps, env, complete),
+    }
+}
+
+// Failed transfers go back to where the funds came from: principal to the staked balance
+// and rewards to the unclaimed rewards, so the user can withdraw them again.
+fn ibc_lifecycle_complete(
+    deps: DepsMut,
+    env: Env,
+    complete: IbcLifecycleComplete,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let (channel, sequence, result) = match complete {
+        IbcLifecycleComplete::IbcAck {
+            channel,
+            sequence,
+            success,
+            ..
+        } => (channel, sequence, if success { "ack" } else { "error" }),
+        IbcLifecycleComplete::IbcTimeout { channel, sequence } => (channel, sequence, "timeout"),
+    };
+    let transfer = IBC_TRANSFERS.load(deps.storage, (&channel, sequence))?;
+    IBC_TRANSFERS.remove(deps.storage, (&channel, sequence));
+
+    let response = Response::new().add_attributes(vec![
+        ("action", "ibc_lifecycle_complete"),
+        ("channel_id", &channel),
+        ("sequence", &sequence.to_string()),
+        ("result", result),
+    ]);
+    if result == "ack" {
+        return Ok(response);
+    }
+    let mut hooks = vec![];
+    match transfer.kind {
+        IbcTransferKind::Unstake => {
+            let (balance, _) = add_balance(
+                deps.storage,
+                env.block.height,
+                transfer.user.clone(),
+                &transfer.asset,
+                transfer.amount,
+            )?;
+            hooks = stake_changed_hooks(
+                deps.storage,
+                &transfer.user,
+                &transfer.asset,
+                balance - transfer.amount,
+                balance,
+                STAKE_HOOK_REPLY_ID,
+            )?;
+        }
+        IbcTransferKind::ClaimRewards => {
+            UNCLAIMED_REWARDS.update(
+                deps.storage,
+                (transfer.user.clone(), &transfer.asset),
+                |balance| -> Result<_, ContractError> {
+                    Ok(balance.unwrap_or(Uint128::zero()) + transfer.amount)
+                },
+            )?;
+            add_reward_liabilities(deps.storage, Decimal::from_atomics(transfer.amount, 0)?)?;
+        }
+    }
+    Ok(response
+        .add_attributes(vec![
+            ("refund_user", transfer.user.as_str()),
+            ("refund_amount", &transfer.amount.to_string()),
+        ])
+        .add_event(events::ibc_refund(
+            &transfer.user,
+            &transfer.asset,
+            transfer.amount,
+            &transfer.kind,
+        ))
+        .add_submessages(hooks))
+}
+
+// Harvests the rewards when an update is due. The chain scheduler treats errors as
+// faults, so updates that cannot run yet are skipped with an empty response instead.
+// The callback is dispatched as a message from the hub, like a keeper triggered update.
+fn end_block(deps: DepsMut, env: Env) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let due = ensure_not_paused(deps.storage, &PauseScope::RewardUpdates {}).is_ok()
+        && env.block.time >= next_reward_update(&config)
+        && ASSET_REWARD_DISTRIBUTION.may_load(deps.storage)?.is_some();
+    if !due {
+        return Ok(Response::new()
+            .add_attributes(vec![("action", "end_block"), ("reward_update", "skipped")]));
+    }
+    let response = harvest_rewards(deps, env, &config, Uint128::zero(), None)?;
+    Ok(response.add_attributes(vec![
+        ("action", "end_block"),
+        ("reward_update", "scheduled"),
+    ]))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(
     deps: DepsMut,
@@ -749,43 +1802,29 @@ pub fn reply(
 ) -> Result<Response<CustomExecuteMsg>, ContractError> {
     match reply.id {
         CREATE_REPLY_ID => {
-            let response = reply.result.unwrap();
-            // It works because the response data is a protobuf encoded string that contains the denom in the first slot (similar to the contract instantiation response)
-            let denom = parse_instantiate_response_data(response.data.unwrap().as_slice())
-                .map_err(|_| ContractError::Std(StdError::generic_err("parse error".to_string())))?
-                .contract_address;
+            let response = reply
+                .result
+                .into_result()
+                .map_err(ContractError::InvalidCreateDenomReply)?;
+            let mut config = CONFIG.load(deps.storage)?;
+            let backend = config.token_factory_backend.clone();
+            let denom = backend.parse_create_denom_response(response.data)?;
             let total_supply = Uint128::from(1_000_000_000_000_u128);
-            let sub_msg_mint = SubMsg::new(CosmosMsg::Custom(CustomExecuteMsg::Token(
-                TokenExecuteMsg::MintTokens {
-                    denom: denom.clone(),
-                    amount: total_supply,
-                    mint_to_address: env.contract.address.to_string(),
-                },
-            )));
-            CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
-                config.alliance_token_denom = denom.clone();
-                config.alliance_token_supply = total_supply;
-                Ok(config)
-            })?;
-            let symbol = "ALLIANCE";
-
-            let sub_msg_metadata = SubMsg::new(CosmosMsg::Custom(CustomExecuteMsg::Token(
-                TokenExecuteMsg::SetMetadata {
-                    denom: denom.clone(),
-                    metadata: Metadata {
-                        description: "Staking token for the alliance protocol".to_string(),
-                        denom_units: vec![DenomUnit {
-                            denom: denom.clone(),
-                            exponent: 0,
-                            aliases: vec![],
-                        }],
-                        base: denom.to_string(),
-                        display: denom.to_string(),
-                        name: "Alliance Token".to_string(),
-                        symbol: symbol.to_string(),
-                    },
-                },
-            )));
+            config.alliance_token_denom = denom.clone();
+            config.alliance_token_supply = total_supply;
+            CONFIG.save(deps.storage, &config)?;
+
+            let contract_address = env.contract.address.as_str();
+            let sub_msg_mint =
+                SubMsg::new(backend.mint(contract_address, &denom, total_supply, contract_address));
+            let metadata = ALLIANCE_TOKEN_METADATA
+                .may_load(deps.storage)?
+                .unwrap_or_default();
+            let sub_msg_metadata = SubMsg::new(backend.set_metadata(
+                contract_address,
+                &denom,
+                Metadata::new(&denom, metadata),
+            ));
             Ok(Response::new()
                 .add_attributes(vec![
                     ("alliance_token_denom", denom),
@@ -794,23 +1833,130 @@ pub fn reply(
                 .add_submessage(sub_msg_mint)
                 .add_submessage(sub_msg_metadata))
         }
-        CLAIM_REWARD_ERROR_REPLY_ID => {
-            Ok(Response::new().add_attributes(vec![("action", "claim_reward_error")]))
+        CLAIM_REWARD_REPLY_ID => {
+            let validator = PENDING_CLAIMS
+                .pop_front(deps.storage)?
+                .ok_or_else(|| StdError::generic_err("no pending reward claim"))?;
+            match reply.result {
+                SubMsgResult::Ok(_) => {
+                    CLAIM_FAILURES.remove(deps.storage, &validator);
+                    Ok(Response::new())
+                }
+                SubMsgResult::Err(error) => {
+                    let failure = record_claim_failure(deps.storage, &validator, error)?;
+                    Ok(Response::new().add_attributes(vec![
+                        ("action", "claim_reward_error"),
+                        ("validator", &validator),
+                        ("error", &failure.last_error),
+                        (
+                            "consecutive_failures",
+                            &failure.consecutive_failures.to_string(),
+                        ),
+                        ("excluded", &failure.excluded.to_string()),
+                    ]))
+                }
+            }
         }
+        IBC_TRANSFER_REPLY_ID => {
+            let response = reply
+                .result
+                .into_result()
+                .map_err(ContractError::InvalidIbcTransferReply)?;
+            let sequence = parse_ibc_transfer_sequence(response.data)?;
+            let (channel, transfer) = TEMP_IBC_TRANSFER.load(deps.storage)?;
+            TEMP_IBC_TRANSFER.remove(deps.storage);
+            IBC_TRANSFERS.save(deps.storage, (&channel, sequence), &transfer)?;
+            Ok(Response::new().add_attributes(vec![
+                ("action", "ibc_transfer"),
+                ("channel_id", &channel),
+                ("sequence", &sequence.to_string()),
+            ]))
+        }
+        WITHDRAWN_REWARDS_REPLY_ID => {
+            let config = CONFIG.load(deps.storage)?;
+            let balance = config
+                .reward_asset
+                .query_balance(&deps.querier, env.contract.address)?;
+            // The bond denom is never the reward asset, so the staking message itself
+            // cannot lower the balance
+            let withdrawn = balance.saturating_sub(TEMP_STAKING_BALANCE.load(deps.storage)?);
+            TEMP_STAKING_BALANCE.save(deps.storage, &balance)?;
+            if withdrawn.is_zero() {
+                return Ok(Response::new());
+            }
+            let total = WITHDRAWN_REWARDS
+                .may_load(deps.storage)?
+                .unwrap_or_default();
+            WITHDRAWN_REWARDS.save(deps.storage, &(total + withdrawn))?;
+            Ok(Response::new()
+                .add_attributes(vec![
+                    ("action", "withdrawn_rewards"),
+                    ("amount", &withdrawn.to_string()),
+                ])
+                .add_event(events::rewards_withdrawn(&config.reward_asset, withdrawn)))
+        }
+        // Failed hooks are reverted on their own and do not block the balance change
+        STAKE_HOOK_REPLY_ID => match reply.result {
+            SubMsgResult::Ok(_) => Ok(Response::new()),
+            SubMsgResult::Err(error) => Ok(Response::new()
+                .add_attributes(vec![("action", "stake_hook_error"), ("error", &error)])),
+        },
         _ => Err(ContractError::InvalidReplyId(reply.id)),
     }
 }
 
-// Controller is used to perform administrative operations that deals with delegating the virtual
-// tokens to the expected validators
-fn is_controller(info: &MessageInfo, config: &Config) -> Result<(), ContractError> {
-    if info.sender != config.controller {
-        return Err(ContractError::Unauthorized {});
+// cw-asset only builds bank and wasm messages, which are re-typed for the custom response
+fn transfer_msg(
+    asset: &Asset,
+    recipient: &Addr,
+) -> Result<CosmosMsg<CustomExecuteMsg>, ContractError> {
+    match asset.transfer_msg(recipient)? {
+        CosmosMsg::Bank(msg) => Ok(CosmosMsg::Bank(msg)),
+        CosmosMsg::Wasm(msg) => Ok(CosmosMsg::Wasm(msg)),
+        _ => Err(ContractError::Std(StdError::generic_err(
+            "unsupported transfer message",
+        ))),
+    }
+}
+
+// The base unit is added when building the bank metadata, so the additional units must
+// use a positive exponent and `display` must name one of them.
+fn validate_token_metadata(metadata: &AllianceTokenMetadata) -> Result<(), ContractError> {
+    if metadata.name.is_empty() || metadata.symbol.is_empty() {
+        return Err(ContractError::InvalidAllianceTokenMetadata(
+            "name and symbol cannot be empty".to_string(),
+        ));
+    }
+    let mut denoms = HashSet::new();
+    for unit in &metadata.denom_units {
+        if unit.exponent == 0 || !denoms.insert(unit.denom.as_str()) {
+            return Err(ContractError::InvalidAllianceTokenMetadata(format!(
+                "invalid denom unit {}",
+                unit.denom
+            )));
+        }
+    }
+    if let Some(display) = &metadata.display {
+        if !denoms.contains(display.as_str()) {
+            return Err(ContractError::InvalidAllianceTokenMetadata(format!(
+                "display {} is not a denom unit",
+                display
+            )));
+        }
+    }
+    Ok(())
+}
+
+// ibc-hooks attaches the transferred funds to every call it makes, which would otherwise
+// stay in the hub without being credited to anyone
+fn ensure_no_funds(info: &MessageInfo) -> Result<(), ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::FundsNotAccepted {});
     }
     Ok(())
 }
 
-// Only governance (through a on-chain prop) can change the whitelisted assets
+// Only governance (through a on-chain prop) can manage the permission registry
 fn is_governance(info: &MessageInfo, config: &Config) -> Result<(), ContractError> {
     if info.sender != config.governance {
         return Err(ContractError::Unauthorized {});
@@ -818,11 +1964,19 @@ fn is_governance(info: &MessageInfo, config: &Config) -> Result<(), ContractErro
     Ok(())
 }
 
-// Only governance or the operator can pass through this function
-fn is_authorized(info: &MessageInfo, config: &Config) -> Result<(), ContractError> {
-    ensure!(
-        info.sender == config.governance || info.sender == config.operator,
-        ContractError::Unauthorized {}
-    );
-    Ok(())
+// The current holder of a role can hand it over. Holders of the config capability can
+// reassign every role except governance, which only governance itself can hand over.
+fn can_manage_role(
+    storage: &dyn Storage,
+    info: &MessageInfo,
+    config: &Config,
+    role: Role,
+) -> Result<(), ContractError> {
+    if info.sender == config.role(role) {
+        return Ok(());
+    }
+    if role != Role::Governance && has_capability(storage, &info.sender, Capability::Config)? {
+        return Ok(());
+    }
+    Err(ContractError::Unauthorized {})
 }
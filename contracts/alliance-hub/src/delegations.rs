@@ -0,0 +1,267 @@
+use std::collections::{BTreeMap, HashSet};
+
+use alliance_protocol::alliance_protocol::{
+    AllianceDelegation, AllianceRedelegation, ClaimFailure, DelegationStrategy, RebalanceAction,
+    Unbonding, ValidatorDelegation,
+};
+use cosmwasm_std::{Decimal, Order, StdResult, Storage, Timestamp, Uint128};
+
+use crate::error::ContractError;
+use crate::state::{CLAIM_FAILURES, CONFIG, DELEGATIONS, REDELEGATION_COOLDOWNS};
+
+// Consecutive failed reward claims after which a validator is excluded from reward updates
+pub(crate) const MAX_CONSECUTIVE_CLAIM_FAILURES: u32 = 3;
+
+pub(crate) fn record_delegation(
+    storage: &mut dyn Storage,
+    validator: &str,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let mut delegation = DELEGATIONS
+        .may_load(storage, validator)?
+        .unwrap_or_else(|| ValidatorDelegation::new(validator.to_string()));
+    delegation.delegated += amount;
+    DELEGATIONS.save(storage, validator, &delegation)?;
+    CLAIM_FAILURES.remove(storage, validator);
+    Ok(())
+}
+
+pub(crate) fn record_undelegation(
+    storage: &mut dyn Storage,
+    validator: &str,
+    amount: Uint128,
+    now: Timestamp,
+) -> Result<(), ContractError> {
+    let mut delegation = withdraw(storage, validator, amount)?;
+    delegation
+        .undelegating
+        .push(unbonding(storage, amount, now)?);
+    save_or_prune(storage, delegation, now)
+}
+
+pub(crate) fn record_redelegation(
+    storage: &mut dyn Storage,
+    src_validator: &str,
+    dst_validator: &str,
+    amount: Uint128,
+    now: Timestamp,
+) -> Result<(), ContractError> {
+    let mut delegation = withdraw(storage, src_validator, amount)?;
+    delegation
+        .redelegating
+        .push(unbonding(storage, amount, now)?);
+    save_or_prune(storage, delegation, now)?;
+    record_delegation(storage, dst_validator, amount)
+}
+
+// Validators the hub still delegates to. Entries left only with unbondings are pruned
+// once those completed.
+pub(crate) fn delegated_validators(
+    storage: &mut dyn Storage,
+    now: Timestamp,
+) -> Result<Vec<String>, ContractError> {
+    let delegations = DELEGATIONS
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| Ok(item?.1))
+        .collect::<StdResult<Vec<ValidatorDelegation>>>()?;
+    let mut validators = vec![];
+    for delegation in delegations {
+        if delegation.delegated.is_zero() {
+            save_or_prune(storage, delegation, now)?;
+        } else {
+            validators.push(delegation.validator);
+        }
+    }
+    Ok(validators)
+}
+
+pub(crate) fn record_claim_failure(
+    storage: &mut dyn Storage,
+    validator: &str,
+    error: String,
+) -> Result<ClaimFailure, ContractError> {
+    let mut failure = CLAIM_FAILURES
+        .may_load(storage, validator)?
+        .unwrap_or_else(|| ClaimFailure {
+            validator: validator.to_string(),
+            consecutive_failures: 0,
+            last_error: String::new(),
+            excluded: false,
+        });
+    failure.consecutive_failures += 1;
+    failure.last_error = error;
+    failure.excluded = failure.consecutive_failures >= MAX_CONSECUTIVE_CLAIM_FAILURES;
+    CLAIM_FAILURES.save(storage, validator, &failure)?;
+    Ok(failure)
+}
+
+pub(crate) fn is_claim_excluded(storage: &dyn Storage, validator: &str) -> StdResult<bool> {
+    Ok(CLAIM_FAILURES
+        .may_load(storage, validator)?
+        .map_or(false, |failure| failure.excluded))
+}
+
+// Alliance tokens out of the hub: delegated, or undelegated and still unbonding at `now`
+pub(crate) fn total_bonded(storage: &dyn Storage, now: Timestamp) -> StdResult<Uint128> {
+    DELEGATIONS
+        .range(storage, None, None, Order::Ascending)
+        .try_fold(Uint128::zero(), |total, item| {
+            let delegation = item?.1;
+            let unbonding = delegation
+                .undelegating
+                .iter()
+                .filter(|unbonding| unbonding.completes_at > now)
+                .fold(Uint128::zero(), |acc, unbonding| acc + unbonding.amount);
+            Ok(total + delegation.delegated + unbonding)
+        })
+}
+
+// The staking module rejects transitive redelegations until the incoming one matured, so
+// the destination of a redelegation is locked as a source for the unbonding period.
+pub(crate) fn record_redelegation_cooldown(
+    storage: &mut dyn Storage,
+    dst_validator: &str,
+    now: Timestamp,
+) -> Result<(), ContractError> {
+    let unbonding_period = CONFIG.load(storage)?.unbonding_period;
+    REDELEGATION_COOLDOWNS.save(storage, dst_validator, &now.plus_seconds(unbonding_period))?;
+    Ok(())
+}
+
+pub(crate) fn validate_strategy(strategy: &DelegationStrategy) -> Result<(), ContractError> {
+    let mut validators = HashSet::new();
+    let mut total_weight = Decimal::zero();
+    for validator in &strategy.validators {
+        if validator.weight.is_zero() || !validators.insert(validator.validator.as_str()) {
+            return Err(ContractError::InvalidDelegationStrategy {});
+        }
+        total_weight += validator.weight;
+    }
+    if total_weight != Decimal::one() {
+        return Err(ContractError::InvalidDelegationStrategy {});
+    }
+    Ok(())
+}
+
+// Computes the actions moving the current delegations plus the `idle` alliance tokens
+// held by the hub towards the strategy weights. Surplus is first redelegated from
+// validators outside of their cooldown and idle tokens then cover the remaining deficits.
+// The targets add up to everything the hub holds, so the deficits always absorb the
+// surplus and nothing is undelegated: surplus on cooling validators waits for the cooldown.
+pub(crate) fn plan_rebalance(
+    storage: &dyn Storage,
+    strategy: &DelegationStrategy,
+    idle: Uint128,
+    now: Timestamp,
+) -> StdResult<Vec<RebalanceAction>> {
+    let current = DELEGATIONS
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (validator, delegation) = item?;
+            Ok((validator, delegation.delegated))
+        })
+        .collect::<StdResult<BTreeMap<String, Uint128>>>()?;
+    let total = current.values().fold(idle, |acc, amount| acc + amount);
+
+    let mut targets: BTreeMap<String, Uint128> = BTreeMap::new();
+    for validator in &strategy.validators {
+        targets.insert(
+            validator.validator.clone(),
+            total.mul_floor(validator.weight),
+        );
+    }
+    // Rounding dust goes to the first validator of the strategy
+    let allocated = targets
+        .values()
+        .fold(Uint128::zero(), |acc, amount| acc + amount);
+    if let Some(first) = strategy.validators.first() {
+        *targets.entry(first.validator.clone()).or_default() += total - allocated;
+    }
+
+    let mut surpluses: Vec<(String, Uint128)> = vec![];
+    let mut deficits: Vec<(String, Uint128)> = vec![];
+    for validator in current.keys().chain(targets.keys()).collect::<HashSet<_>>() {
+        let delegated = current.get(validator).copied().unwrap_or_default();
+        let target = targets.get(validator).copied().unwrap_or_default();
+        if delegated > target {
+            surpluses.push((validator.clone(), delegated - target));
+        } else if target > delegated {
+            deficits.push((validator.clone(), target - delegated));
+        }
+    }
+    surpluses.sort();
+    deficits.sort();
+
+    let mut actions = vec![];
+    for (src_validator, surplus) in surpluses.iter_mut() {
+        let cooling = REDELEGATION_COOLDOWNS
+            .may_load(storage, src_validator)?
+            .map_or(false, |until| until > now);
+        if cooling {
+            continue;
+        }
+        for (dst_validator, deficit) in deficits.iter_mut() {
+            let amount = (*surplus).min(*deficit);
+            if amount.is_zero() {
+                continue;
+            }
+            *surplus -= amount;
+            *deficit -= amount;
+            actions.push(RebalanceAction::Redelegate(AllianceRedelegation {
+                src_validator: src_validator.clone(),
+                dst_validator: dst_validator.clone(),
+                amount,
+            }));
+        }
+    }
+
+    let mut idle = idle;
+    for (validator, deficit) in deficits.iter() {
+        let amount = idle.min(*deficit);
+        if amount.is_zero() {
+            continue;
+        }
+        idle -= amount;
+        actions.push(RebalanceAction::Delegate(AllianceDelegation {
+            validator: validator.clone(),
+            amount,
+        }));
+    }
+    Ok(actions)
+}
+
+fn withdraw(
+    storage: &dyn Storage,
+    validator: &str,
+    amount: Uint128,
+) -> Result<ValidatorDelegation, ContractError> {
+    let mut delegation = DELEGATIONS
+        .may_load(storage, validator)?
+        .ok_or_else(|| ContractError::InsufficientDelegation(validator.to_string()))?;
+    delegation.delegated = delegation
+        .delegated
+        .checked_sub(amount)
+        .map_err(|_| ContractError::InsufficientDelegation(validator.to_string()))?;
+    Ok(delegation)
+}
+
+fn unbonding(storage: &dyn Storage, amount: Uint128, now: Timestamp) -> StdResult<Unbonding> {
+    Ok(Unbonding {
+        amount,
+        completes_at: now.plus_seconds(CONFIG.load(storage)?.unbonding_period),
+    })
+}
+
+fn save_or_prune(
+    storage: &mut dyn Storage,
+    mut delegation: ValidatorDelegation,
+    now: Timestamp,
+) -> Result<(), ContractError> {
+    delegation.prune_unbondings(now);
+    if delegation.is_empty() {
+        DELEGATIONS.remove(storage, &delegation.validator);
+    } else {
+        DELEGATIONS.save(storage, &delegation.validator, &delegation)?;
+    }
+    Ok(())
+}
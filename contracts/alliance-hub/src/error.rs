@@ -1,4 +1,4 @@
-use cosmwasm_std::{DecimalRangeExceeded, StdError};
+use cosmwasm_std::{Decimal, DecimalRangeExceeded, StdError, Timestamp, Uint128};
 use cw_asset_v3::AssetError;
 use thiserror::Error;
 
@@ -32,12 +32,96 @@ pub enum ContractError {
     #[error("Invalid reply id {0}")]
     InvalidReplyId(u64),
 
+    #[error("Invalid create denom reply: {0}")]
+    InvalidCreateDenomReply(String),
+
     #[error("Empty delegation")]
     EmptyDelegation {},
 
+    #[error("Insufficient delegation to validator {0}")]
+    InsufficientDelegation(String),
+
+    #[error("Delegations for the legacy validators {0} are missing from the migrate message")]
+    MissingLegacyDelegations(String),
+
+    #[error("Burning {0} alliance tokens would leave the supply below the delegated and unbonding amount")]
+    BurnExceedsUndelegatedSupply(String),
+
+    #[error("Invalid alliance token metadata: {0}")]
+    InvalidAllianceTokenMetadata(String),
+
+    #[error("Invalid delegation strategy")]
+    InvalidDelegationStrategy {},
+
+    #[error("Delegation strategy not set")]
+    DelegationStrategyNotSet {},
+
+    #[error("Rewards cannot be updated before {0}")]
+    RewardUpdateTooSoon(Timestamp),
+
+    #[error("Keeper bounty cannot exceed {0}")]
+    KeeperBountyTooHigh(Decimal),
+
+    #[error("Invalid reward asset: {0}")]
+    InvalidRewardAsset(String),
+
+    #[error("Invalid bond denom: {0}")]
+    InvalidBondDenom(String),
+
+    #[error("The reward asset cannot change while {0} of rewards are owed to the stakers")]
+    RewardLiabilitiesOutstanding(Uint128),
+
+    #[error("Reward balance decreased from {previous} to {current} while claiming rewards")]
+    RewardBalanceDecreased { previous: Uint128, current: Uint128 },
+
+    #[error("Invalid IBC destination: {0}")]
+    InvalidIbcDestination(String),
+
+    #[error("Invalid IBC transfer reply: {0}")]
+    InvalidIbcTransferReply(String),
+
+    #[error("Invalid IBC origin: {0}")]
+    InvalidIbcOrigin(String),
+
+    #[error("Funds cannot be sent with this message")]
+    FundsNotAccepted {},
+
     #[error("Invalid Distribution")]
     InvalidDistribution {},
 
+    #[error("No pending transfer for role {0}")]
+    NoPendingRoleTransfer(String),
+
+    #[error("Transfer for role {0} has expired")]
+    RoleTransferExpired(String),
+
+    #[error("Role transfer expiry must be greater than zero")]
+    InvalidRoleTransferExpiry {},
+
+    #[error("Paused: {0}")]
+    Paused(String),
+
+    #[error("Invalid voting weights: {0}")]
+    InvalidVotingWeights(String),
+
+    #[error("Hook {0} is already registered")]
+    HookAlreadyRegistered(String),
+
+    #[error("Hook {0} is not registered")]
+    HookNotRegistered(String),
+
+    #[error("Hook gas limit must be greater than zero")]
+    InvalidHookGasLimit {},
+
+    #[error("{0} cannot be swept")]
+    CannotSweepAsset(String),
+
+    #[error("No surplus of {0} to sweep")]
+    NothingToSweep(String),
+
+    #[error("Unsupported legacy asset {0}")]
+    UnsupportedLegacyAsset(String),
+
     #[error("Semver parsing error: {0}")]
     SemVer(String),
 
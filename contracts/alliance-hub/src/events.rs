@@ -0,0 +1,239 @@
+use alliance_protocol::alliance_protocol::{
+    AssetDistribution, Capability, IbcTransferKind, PauseScope, RebalanceAction, Role,
+};
+use cosmwasm_std::{to_json_string, Addr, Decimal, Event, StdResult, Storage, Timestamp, Uint128};
+use cw_asset_v3::AssetInfo;
+
+use crate::state::DELEGATIONS;
+
+// Events cover the balances, rewards, delegations, alliance token supply, whitelist,
+// roles, capabilities, pauses and the funds leaving the hub. Governance settings (rewards,
+// voting and token metadata configuration, delegation strategy, hooks) and the claim
+// failure records emit no event and are read back through their queries.
+//
+// Every event carries the version of its attribute set. It is bumped whenever attributes
+// of an existing event are renamed or removed; adding attributes keeps the version.
+pub(crate) const EVENTS_SCHEMA_VERSION: &str = "1";
+
+fn event(name: &str) -> Event {
+    Event::new(format!("alliance_hub/{}", name))
+        .add_attribute("schema_version", EVENTS_SCHEMA_VERSION)
+}
+
+// `balance` and `total_balance` are the staked amounts of the user and of everyone after
+// the action.
+pub(crate) fn stake(
+    user: &Addr,
+    asset: &AssetInfo,
+    amount: Uint128,
+    balance: Uint128,
+    total_balance: Uint128,
+) -> Event {
+    event("stake").add_attributes(vec![
+        ("user", user.to_string()),
+        ("asset", asset.to_string()),
+        ("amount", amount.to_string()),
+        ("balance", balance.to_string()),
+        ("total_balance", total_balance.to_string()),
+    ])
+}
+
+pub(crate) fn unstake(
+    user: &Addr,
+    asset: &AssetInfo,
+    amount: Uint128,
+    balance: Uint128,
+    total_balance: Uint128,
+) -> Event {
+    event("unstake").add_attributes(vec![
+        ("user", user.to_string()),
+        ("asset", asset.to_string()),
+        ("amount", amount.to_string()),
+        ("balance", balance.to_string()),
+        ("total_balance", total_balance.to_string()),
+    ])
+}
+
+// Claims reset the unclaimed rewards of the user for `asset` to zero
+pub(crate) fn claim(
+    user: &Addr,
+    asset: &AssetInfo,
+    reward_asset: &AssetInfo,
+    amount: Uint128,
+) -> Event {
+    event("claim").add_attributes(vec![
+        ("user", user.to_string()),
+        ("asset", asset.to_string()),
+        ("reward_asset", reward_asset.to_string()),
+        ("amount", amount.to_string()),
+    ])
+}
+
+// `reward_index` is the cumulative amount of rewards per staked unit of `asset`
+pub(crate) fn rewards_distributed(
+    asset: &AssetInfo,
+    reward_asset: &AssetInfo,
+    amount: Uint128,
+    reward_index_delta: Decimal,
+    reward_index: Decimal,
+) -> Event {
+    event("rewards_distributed").add_attributes(vec![
+        ("asset", asset.to_string()),
+        ("reward_asset", reward_asset.to_string()),
+        ("amount", amount.to_string()),
+        ("reward_index_delta", reward_index_delta.to_string()),
+        ("reward_index", reward_index.to_string()),
+    ])
+}
+
+// The distribution replaces the previous one and is encoded as JSON
+pub(crate) fn distribution_updated(distribution: &[AssetDistribution]) -> StdResult<Event> {
+    Ok(event("distribution_updated").add_attribute("distribution", to_json_string(distribution)?))
+}
+
+// Failed IBC transfers are credited back: unstaked funds to the staked balance and claimed
+// rewards to the unclaimed rewards.
+pub(crate) fn ibc_refund(
+    user: &Addr,
+    asset: &AssetInfo,
+    amount: Uint128,
+    kind: &IbcTransferKind,
+) -> Event {
+    let kind = match kind {
+        IbcTransferKind::Unstake => "unstake",
+        IbcTransferKind::ClaimRewards => "claim_rewards",
+    };
+    event("ibc_refund").add_attributes(vec![
+        ("user", user.to_string()),
+        ("asset", asset.to_string()),
+        ("amount", amount.to_string()),
+        ("kind", kind.to_string()),
+    ])
+}
+
+// Must be built after the change is recorded, `delegated` being the amount delegated to
+// each validator afterwards.
+pub(crate) fn delegation_changed(
+    storage: &dyn Storage,
+    action: &RebalanceAction,
+) -> StdResult<Event> {
+    let delegated = |validator: &str| -> StdResult<String> {
+        Ok(DELEGATIONS
+            .may_load(storage, validator)?
+            .map(|delegation| delegation.delegated)
+            .unwrap_or_default()
+            .to_string())
+    };
+    let event = event("delegation_changed");
+    Ok(match action {
+        RebalanceAction::Delegate(delegation) => event.add_attributes(vec![
+            ("kind", "delegate".to_string()),
+            ("validator", delegation.validator.clone()),
+            ("amount", delegation.amount.to_string()),
+            ("delegated", delegated(&delegation.validator)?),
+        ]),
+        RebalanceAction::Undelegate(delegation) => event.add_attributes(vec![
+            ("kind", "undelegate".to_string()),
+            ("validator", delegation.validator.clone()),
+            ("amount", delegation.amount.to_string()),
+            ("delegated", delegated(&delegation.validator)?),
+        ]),
+        RebalanceAction::Redelegate(redelegation) => event.add_attributes(vec![
+            ("kind", "redelegate".to_string()),
+            ("validator", redelegation.src_validator.clone()),
+            ("dst_validator", redelegation.dst_validator.clone()),
+            ("amount", redelegation.amount.to_string()),
+            ("delegated", delegated(&redelegation.src_validator)?),
+            ("dst_delegated", delegated(&redelegation.dst_validator)?),
+        ]),
+    })
+}
+
+pub(crate) fn asset_whitelisted(asset: &AssetInfo, chain_id: &str) -> Event {
+    event("asset_whitelisted").add_attributes(vec![
+        ("asset", asset.to_string()),
+        ("chain_id", chain_id.to_string()),
+    ])
+}
+
+pub(crate) fn asset_removed(asset: &AssetInfo) -> Event {
+    event("asset_removed").add_attribute("asset", asset.to_string())
+}
+
+// `total_supply` is the alliance token supply after the mint or burn
+pub(crate) fn supply_changed(kind: &str, amount: Uint128, total_supply: Uint128) -> Event {
+    event("supply_changed").add_attributes(vec![
+        ("kind", kind.to_string()),
+        ("amount", amount.to_string()),
+        ("total_supply", total_supply.to_string()),
+    ])
+}
+
+pub(crate) fn role_transfer_proposed(role: Role, new_address: &Addr, expiry: Timestamp) -> Event {
+    event("role_transfer").add_attributes(vec![
+        ("kind", "propose".to_string()),
+        ("role", role.to_string()),
+        ("new_address", new_address.to_string()),
+        ("expiry", expiry.seconds().to_string()),
+    ])
+}
+
+// The capabilities held by `previous_address` through the role move to `new_address`
+pub(crate) fn role_transfer_accepted(
+    role: Role,
+    previous_address: &Addr,
+    new_address: &Addr,
+) -> Event {
+    event("role_transfer").add_attributes(vec![
+        ("kind", "accept".to_string()),
+        ("role", role.to_string()),
+        ("previous_address", previous_address.to_string()),
+        ("new_address", new_address.to_string()),
+    ])
+}
+
+pub(crate) fn role_transfer_cancelled(role: Role) -> Event {
+    event("role_transfer").add_attributes(vec![
+        ("kind", "cancel".to_string()),
+        ("role", role.to_string()),
+    ])
+}
+
+pub(crate) fn capability_changed(address: &Addr, capability: Capability, granted: bool) -> Event {
+    event("capability_changed").add_attributes(vec![
+        ("address", address.to_string()),
+        ("capability", capability.as_str().to_string()),
+        ("granted", granted.to_string()),
+    ])
+}
+
+pub(crate) fn pause_changed(scope: &PauseScope, paused: bool) -> Event {
+    event("pause_changed").add_attributes(vec![
+        ("scope", scope.to_string()),
+        ("paused", paused.to_string()),
+    ])
+}
+
+// Rewards withdrawn along with native delegation changes, distributed by the next update
+pub(crate) fn rewards_withdrawn(reward_asset: &AssetInfo, amount: Uint128) -> Event {
+    event("rewards_withdrawn").add_attributes(vec![
+        ("reward_asset", reward_asset.to_string()),
+        ("amount", amount.to_string()),
+    ])
+}
+
+pub(crate) fn keeper_bounty(keeper: &Addr, reward_asset: &AssetInfo, amount: Uint128) -> Event {
+    event("keeper_bounty").add_attributes(vec![
+        ("keeper", keeper.to_string()),
+        ("reward_asset", reward_asset.to_string()),
+        ("amount", amount.to_string()),
+    ])
+}
+
+pub(crate) fn sweep(asset: &AssetInfo, amount: Uint128, recipient: &Addr) -> Event {
+    event("sweep").add_attributes(vec![
+        ("asset", asset.to_string()),
+        ("amount", amount.to_string()),
+        ("recipient", recipient.to_string()),
+    ])
+}
@@ -0,0 +1,67 @@
+use alliance_protocol::alliance_protocol::{HookRes, StakeChangedHookMsg};
+use cosmwasm_std::{Addr, Order, StdResult, Storage, SubMsg, Uint128};
+use cw_asset_v3::AssetInfo;
+
+use crate::error::ContractError;
+use crate::state::HOOKS;
+use crate::token_factory::CustomExecuteMsg;
+
+pub(crate) fn add_hook(
+    storage: &mut dyn Storage,
+    hook: &Addr,
+    gas_limit: u64,
+) -> Result<(), ContractError> {
+    if HOOKS.has(storage, hook) {
+        return Err(ContractError::HookAlreadyRegistered(hook.to_string()));
+    }
+    if gas_limit == 0 {
+        return Err(ContractError::InvalidHookGasLimit {});
+    }
+    HOOKS.save(storage, hook, &gas_limit)?;
+    Ok(())
+}
+
+pub(crate) fn remove_hook(storage: &mut dyn Storage, hook: &Addr) -> Result<(), ContractError> {
+    if !HOOKS.has(storage, hook) {
+        return Err(ContractError::HookNotRegistered(hook.to_string()));
+    }
+    HOOKS.remove(storage, hook);
+    Ok(())
+}
+
+pub(crate) fn hooks(storage: &dyn Storage) -> StdResult<Vec<HookRes>> {
+    HOOKS
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (addr, gas_limit) = item?;
+            Ok(HookRes { addr, gas_limit })
+        })
+        .collect()
+}
+
+// Called on stake, unstake and the refund of a failed UnstakeOverIbc transfer.
+// Every hook is its own submessage replying on error, so a failing hook only reverts
+// itself instead of the balance change that triggered it. The gas limit keeps a hook
+// from consuming the gas of the whole transaction.
+pub(crate) fn stake_changed_hooks(
+    storage: &dyn Storage,
+    user: &Addr,
+    asset: &AssetInfo,
+    old: Uint128,
+    new: Uint128,
+    reply_id: u64,
+) -> StdResult<Vec<SubMsg<CustomExecuteMsg>>> {
+    hooks(storage)?
+        .into_iter()
+        .map(|HookRes { addr, gas_limit }| {
+            let msg = StakeChangedHookMsg {
+                user: user.clone(),
+                asset: asset.clone(),
+                old,
+                new,
+            }
+            .into_cosmos_msg(addr)?;
+            Ok(SubMsg::reply_on_error(msg, reply_id).with_gas_limit(gas_limit))
+        })
+        .collect()
+}
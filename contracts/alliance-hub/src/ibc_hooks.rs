@@ -0,0 +1,136 @@
+use alliance_protocol::alliance_protocol::{IbcDestination, IbcOrigin};
+use bech32::{ToBase32, Variant};
+use cosmwasm_std::{Addr, Binary, Coin, CosmosMsg, Env, Storage};
+use cw_asset_v3::AssetInfo;
+use sha2::{Digest, Sha256};
+use terra_proto_rs::cosmos::base::v1beta1::Coin as ProtoCoin;
+use terra_proto_rs::ibc::applications::transfer::v1::{MsgTransfer, MsgTransferResponse};
+use terra_proto_rs::traits::Message;
+
+use crate::error::ContractError;
+use crate::state::REMOTE_IDENTITIES;
+use crate::token_factory::CustomExecuteMsg;
+
+const SENDER_PREFIX: &str = "ibc-wasm-hook-intermediary";
+// Timeout of the transfers back to the origin chain of remote stakers
+const ORIGIN_TRANSFER_TIMEOUT_SECONDS: u64 = 60 * 60;
+
+// Address ibc-hooks executes the hub with for an ICS-20 transfer of `origin.sender` over
+// `origin.channel_id`: the bech32 encoding of sha256(sha256(SENDER_PREFIX) || "channel/sender").
+pub(crate) fn intermediate_sender(
+    origin: &IbcOrigin,
+    bech32_prefix: &str,
+) -> Result<String, ContractError> {
+    let mut hasher = Sha256::new();
+    hasher.update(Sha256::digest(SENDER_PREFIX.as_bytes()));
+    hasher.update(format!("{}/{}", origin.channel_id, origin.sender).as_bytes());
+    bech32::encode(
+        bech32_prefix,
+        hasher.finalize().to_base32(),
+        Variant::Bech32,
+    )
+    .map_err(|err| ContractError::InvalidIbcOrigin(err.to_string()))
+}
+
+// Checks that `sender` is the intermediate sender of `origin`, using the bech32 prefix
+// of the hub address.
+pub(crate) fn verify_ibc_origin(
+    contract: &Addr,
+    sender: &Addr,
+    origin: &IbcOrigin,
+) -> Result<(), ContractError> {
+    let (bech32_prefix, _) = contract.as_str().rsplit_once('1').ok_or_else(|| {
+        ContractError::InvalidIbcOrigin(format!("{} is not a bech32 address", contract))
+    })?;
+    if intermediate_sender(origin, bech32_prefix)? != sender.as_str() {
+        return Err(ContractError::InvalidIbcOrigin(format!(
+            "{} is not the ibc-hooks sender of {}/{}",
+            sender, origin.channel_id, origin.sender
+        )));
+    }
+    Ok(())
+}
+
+// Nobody holds the keys of an intermediate sender, so the funds it unstakes or claims go
+// back over its channel to the origin chain sender instead. Cw20 assets cannot travel over
+// ICS-20, so they are rejected rather than paid to the intermediate sender.
+pub(crate) fn origin_destination(
+    storage: &dyn Storage,
+    sender: &Addr,
+    asset: &AssetInfo,
+) -> Result<Option<IbcDestination>, ContractError> {
+    let origin = match REMOTE_IDENTITIES.may_load(storage, sender)? {
+        Some(origin) => origin,
+        None => return Ok(None),
+    };
+    if !matches!(asset, AssetInfo::Native(_)) {
+        return Err(ContractError::InvalidIbcDestination(format!(
+            "{} cannot be sent over IBC",
+            asset
+        )));
+    }
+    Ok(Some(IbcDestination {
+        channel_id: origin.channel_id,
+        receiver: origin.sender,
+        timeout_seconds: ORIGIN_TRANSFER_TIMEOUT_SECONDS,
+    }))
+}
+
+pub(crate) fn validate_ibc_destination(destination: &IbcDestination) -> Result<(), ContractError> {
+    if !destination.channel_id.starts_with("channel-") {
+        return Err(ContractError::InvalidIbcDestination(format!(
+            "invalid channel {}",
+            destination.channel_id
+        )));
+    }
+    if destination.receiver.is_empty() {
+        return Err(ContractError::InvalidIbcDestination(
+            "receiver cannot be empty".to_string(),
+        ));
+    }
+    if destination.timeout_seconds == 0 {
+        return Err(ContractError::InvalidIbcDestination(
+            "timeout cannot be zero".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// ICS-20 transfer with an `ibc_callback` memo, so ibc-hooks reports the acknowledgement or
+// timeout of the packet back to the hub through sudo.
+pub(crate) fn ibc_transfer_msg(
+    env: &Env,
+    coin: Coin,
+    destination: &IbcDestination,
+) -> CosmosMsg<CustomExecuteMsg> {
+    let msg = MsgTransfer {
+        source_port: "transfer".to_string(),
+        source_channel: destination.channel_id.clone(),
+        token: Some(ProtoCoin {
+            denom: coin.denom,
+            amount: coin.amount.to_string(),
+        }),
+        sender: env.contract.address.to_string(),
+        receiver: destination.receiver.clone(),
+        timeout_height: None,
+        timeout_timestamp: env
+            .block
+            .time
+            .plus_seconds(destination.timeout_seconds)
+            .nanos(),
+        memo: format!(r#"{{"ibc_callback":"{}"}}"#, env.contract.address),
+    };
+    CosmosMsg::Stargate {
+        type_url: "/ibc.applications.transfer.v1.MsgTransfer".to_string(),
+        value: Binary::from(msg.encode_to_vec()),
+    }
+}
+
+pub(crate) fn parse_ibc_transfer_sequence(data: Option<Binary>) -> Result<u64, ContractError> {
+    let data = data.ok_or_else(|| {
+        ContractError::InvalidIbcTransferReply("missing response data".to_string())
+    })?;
+    let response = MsgTransferResponse::decode(data.as_slice())
+        .map_err(|err| ContractError::InvalidIbcTransferReply(err.to_string()))?;
+    Ok(response.sequence)
+}
@@ -0,0 +1,199 @@
+use alliance_protocol::alliance_protocol::{
+    AssetInvariantsRes, InvariantsCursor, InvariantsRes, RewardInvariantsRes,
+};
+use cosmwasm_std::{Addr, Decimal, Deps, Env, Order, StdError, StdResult, Storage, Uint128};
+use cw_asset_v3::AssetInfo;
+use cw_storage_plus_120::{Bound, PrimaryKey};
+use std::collections::BTreeMap;
+
+use crate::error::ContractError;
+use crate::state::{
+    ASSET_REWARD_RATE, BALANCES, CONFIG, REWARD_LIABILITIES, TOTAL_BALANCES, UNCLAIMED_REWARDS,
+    USER_ASSET_REWARD_RATE, WHITELIST, WITHDRAWN_REWARDS,
+};
+
+const DEFAULT_LIMIT: u32 = 100;
+const MAX_LIMIT: u32 = 500;
+
+// Staked amount and reward liabilities of the scanned positions, keyed by asset
+type PositionTotals = BTreeMap<String, (Uint128, Uint128)>;
+
+// Pending and unclaimed rewards owed on a stake position, rounded like a claim
+fn position_liabilities(
+    storage: &dyn Storage,
+    user: &Addr,
+    asset: &AssetInfo,
+    balance: Uint128,
+) -> StdResult<Uint128> {
+    let asset_reward_rate = ASSET_REWARD_RATE
+        .may_load(storage, asset)?
+        .unwrap_or_default();
+    let user_reward_rate = USER_ASSET_REWARD_RATE
+        .may_load(storage, (user.clone(), asset))?
+        .unwrap_or(asset_reward_rate);
+    let unclaimed_rewards = UNCLAIMED_REWARDS
+        .may_load(storage, (user.clone(), asset))?
+        .unwrap_or_default();
+    Ok(unclaimed_rewards + asset_reward_rate.saturating_sub(user_reward_rate) * balance)
+}
+
+// Rewards accrued on `balance` of a position since its rate was last settled, unrounded
+pub(crate) fn accrued_rewards(
+    storage: &dyn Storage,
+    user: &Addr,
+    asset: &AssetInfo,
+    balance: Uint128,
+) -> Result<Decimal, ContractError> {
+    let asset_reward_rate = ASSET_REWARD_RATE
+        .may_load(storage, asset)?
+        .unwrap_or_default();
+    let user_reward_rate = USER_ASSET_REWARD_RATE
+        .may_load(storage, (user.clone(), asset))?
+        .unwrap_or(asset_reward_rate);
+    Ok(asset_reward_rate.saturating_sub(user_reward_rate) * Decimal::from_atomics(balance, 0)?)
+}
+
+pub(crate) fn add_reward_liabilities(storage: &mut dyn Storage, amount: Decimal) -> StdResult<()> {
+    let liabilities = REWARD_LIABILITIES.may_load(storage)?.unwrap_or_default();
+    REWARD_LIABILITIES.save(storage, &(liabilities + amount))
+}
+
+pub(crate) fn deduct_reward_liabilities(
+    storage: &mut dyn Storage,
+    amount: Decimal,
+) -> StdResult<()> {
+    let liabilities = REWARD_LIABILITIES.may_load(storage)?.unwrap_or_default();
+    REWARD_LIABILITIES.save(storage, &liabilities.saturating_sub(amount))
+}
+
+// Sums the liabilities of every position, only used to seed REWARD_LIABILITIES
+pub(crate) fn sum_reward_liabilities(storage: &dyn Storage) -> Result<Decimal, ContractError> {
+    let positions = BALANCES
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut liabilities = Decimal::zero();
+    for ((user, asset), balance) in positions {
+        liabilities += accrued_rewards(storage, &user, &asset, balance)?;
+    }
+    for unclaimed in UNCLAIMED_REWARDS.range(storage, None, None, Order::Ascending) {
+        liabilities += Decimal::from_atomics(unclaimed?.1, 0)?;
+    }
+    Ok(liabilities)
+}
+
+fn held_balance(deps: Deps, env: &Env, asset: &AssetInfo) -> StdResult<Uint128> {
+    asset
+        .query_balance(&deps.querier, &env.contract.address)
+        .map_err(|err| StdError::generic_err(err.to_string()))
+}
+
+// Sums the balances and reward liabilities of up to `limit` stake positions after
+// `start_after` per asset, and returns the last one scanned when more positions remain
+fn scan_positions(
+    storage: &dyn Storage,
+    start_after: Option<(Addr, AssetInfo)>,
+    limit: usize,
+) -> StdResult<(PositionTotals, Option<(Addr, AssetInfo)>)> {
+    let start = start_after.map(|(user, asset)| Bound::ExclusiveRaw((user, &asset).joined_key()));
+    let mut positions = BALANCES
+        .range(storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let more = positions.len() > limit;
+    positions.truncate(limit);
+
+    let mut totals = PositionTotals::new();
+    for ((user, asset), balance) in &positions {
+        let liabilities = position_liabilities(storage, user, asset, *balance)?;
+        let (staked, reward_liabilities) = totals.entry(asset.to_string()).or_default();
+        *staked += balance;
+        *reward_liabilities += liabilities;
+    }
+    let next = match positions.pop() {
+        Some((last, _)) if more => Some(last),
+        _ => None,
+    };
+    Ok((totals, next))
+}
+
+// Amount of `asset` the hub owes its stakers: the staked total and, for the reward asset,
+// the pending and unclaimed rewards rounded up
+pub(crate) fn owed_amount(storage: &dyn Storage, asset: &AssetInfo) -> StdResult<Uint128> {
+    let staked = TOTAL_BALANCES.may_load(storage, asset)?.unwrap_or_default();
+    if asset != &CONFIG.load(storage)?.reward_asset {
+        return Ok(staked);
+    }
+    let liabilities = REWARD_LIABILITIES.may_load(storage)?.unwrap_or_default();
+    let withdrawn = WITHDRAWN_REWARDS.may_load(storage)?.unwrap_or_default();
+    Ok(staked + liabilities.to_uint_ceil() + withdrawn)
+}
+
+// Position sums only cover the page, clients add them up across pages. Every other
+// figure is read from storage, so a single page is enough to check solvency.
+pub(crate) fn check_invariants(
+    deps: Deps,
+    env: Env,
+    start_after: Option<InvariantsCursor>,
+    limit: Option<u32>,
+) -> StdResult<InvariantsRes> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let first_page = start_after.is_none();
+    let start_after = start_after
+        .map(|cursor| -> StdResult<_> {
+            Ok((deps.api.addr_validate(&cursor.address)?, cursor.asset))
+        })
+        .transpose()?;
+    let (totals, next) = scan_positions(deps.storage, start_after, limit)?;
+    let all_positions = first_page && next.is_none();
+
+    // Assets no longer whitelisted are still checked while they have a total balance
+    let mut assets: BTreeMap<String, AssetInfo> = BTreeMap::new();
+    for asset in WHITELIST
+        .keys(deps.storage, None, None, Order::Ascending)
+        .chain(TOTAL_BALANCES.keys(deps.storage, None, None, Order::Ascending))
+    {
+        let asset = asset?;
+        assets.insert(asset.to_string(), asset);
+    }
+
+    let mut asset_invariants = vec![];
+    for (key, asset) in assets {
+        let total_balance = TOTAL_BALANCES
+            .may_load(deps.storage, &asset)?
+            .unwrap_or_default();
+        let (sum_of_balances, reward_liabilities) = totals.get(&key).copied().unwrap_or_default();
+        let contract_balance = held_balance(deps, &env, &asset)?;
+        let owed = owed_amount(deps.storage, &asset)?;
+        asset_invariants.push(AssetInvariantsRes {
+            asset,
+            total_balance,
+            sum_of_balances,
+            contract_balance,
+            reward_liabilities,
+            balances_match: all_positions.then_some(sum_of_balances == total_balance),
+            solvent: contract_balance >= owed,
+        });
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let staked_rewards = TOTAL_BALANCES
+        .may_load(deps.storage, &config.reward_asset)?
+        .unwrap_or_default();
+    let liabilities = owed_amount(deps.storage, &config.reward_asset)? - staked_rewards;
+    let balance = held_balance(deps, &env, &config.reward_asset)?.saturating_sub(staked_rewards);
+
+    Ok(InvariantsRes {
+        assets: asset_invariants,
+        rewards: RewardInvariantsRes {
+            reward_asset: config.reward_asset,
+            liabilities,
+            balance,
+            surplus: balance.saturating_sub(liabilities),
+            deficit: liabilities.saturating_sub(balance),
+        },
+        next: next.map(|(user, asset)| InvariantsCursor {
+            address: user.to_string(),
+            asset,
+        }),
+    })
+}
@@ -1,8 +1,16 @@
 pub mod contract;
+mod delegations;
 pub mod error;
+mod events;
+mod hooks;
+mod ibc_hooks;
+mod invariants;
 mod migrations;
+mod permissions;
 pub mod query;
+mod staking;
 pub mod state;
 #[cfg(test)]
 mod tests;
-mod token_factory;
+pub mod token_factory;
+mod voting;
@@ -1,15 +1,124 @@
+use crate::contract::MAX_KEEPER_BOUNTY;
+use crate::delegations::record_delegation;
 use crate::error::ContractError;
+use crate::invariants::sum_reward_liabilities;
+use crate::permissions::grant_role_capabilities;
+use crate::staking::validate_bond_denom;
 use crate::state::{
-    ASSET_REWARD_DISTRIBUTION, ASSET_REWARD_RATE, BALANCES, TOTAL_BALANCES, UNCLAIMED_REWARDS,
+    ASSET_REWARD_DISTRIBUTION, ASSET_REWARD_RATE, CONFIG, REWARD_LIABILITIES, UNCLAIMED_REWARDS,
     USER_ASSET_REWARD_RATE, WHITELIST,
 };
 use alliance_protocol::alliance_oracle_types::ChainId;
-use alliance_protocol::alliance_protocol::AssetDistribution;
+use alliance_protocol::alliance_protocol::{
+    AssetDistribution, Config, MigrateMsg, MigrationStepRes, StakingBackend, TokenFactoryBackend,
+    DEFAULT_UNBONDING_PERIOD,
+};
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Decimal, DepsMut, Order, Uint128};
+use cosmwasm_std::{Addr, Decimal, DepsMut, Order, StdResult, Timestamp, Uint128};
+use cw_asset_v3::AssetInfo;
 use cw_storage_plus_016::{Item as Item016, Map as Map016};
+use cw_storage_plus_120::{Item, Map};
+use semver::Version;
+use std::collections::HashSet;
+
+pub(crate) struct MigrationStep {
+    // Version introducing the step, which runs when upgrading from an older version
+    // to this one or a newer one
+    pub version: &'static str,
+    pub name: &'static str,
+    pub run: fn(DepsMut, &MigrateMsg) -> Result<(), ContractError>,
+}
+
+// Steps run in this order, so later steps can rely on the state of the earlier ones
+pub(crate) const MIGRATIONS: &[MigrationStep] = &[
+    MigrationStep {
+        version: "0.1.3",
+        name: "cw_storage_plus_maps",
+        run: migrate_maps,
+    },
+    MigrationStep {
+        version: "0.2.0",
+        name: "reward_asset",
+        run: migrate_reward_asset,
+    },
+    MigrationStep {
+        version: "0.2.0",
+        name: "role_capabilities",
+        run: migrate_role_capabilities,
+    },
+    MigrationStep {
+        version: "0.2.0",
+        name: "delegation_ledger",
+        run: migrate_delegation_ledger,
+    },
+    MigrationStep {
+        version: "0.2.0",
+        name: "reward_liabilities",
+        run: migrate_reward_liabilities,
+    },
+];
+
+pub(crate) fn pending_migrations(
+    from: &Version,
+    to: &Version,
+) -> Result<Vec<&'static MigrationStep>, ContractError> {
+    let mut steps = vec![];
+    for step in MIGRATIONS {
+        let version: Version = step.version.parse()?;
+        if from < &version && &version <= to {
+            steps.push(step);
+        }
+    }
+    Ok(steps)
+}
+
+pub(crate) fn run_migrations(
+    mut deps: DepsMut,
+    from: &Version,
+    to: &Version,
+    msg: &MigrateMsg,
+) -> Result<Vec<MigrationStepRes>, ContractError> {
+    let steps = pending_migrations(from, to)?;
+    for step in &steps {
+        (step.run)(deps.branch(), msg)?;
+    }
+    Ok(steps
+        .into_iter()
+        .map(|step| MigrationStepRes {
+            version: step.version.to_string(),
+            name: step.name.to_string(),
+        })
+        .collect())
+}
 
-pub(crate) fn migrate_maps(mut deps: DepsMut) -> Result<(), ContractError> {
+// Applied after the steps, on every migration providing them
+pub(crate) fn apply_migrate_params(deps: DepsMut, msg: &MigrateMsg) -> Result<(), ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if let Some(token_factory_backend) = &msg.token_factory_backend {
+        config.token_factory_backend = token_factory_backend.clone();
+    }
+    if let Some(staking_backend) = &msg.staking_backend {
+        config.staking_backend = staking_backend.clone();
+        validate_bond_denom(deps.storage, &config)?;
+    }
+    if let Some(reward_update_interval) = msg.reward_update_interval {
+        config.reward_update_interval = reward_update_interval;
+    }
+    if let Some(keeper_bounty) = msg.keeper_bounty {
+        if keeper_bounty > MAX_KEEPER_BOUNTY {
+            return Err(ContractError::KeeperBountyTooHigh(MAX_KEEPER_BOUNTY));
+        }
+        config.keeper_bounty = keeper_bounty;
+    }
+    if let Some(unbonding_period) = msg.unbonding_period {
+        config.unbonding_period = unbonding_period;
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(())
+}
+
+fn migrate_maps(mut deps: DepsMut, _msg: &MigrateMsg) -> Result<(), ContractError> {
     migrate_whitelist_map(deps.branch())?;
     migrate_balances_map(deps.branch())?;
     migrate_total_balances_map(deps.branch())?;
@@ -21,60 +130,139 @@ pub(crate) fn migrate_maps(mut deps: DepsMut) -> Result<(), ContractError> {
     Ok(())
 }
 
+// The native reward denom became a reward asset, which may also be a cw20 token.
+// Configs already using the new format are left as they are.
+pub(crate) fn migrate_reward_asset(deps: DepsMut, _msg: &MigrateMsg) -> Result<(), ContractError> {
+    #[cw_serde]
+    pub struct OldConfig {
+        pub governance: Addr,
+        pub controller: Addr,
+        pub oracle: Addr,
+        pub operator: Addr,
+        pub last_reward_update_timestamp: Timestamp,
+        pub alliance_token_denom: String,
+        pub alliance_token_supply: Uint128,
+        pub reward_denom: String,
+        #[serde(default)]
+        pub token_factory_backend: TokenFactoryBackend,
+        #[serde(default)]
+        pub staking_backend: StakingBackend,
+        #[serde(default)]
+        pub reward_update_interval: u64,
+        #[serde(default)]
+        pub keeper_bounty: Decimal,
+    }
+
+    const OLD_CONFIG: Item<OldConfig> = Item::new("config");
+    if CONFIG.load(deps.storage).is_ok() {
+        return Ok(());
+    }
+    let old_config = OLD_CONFIG.load(deps.storage)?;
+    let config = Config {
+        governance: old_config.governance,
+        controller: old_config.controller,
+        oracle: old_config.oracle,
+        operator: old_config.operator,
+        last_reward_update_timestamp: old_config.last_reward_update_timestamp,
+        alliance_token_denom: old_config.alliance_token_denom,
+        alliance_token_supply: old_config.alliance_token_supply,
+        reward_asset: cw_asset_v3::AssetInfo::native(old_config.reward_denom),
+        token_factory_backend: old_config.token_factory_backend,
+        staking_backend: old_config.staking_backend,
+        reward_update_interval: old_config.reward_update_interval,
+        keeper_bounty: old_config.keeper_bounty,
+        unbonding_period: DEFAULT_UNBONDING_PERIOD,
+    };
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(())
+}
+
+// The fixed governance, controller and operator checks became entries in the
+// permission registry, so the current role holders get the equivalent grants.
+fn migrate_role_capabilities(deps: DepsMut, _msg: &MigrateMsg) -> Result<(), ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    grant_role_capabilities(deps.storage, &config)?;
+
+    Ok(())
+}
+
+// The grow-only validator set is replaced by a ledger of the amounts delegated to each
+// validator. Those amounts are not stored anywhere in the contract, so they have to be
+// provided for every validator of the legacy set that still has a delegation.
+fn migrate_delegation_ledger(deps: DepsMut, msg: &MigrateMsg) -> Result<(), ContractError> {
+    const OLD_VALIDATORS: Item<HashSet<String>> = Item::new("validators");
+
+    // Every legacy validator needs an entry, zero for the ones the hub fully left
+    let legacy_validators = OLD_VALIDATORS.may_load(deps.storage)?.unwrap_or_default();
+    let delegations = msg.delegations.clone().unwrap_or_default();
+    let mut missing = legacy_validators
+        .into_iter()
+        .filter(|validator| {
+            !delegations
+                .iter()
+                .any(|delegation| &delegation.validator == validator)
+        })
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        missing.sort();
+        return Err(ContractError::MissingLegacyDelegations(missing.join(",")));
+    }
+
+    for delegation in delegations {
+        if !delegation.amount.is_zero() {
+            record_delegation(deps.storage, &delegation.validator, delegation.amount)?;
+        }
+    }
+    OLD_VALIDATORS.remove(deps.storage);
+
+    Ok(())
+}
+
+// Reward liabilities are tracked as they change, starting from the sum over the
+// positions at the time of the migration
+fn migrate_reward_liabilities(deps: DepsMut, _msg: &MigrateMsg) -> Result<(), ContractError> {
+    let liabilities = sum_reward_liabilities(deps.storage)?;
+    REWARD_LIABILITIES.save(deps.storage, &liabilities)?;
+
+    Ok(())
+}
+
 fn migrate_whitelist_map(deps: DepsMut) -> Result<(), ContractError> {
     const OLD_WHITELIST: Map016<cw_asset_v2::AssetInfoKey, ChainId> = Map016::new("whitelist");
 
     let old_map = OLD_WHITELIST
         .range(deps.storage, None, None, Order::Ascending)
-        .map(|item| {
-            let (key, value) = item.unwrap();
-            (key, value)
-        })
-        .collect::<Vec<_>>();
+        .collect::<StdResult<Vec<_>>>()?;
 
     OLD_WHITELIST.clear(deps.storage);
 
     for (key, value) in old_map {
-        let asset_info_v3 = match key {
-            cw_asset_v2::AssetInfoUnchecked::Native(x) => cw_asset_v3::AssetInfo::native(x),
-            cw_asset_v2::AssetInfoUnchecked::Cw20(x) => {
-                cw_asset_v3::AssetInfo::cw20(deps.api.addr_validate(&x)?)
-            }
-            _ => panic!("unsupported"),
-        };
-        WHITELIST
-            .save(deps.storage, &asset_info_v3, &value)
-            .unwrap();
+        let asset_info_v3 = legacy_asset_info(&deps, key)?;
+        WHITELIST.save(deps.storage, &asset_info_v3, &value)?;
     }
 
     Ok(())
 }
+// Primary namespaces of the BALANCES and TOTAL_BALANCES snapshot maps. Balances are seeded
+// without a changelog entry, so they also hold at the heights before the migration.
+const SNAPSHOT_BALANCES: Map<(Addr, &AssetInfo), Uint128> = Map::new("balances");
+const SNAPSHOT_TOTAL_BALANCES: Map<&AssetInfo, Uint128> = Map::new("total_balances");
+
 fn migrate_balances_map(deps: DepsMut) -> Result<(), ContractError> {
     const OLD_BALANCES: Map016<(Addr, cw_asset_v2::AssetInfoKey), Uint128> =
         Map016::new("balances");
 
     let old_map = OLD_BALANCES
         .range(deps.storage, None, None, Order::Ascending)
-        .map(|item| {
-            let (key, value) = item.unwrap();
-            (key, value)
-        })
-        .collect::<Vec<_>>();
+        .collect::<StdResult<Vec<_>>>()?;
 
     OLD_BALANCES.clear(deps.storage);
 
     for (key, value) in old_map {
-        let asset_info_v3 = match key.1 {
-            cw_asset_v2::AssetInfoUnchecked::Native(x) => cw_asset_v3::AssetInfo::native(x),
-            cw_asset_v2::AssetInfoUnchecked::Cw20(x) => {
-                cw_asset_v3::AssetInfo::cw20(deps.api.addr_validate(&x)?)
-            }
-            _ => panic!("unsupported"),
-        };
+        let asset_info_v3 = legacy_asset_info(&deps, key.1)?;
 
-        BALANCES
-            .save(deps.storage, (key.0, &asset_info_v3), &value)
-            .unwrap();
+        SNAPSHOT_BALANCES.save(deps.storage, (key.0, &asset_info_v3), &value)?;
     }
 
     Ok(())
@@ -85,26 +273,14 @@ fn migrate_total_balances_map(deps: DepsMut) -> Result<(), ContractError> {
 
     let old_map = OLD_TOTAL_BALANCES
         .range(deps.storage, None, None, Order::Ascending)
-        .map(|item| {
-            let (key, value) = item.unwrap();
-            (key, value)
-        })
-        .collect::<Vec<_>>();
+        .collect::<StdResult<Vec<_>>>()?;
 
     OLD_TOTAL_BALANCES.clear(deps.storage);
 
     for (key, value) in old_map {
-        let asset_info_v3 = match key {
-            cw_asset_v2::AssetInfoUnchecked::Native(x) => cw_asset_v3::AssetInfo::native(x),
-            cw_asset_v2::AssetInfoUnchecked::Cw20(x) => {
-                cw_asset_v3::AssetInfo::cw20(deps.api.addr_validate(&x)?)
-            }
-            _ => panic!("unsupported"),
-        };
+        let asset_info_v3 = legacy_asset_info(&deps, key)?;
 
-        TOTAL_BALANCES
-            .save(deps.storage, &asset_info_v3, &value)
-            .unwrap();
+        SNAPSHOT_TOTAL_BALANCES.save(deps.storage, &asset_info_v3, &value)?;
     }
 
     Ok(())
@@ -119,7 +295,11 @@ fn migrate_asset_reward_distribution(deps: DepsMut) -> Result<(), ContractError>
 
     const OLD_ASSET_REWARD_DISTRIBUTION: Item016<Vec<OldAssetDistribution>> =
         Item016::new("asset_reward_distribution");
-    let old_asset_reward_distribution = OLD_ASSET_REWARD_DISTRIBUTION.load(deps.storage)?;
+    let Some(old_asset_reward_distribution) =
+        OLD_ASSET_REWARD_DISTRIBUTION.may_load(deps.storage)?
+    else {
+        return Ok(());
+    };
 
     OLD_ASSET_REWARD_DISTRIBUTION.remove(deps.storage);
 
@@ -128,7 +308,7 @@ fn migrate_asset_reward_distribution(deps: DepsMut) -> Result<(), ContractError>
         let asset_info_v3 = match a.asset {
             cw_asset_v2::AssetInfo::Native(x) => cw_asset_v3::AssetInfo::native(x),
             cw_asset_v2::AssetInfo::Cw20(x) => cw_asset_v3::AssetInfo::cw20(x),
-            _ => panic!("unsupported"),
+            asset => return Err(ContractError::UnsupportedLegacyAsset(asset.to_string())),
         };
 
         asset_reward_distribution.push(AssetDistribution {
@@ -148,26 +328,14 @@ fn migrate_asset_reward_rate(deps: DepsMut) -> Result<(), ContractError> {
 
     let old_map = OLD_ASSET_REWARD_RATE
         .range(deps.storage, None, None, Order::Ascending)
-        .map(|item| {
-            let (key, value) = item.unwrap();
-            (key, value)
-        })
-        .collect::<Vec<_>>();
+        .collect::<StdResult<Vec<_>>>()?;
 
     OLD_ASSET_REWARD_RATE.clear(deps.storage);
 
     for (key, value) in old_map {
-        let asset_info_v3 = match key {
-            cw_asset_v2::AssetInfoUnchecked::Native(x) => cw_asset_v3::AssetInfo::native(x),
-            cw_asset_v2::AssetInfoUnchecked::Cw20(x) => {
-                cw_asset_v3::AssetInfo::cw20(deps.api.addr_validate(&x)?)
-            }
-            _ => panic!("unsupported"),
-        };
+        let asset_info_v3 = legacy_asset_info(&deps, key)?;
 
-        ASSET_REWARD_RATE
-            .save(deps.storage, &asset_info_v3, &value)
-            .unwrap();
+        ASSET_REWARD_RATE.save(deps.storage, &asset_info_v3, &value)?;
     }
 
     Ok(())
@@ -179,26 +347,14 @@ fn migrate_user_asset_reward_rate(deps: DepsMut) -> Result<(), ContractError> {
 
     let old_map = OLD_USER_ASSET_REWARD_RATE
         .range(deps.storage, None, None, Order::Ascending)
-        .map(|item| {
-            let (key, value) = item.unwrap();
-            (key, value)
-        })
-        .collect::<Vec<_>>();
+        .collect::<StdResult<Vec<_>>>()?;
 
     OLD_USER_ASSET_REWARD_RATE.clear(deps.storage);
 
     for (key, value) in old_map {
-        let asset_info_v3 = match key.1 {
-            cw_asset_v2::AssetInfoUnchecked::Native(x) => cw_asset_v3::AssetInfo::native(x),
-            cw_asset_v2::AssetInfoUnchecked::Cw20(x) => {
-                cw_asset_v3::AssetInfo::cw20(deps.api.addr_validate(&x)?)
-            }
-            _ => panic!("unsupported"),
-        };
+        let asset_info_v3 = legacy_asset_info(&deps, key.1)?;
 
-        USER_ASSET_REWARD_RATE
-            .save(deps.storage, (key.0, &asset_info_v3), &value)
-            .unwrap();
+        USER_ASSET_REWARD_RATE.save(deps.storage, (key.0, &asset_info_v3), &value)?;
     }
 
     Ok(())
@@ -210,27 +366,35 @@ fn migrate_unclaimed_rewards(deps: DepsMut) -> Result<(), ContractError> {
 
     let old_map = OLD_UNCLAIMED_REWARDS
         .range(deps.storage, None, None, Order::Ascending)
-        .map(|item| {
-            let (key, value) = item.unwrap();
-            (key, value)
-        })
-        .collect::<Vec<_>>();
+        .collect::<StdResult<Vec<_>>>()?;
 
     OLD_UNCLAIMED_REWARDS.clear(deps.storage);
 
     for (key, value) in old_map {
-        let asset_info_v3 = match key.1 {
-            cw_asset_v2::AssetInfoUnchecked::Native(x) => cw_asset_v3::AssetInfo::native(x),
-            cw_asset_v2::AssetInfoUnchecked::Cw20(x) => {
-                cw_asset_v3::AssetInfo::cw20(deps.api.addr_validate(&x)?)
-            }
-            _ => panic!("unsupported"),
-        };
+        let asset_info_v3 = legacy_asset_info(&deps, key.1)?;
 
-        UNCLAIMED_REWARDS
-            .save(deps.storage, (key.0, &asset_info_v3), &value)
-            .unwrap();
+        UNCLAIMED_REWARDS.save(deps.storage, (key.0, &asset_info_v3), &value)?;
     }
 
     Ok(())
 }
+
+// Cw1155 tokens could be whitelisted before the cw-asset v3 upgrade, but cannot be staked since
+fn legacy_asset_info(
+    deps: &DepsMut,
+    asset: cw_asset_v2::AssetInfoUnchecked,
+) -> Result<cw_asset_v3::AssetInfo, ContractError> {
+    match asset {
+        cw_asset_v2::AssetInfoUnchecked::Native(x) => Ok(cw_asset_v3::AssetInfo::native(x)),
+        cw_asset_v2::AssetInfoUnchecked::Cw20(x) => {
+            Ok(cw_asset_v3::AssetInfo::cw20(deps.api.addr_validate(&x)?))
+        }
+        cw_asset_v2::AssetInfoUnchecked::Cw1155(x, id) => Err(
+            ContractError::UnsupportedLegacyAsset(format!("cw1155:{}:{}", x, id)),
+        ),
+        asset => Err(ContractError::UnsupportedLegacyAsset(format!(
+            "{:?}",
+            asset
+        ))),
+    }
+}
@@ -0,0 +1,79 @@
+use alliance_protocol::alliance_protocol::{Capability, Config, Role};
+use cosmwasm_std::{Addr, Empty, StdResult, Storage};
+
+use crate::error::ContractError;
+use crate::state::CAPABILITIES;
+
+pub(crate) fn has_capability(
+    storage: &dyn Storage,
+    address: &Addr,
+    capability: Capability,
+) -> StdResult<bool> {
+    Ok(CAPABILITIES.has(storage, (capability.as_str(), address)))
+}
+
+pub(crate) fn ensure_capability(
+    storage: &dyn Storage,
+    address: &Addr,
+    capability: Capability,
+) -> Result<(), ContractError> {
+    if !has_capability(storage, address, capability)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+pub(crate) fn grant_capability(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    capability: Capability,
+) -> StdResult<()> {
+    CAPABILITIES.save(storage, (capability.as_str(), address), &Empty {})
+}
+
+pub(crate) fn revoke_capability(storage: &mut dyn Storage, address: &Addr, capability: Capability) {
+    CAPABILITIES.remove(storage, (capability.as_str(), address))
+}
+
+// Grants every role holder in the config the capabilities of its role
+pub(crate) fn grant_role_capabilities(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    for role in Role::ALL {
+        for capability in role.default_capabilities() {
+            grant_capability(storage, config.role(role), *capability)?;
+        }
+    }
+    Ok(())
+}
+
+// Moves the capabilities the previous holder of a role actually holds to the one in the
+// config, so revoked defaults stay revoked and extra grants follow the role. The previous
+// holder keeps whatever it still needs for the other roles it holds, capabilities that
+// only belong to those roles are not moved.
+pub(crate) fn transfer_role_capabilities(
+    storage: &mut dyn Storage,
+    config: &Config,
+    role: Role,
+    previous_holder: &Addr,
+) -> StdResult<()> {
+    let retained: Vec<Capability> = Role::ALL
+        .iter()
+        .copied()
+        .filter(|other| config.role(*other) == previous_holder)
+        .flat_map(|other| other.default_capabilities().iter().copied())
+        .collect();
+    for capability in Capability::ALL {
+        if !has_capability(storage, previous_holder, capability)? {
+            continue;
+        }
+        let of_role = role.default_capabilities().contains(&capability);
+        if retained.contains(&capability) {
+            if of_role {
+                grant_capability(storage, config.role(role), capability)?;
+            }
+        } else {
+            revoke_capability(storage, previous_holder, capability);
+            grant_capability(storage, config.role(role), capability)?;
+        }
+    }
+    Ok(())
+}
@@ -1,23 +1,43 @@
 use alliance_protocol::alliance_protocol::{
-    AllPendingRewardsQuery, AllStakedBalancesQuery, AssetQuery, PendingRewardsRes, QueryMsg,
-    StakedBalanceRes, WhitelistedAssetsResponse,
+    AllPendingRewardsQuery, AllStakedBalancesQuery, AssetPauseStatus, AssetQuery, Capability,
+    CapabilityHoldersRes, ClaimFailure, InfoResponse, MigrationStepRes, PauseStatusRes,
+    PendingRewardsRes, PendingRoleTransfer, QueryMsg, StakedBalanceAtHeightRes, StakedBalanceRes,
+    TotalPowerAtHeightResponse, VotingConfigRes, VotingPowerAtHeightResponse,
+    WhitelistedAssetsResponse,
 };
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_json_binary, Binary, Deps, Env, Order, StdResult, Uint128};
+use cosmwasm_std::{to_json_binary, Binary, Deps, Env, Order, StdError, StdResult, Uint128};
+use cw2::get_contract_version;
 use cw_asset_v3::AssetInfo;
-use std::collections::HashMap;
+use semver::Version;
+use std::collections::{HashMap, HashSet};
 
+use crate::contract::CONTRACT_VERSION;
+use crate::delegations::plan_rebalance;
+use crate::hooks::hooks;
+use crate::invariants::check_invariants;
+use crate::migrations::pending_migrations;
+use crate::staking::Staking;
 use crate::state::{
-    ASSET_REWARD_DISTRIBUTION, ASSET_REWARD_RATE, BALANCES, CONFIG, TOTAL_BALANCES,
-    UNCLAIMED_REWARDS, USER_ASSET_REWARD_RATE, VALIDATORS, WHITELIST,
+    ASSET_PAUSES, ASSET_REWARD_DISTRIBUTION, ASSET_REWARD_RATE, BALANCES, CAPABILITIES,
+    CLAIM_FAILURES, CONFIG, DELEGATIONS, DELEGATION_STRATEGY, GLOBAL_PAUSE, PENDING_ROLE_TRANSFERS,
+    REMOTE_IDENTITIES, REWARD_UPDATES_PAUSE, TOTAL_BALANCES, UNCLAIMED_REWARDS,
+    USER_ASSET_REWARD_RATE, VOTING_WEIGHTS, WHITELIST,
 };
+use crate::voting::{dao, total_power, voting_power};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     Ok(match msg {
         QueryMsg::Config {} => get_config(deps)?,
         QueryMsg::Validators {} => get_validators(deps)?,
+        QueryMsg::Delegations {} => get_delegations(deps, env)?,
+        QueryMsg::MigrationPlan { from_version } => get_migration_plan(deps, from_version)?,
+        QueryMsg::RemoteIdentity { address } => get_remote_identity(deps, address)?,
+        QueryMsg::ClaimFailures {} => get_claim_failures(deps)?,
+        QueryMsg::DelegationStrategy {} => get_delegation_strategy(deps)?,
+        QueryMsg::RebalancePlan {} => get_rebalance_plan(deps, env)?,
         QueryMsg::WhitelistedAssets {} => get_whitelisted_assets(deps)?,
         QueryMsg::RewardDistribution {} => get_rewards_distribution(deps)?,
         QueryMsg::StakedBalance(asset_query) => get_staked_balance(deps, asset_query)?,
@@ -25,6 +45,30 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::AllStakedBalances(query) => get_all_staked_balances(deps, query)?,
         QueryMsg::AllPendingRewards(query) => get_all_pending_rewards(deps, query)?,
         QueryMsg::TotalStakedBalances {} => get_total_staked_balances(deps)?,
+        QueryMsg::StakedBalanceAtHeight {
+            address,
+            asset,
+            height,
+        } => get_staked_balance_at_height(deps, env, address, asset, height)?,
+        QueryMsg::TotalStakedAtHeight { asset, height } => {
+            get_total_staked_at_height(deps, env, asset, height)?
+        }
+        QueryMsg::PendingRoleTransfers {} => get_pending_role_transfers(deps)?,
+        QueryMsg::PauseStatus {} => get_pause_status(deps)?,
+        QueryMsg::CapabilityHolders {} => get_capability_holders(deps)?,
+        QueryMsg::Hooks {} => to_json_binary(&hooks(deps.storage)?)?,
+        QueryMsg::VotingConfig {} => get_voting_config(deps)?,
+        QueryMsg::VotingPowerAtHeight { address, height } => {
+            get_voting_power_at_height(deps, env, address, height)?
+        }
+        QueryMsg::TotalPowerAtHeight { height } => get_total_power_at_height(deps, env, height)?,
+        QueryMsg::Dao {} => to_json_binary(&dao(deps.storage)?)?,
+        QueryMsg::Info {} => to_json_binary(&InfoResponse {
+            info: get_contract_version(deps.storage)?,
+        })?,
+        QueryMsg::CheckInvariants { start_after, limit } => {
+            to_json_binary(&check_invariants(deps, env, start_after, limit)?)?
+        }
     })
 }
 
@@ -35,11 +79,90 @@ fn get_config(deps: Deps) -> StdResult<Binary> {
 }
 
 fn get_validators(deps: Deps) -> StdResult<Binary> {
-    let validators = VALIDATORS.load(deps.storage)?;
+    let mut validators = HashSet::new();
+    for item in DELEGATIONS.range(deps.storage, None, None, Order::Ascending) {
+        let (validator, delegation) = item?;
+        if !delegation.delegated.is_zero() {
+            validators.insert(validator);
+        }
+    }
 
     to_json_binary(&validators)
 }
 
+// Unbondings completed since the entries were last written are left out
+fn get_delegations(deps: Deps, env: Env) -> StdResult<Binary> {
+    let mut delegations = vec![];
+    for item in DELEGATIONS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, mut delegation) = item?;
+        delegation.prune_unbondings(env.block.time);
+        if !delegation.is_empty() {
+            delegations.push(delegation);
+        }
+    }
+
+    to_json_binary(&delegations)
+}
+
+fn get_migration_plan(deps: Deps, from_version: Option<String>) -> StdResult<Binary> {
+    let from_version = match from_version {
+        Some(from_version) => from_version,
+        None => get_contract_version(deps.storage)?.version,
+    };
+    let parse = |version: &str| {
+        version
+            .parse::<Version>()
+            .map_err(|err| StdError::generic_err(err.to_string()))
+    };
+    let steps = pending_migrations(&parse(&from_version)?, &parse(CONTRACT_VERSION)?)
+        .map_err(|err| StdError::generic_err(err.to_string()))?
+        .into_iter()
+        .map(|step| MigrationStepRes {
+            version: step.version.to_string(),
+            name: step.name.to_string(),
+        })
+        .collect::<Vec<_>>();
+
+    to_json_binary(&steps)
+}
+
+fn get_remote_identity(deps: Deps, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let origin = REMOTE_IDENTITIES.may_load(deps.storage, &address)?;
+
+    to_json_binary(&origin)
+}
+
+fn get_claim_failures(deps: Deps) -> StdResult<Binary> {
+    let failures = CLAIM_FAILURES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| Ok(item?.1))
+        .collect::<StdResult<Vec<ClaimFailure>>>()?;
+
+    to_json_binary(&failures)
+}
+
+fn get_delegation_strategy(deps: Deps) -> StdResult<Binary> {
+    let strategy = DELEGATION_STRATEGY.load(deps.storage)?;
+
+    to_json_binary(&strategy)
+}
+
+fn get_rebalance_plan(deps: Deps, env: Env) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    let strategy = DELEGATION_STRATEGY.load(deps.storage)?;
+    let denom = config
+        .staking_backend
+        .bond_denom(&config.alliance_token_denom);
+    let idle = deps
+        .querier
+        .query_balance(&env.contract.address, denom)?
+        .amount;
+    let actions = plan_rebalance(deps.storage, &strategy, idle, env.block.time)?;
+
+    to_json_binary(&actions)
+}
+
 fn get_whitelisted_assets(deps: Deps) -> StdResult<Binary> {
     let whitelist = WHITELIST.range(deps.storage, None, None, Order::Ascending);
     let mut res: WhitelistedAssetsResponse = HashMap::new();
@@ -85,7 +208,7 @@ fn get_pending_rewards(deps: Deps, asset_query: AssetQuery) -> StdResult<Binary>
     to_json_binary(&PendingRewardsRes {
         rewards: unclaimed_rewards + pending_rewards,
         staked_asset: asset_query.asset,
-        reward_asset: AssetInfo::Native(config.reward_denom),
+        reward_asset: config.reward_asset,
     })
 }
 
@@ -129,7 +252,7 @@ fn get_all_pending_rewards(deps: Deps, query: AllPendingRewardsQuery) -> StdResu
             Ok(PendingRewardsRes {
                 rewards: pending_rewards + unclaimed_rewards,
                 staked_asset: asset_info.clone(),
-                reward_asset: AssetInfo::Native(config.reward_denom.to_string()),
+                reward_asset: config.reward_asset.clone(),
             })
         })
         .collect::<StdResult<Vec<PendingRewardsRes>>>();
@@ -147,3 +270,115 @@ fn get_total_staked_balances(deps: Deps) -> StdResult<Binary> {
         .collect();
     to_json_binary(&total_staked_balances?)
 }
+
+fn get_staked_balance_at_height(
+    deps: Deps,
+    env: Env,
+    address: String,
+    asset: AssetInfo,
+    height: Option<u64>,
+) -> StdResult<Binary> {
+    let addr = deps.api.addr_validate(&address)?;
+    let height = height.unwrap_or(env.block.height);
+    let balance = BALANCES
+        .may_load_at_height(deps.storage, (addr, &asset), height)?
+        .unwrap_or_default();
+
+    to_json_binary(&StakedBalanceAtHeightRes {
+        asset,
+        balance,
+        height,
+    })
+}
+
+fn get_total_staked_at_height(
+    deps: Deps,
+    env: Env,
+    asset: AssetInfo,
+    height: Option<u64>,
+) -> StdResult<Binary> {
+    let height = height.unwrap_or(env.block.height);
+    let balance = TOTAL_BALANCES
+        .may_load_at_height(deps.storage, &asset, height)?
+        .unwrap_or_default();
+
+    to_json_binary(&StakedBalanceAtHeightRes {
+        asset,
+        balance,
+        height,
+    })
+}
+
+fn get_voting_config(deps: Deps) -> StdResult<Binary> {
+    to_json_binary(&VotingConfigRes {
+        dao: dao(deps.storage)?,
+        asset_weights: VOTING_WEIGHTS.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+fn get_voting_power_at_height(
+    deps: Deps,
+    env: Env,
+    address: String,
+    height: Option<u64>,
+) -> StdResult<Binary> {
+    let addr = deps.api.addr_validate(&address)?;
+    let height = height.unwrap_or(env.block.height);
+
+    to_json_binary(&VotingPowerAtHeightResponse {
+        power: voting_power(deps.storage, &addr, height)?,
+        height,
+    })
+}
+
+fn get_total_power_at_height(deps: Deps, env: Env, height: Option<u64>) -> StdResult<Binary> {
+    let height = height.unwrap_or(env.block.height);
+
+    to_json_binary(&TotalPowerAtHeightResponse {
+        power: total_power(deps.storage, height)?,
+        height,
+    })
+}
+
+fn get_pending_role_transfers(deps: Deps) -> StdResult<Binary> {
+    let transfers: StdResult<Vec<PendingRoleTransfer>> = PENDING_ROLE_TRANSFERS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| Ok(item?.1))
+        .collect();
+    to_json_binary(&transfers?)
+}
+
+fn get_pause_status(deps: Deps) -> StdResult<Binary> {
+    let assets: StdResult<Vec<AssetPauseStatus>> = ASSET_PAUSES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (asset, paused) = item?;
+            Ok(AssetPauseStatus { asset, paused })
+        })
+        .collect();
+
+    to_json_binary(&PauseStatusRes {
+        global: GLOBAL_PAUSE.may_load(deps.storage)?.unwrap_or(false),
+        reward_updates: REWARD_UPDATES_PAUSE
+            .may_load(deps.storage)?
+            .unwrap_or(false),
+        assets: assets?,
+    })
+}
+
+fn get_capability_holders(deps: Deps) -> StdResult<Binary> {
+    let capability_holders: StdResult<Vec<CapabilityHoldersRes>> = Capability::ALL
+        .iter()
+        .map(|capability| {
+            let holders = CAPABILITIES
+                .prefix(capability.as_str())
+                .keys(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()?;
+            Ok(CapabilityHoldersRes {
+                capability: *capability,
+                holders,
+            })
+        })
+        .collect();
+    to_json_binary(&capability_holders?)
+}
@@ -0,0 +1,174 @@
+use alliance_protocol::alliance_protocol::{Config, StakingBackend};
+use cosmwasm_std::{Coin as CwCoin, CosmosMsg, DistributionMsg, StakingMsg, Storage, Uint128};
+use cw_asset_v3::AssetInfo;
+use terra_proto_rs::alliance::alliance::{
+    MsgClaimDelegationRewards, MsgDelegate, MsgRedelegate, MsgUndelegate,
+};
+
+use crate::error::ContractError;
+use crate::state::WHITELIST;
+use crate::token_factory::{coin, stargate_msg, CustomExecuteMsg};
+
+// Builds the staking messages of the hub. `delegator` is the hub itself and `denom`
+// the bond denom returned by `bond_denom`.
+pub trait Staking {
+    fn bond_denom(&self, alliance_token_denom: &str) -> String;
+
+    fn delegate(
+        &self,
+        delegator: &str,
+        denom: &str,
+        validator: &str,
+        amount: Uint128,
+    ) -> CosmosMsg<CustomExecuteMsg>;
+
+    fn undelegate(
+        &self,
+        delegator: &str,
+        denom: &str,
+        validator: &str,
+        amount: Uint128,
+    ) -> CosmosMsg<CustomExecuteMsg>;
+
+    fn redelegate(
+        &self,
+        delegator: &str,
+        denom: &str,
+        src_validator: &str,
+        dst_validator: &str,
+        amount: Uint128,
+    ) -> CosmosMsg<CustomExecuteMsg>;
+
+    fn claim_rewards(
+        &self,
+        delegator: &str,
+        denom: &str,
+        validator: &str,
+    ) -> CosmosMsg<CustomExecuteMsg>;
+}
+
+impl Staking for StakingBackend {
+    fn bond_denom(&self, alliance_token_denom: &str) -> String {
+        match self {
+            StakingBackend::Native {
+                bond_denom: Some(bond_denom),
+            } => bond_denom.clone(),
+            _ => alliance_token_denom.to_string(),
+        }
+    }
+
+    fn delegate(
+        &self,
+        delegator: &str,
+        denom: &str,
+        validator: &str,
+        amount: Uint128,
+    ) -> CosmosMsg<CustomExecuteMsg> {
+        match self {
+            StakingBackend::Alliance => stargate_msg(
+                "/alliance.alliance.MsgDelegate",
+                MsgDelegate {
+                    amount: Some(coin(denom, amount)),
+                    delegator_address: delegator.to_string(),
+                    validator_address: validator.to_string(),
+                },
+            ),
+            StakingBackend::Native { .. } => CosmosMsg::Staking(StakingMsg::Delegate {
+                validator: validator.to_string(),
+                amount: CwCoin::new(amount.u128(), denom),
+            }),
+        }
+    }
+
+    fn undelegate(
+        &self,
+        delegator: &str,
+        denom: &str,
+        validator: &str,
+        amount: Uint128,
+    ) -> CosmosMsg<CustomExecuteMsg> {
+        match self {
+            StakingBackend::Alliance => stargate_msg(
+                "/alliance.alliance.MsgUndelegate",
+                MsgUndelegate {
+                    amount: Some(coin(denom, amount)),
+                    delegator_address: delegator.to_string(),
+                    validator_address: validator.to_string(),
+                },
+            ),
+            StakingBackend::Native { .. } => CosmosMsg::Staking(StakingMsg::Undelegate {
+                validator: validator.to_string(),
+                amount: CwCoin::new(amount.u128(), denom),
+            }),
+        }
+    }
+
+    fn redelegate(
+        &self,
+        delegator: &str,
+        denom: &str,
+        src_validator: &str,
+        dst_validator: &str,
+        amount: Uint128,
+    ) -> CosmosMsg<CustomExecuteMsg> {
+        match self {
+            StakingBackend::Alliance => stargate_msg(
+                "/alliance.alliance.MsgRedelegate",
+                MsgRedelegate {
+                    amount: Some(coin(denom, amount)),
+                    delegator_address: delegator.to_string(),
+                    validator_src_address: src_validator.to_string(),
+                    validator_dst_address: dst_validator.to_string(),
+                },
+            ),
+            StakingBackend::Native { .. } => CosmosMsg::Staking(StakingMsg::Redelegate {
+                src_validator: src_validator.to_string(),
+                dst_validator: dst_validator.to_string(),
+                amount: CwCoin::new(amount.u128(), denom),
+            }),
+        }
+    }
+
+    fn claim_rewards(
+        &self,
+        delegator: &str,
+        denom: &str,
+        validator: &str,
+    ) -> CosmosMsg<CustomExecuteMsg> {
+        match self {
+            StakingBackend::Alliance => stargate_msg(
+                "/alliance.alliance.MsgClaimDelegationRewards",
+                MsgClaimDelegationRewards {
+                    delegator_address: delegator.to_string(),
+                    validator_address: validator.to_string(),
+                    denom: denom.to_string(),
+                },
+            ),
+            StakingBackend::Native { .. } => {
+                CosmosMsg::Distribution(DistributionMsg::WithdrawDelegatorReward {
+                    validator: validator.to_string(),
+                })
+            }
+        }
+    }
+}
+
+// x/staking cannot bond the tokenfactory alliance token, so the native backend needs an
+// explicit bond denom. Its idle balance is delegated as a whole, so it cannot also be the
+// reward asset or a staked asset, which the hub owes its stakers.
+pub(crate) fn validate_bond_denom(
+    storage: &dyn Storage,
+    config: &Config,
+) -> Result<(), ContractError> {
+    if let StakingBackend::Native { bond_denom } = &config.staking_backend {
+        let bond_denom = bond_denom.as_deref().unwrap_or_default();
+        let bond_asset = AssetInfo::native(bond_denom);
+        if bond_denom.is_empty()
+            || bond_asset == config.reward_asset
+            || WHITELIST.has(storage, &bond_asset)
+        {
+            return Err(ContractError::InvalidBondDenom(bond_denom.to_string()));
+        }
+    }
+    Ok(())
+}
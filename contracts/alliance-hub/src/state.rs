@@ -1,16 +1,39 @@
 use alliance_protocol::alliance_oracle_types::ChainId;
-use alliance_protocol::alliance_protocol::{AssetDistribution, Config};
-use cosmwasm_std::{Addr, Decimal, Uint128};
+use alliance_protocol::alliance_protocol::{
+    AllianceTokenMetadata, AssetDistribution, AssetVotingWeight, ClaimFailure, Config,
+    DelegationStrategy, IbcOrigin, IbcTransfer, PausedActions, PendingRoleTransfer,
+    ValidatorDelegation,
+};
+use cosmwasm_std::{Addr, Decimal, Empty, Timestamp, Uint128};
 use cw_asset_v3::AssetInfo;
-use cw_storage_plus_120::{Item, Map};
-use std::collections::HashSet;
+use cw_storage_plus_120::{Deque, Item, Map, SnapshotItem, SnapshotMap, Strategy};
 
 pub const CONFIG: Item<Config> = Item::new("config");
+pub const ALLIANCE_TOKEN_METADATA: Item<AllianceTokenMetadata> =
+    Item::new("alliance_token_metadata");
 pub const WHITELIST: Map<&AssetInfo, ChainId> = Map::new("whitelist");
-pub const BALANCES: Map<(Addr, &AssetInfo), Uint128> = Map::new("balances");
-pub const TOTAL_BALANCES: Map<&AssetInfo, Uint128> = Map::new("total_balances");
+// Snapshotted every block for governance voting power. A balance read at a height is
+// the balance at the start of that block.
+pub const BALANCES: SnapshotMap<(Addr, &AssetInfo), Uint128> = SnapshotMap::new(
+    "balances",
+    "balances__checkpoints",
+    "balances__changelog",
+    Strategy::EveryBlock,
+);
+pub const TOTAL_BALANCES: SnapshotMap<&AssetInfo, Uint128> = SnapshotMap::new(
+    "total_balances",
+    "total_balances__checkpoints",
+    "total_balances__changelog",
+    Strategy::EveryBlock,
+);
 
-pub const VALIDATORS: Item<HashSet<String>> = Item::new("validators");
+pub const DELEGATIONS: Map<&str, ValidatorDelegation> = Map::new("delegations");
+pub const CLAIM_FAILURES: Map<&str, ClaimFailure> = Map::new("claim_failures");
+// Validators of the in-flight reward claims, in submessage order
+pub const PENDING_CLAIMS: Deque<String> = Deque::new("pending_claims");
+pub const DELEGATION_STRATEGY: Item<DelegationStrategy> = Item::new("delegation_strategy");
+// Time until which a validator cannot be the source of a redelegation
+pub const REDELEGATION_COOLDOWNS: Map<&str, Timestamp> = Map::new("redelegation_cooldowns");
 
 pub const ASSET_REWARD_DISTRIBUTION: Item<Vec<AssetDistribution>> =
     Item::new("asset_reward_distribution");
@@ -18,5 +41,42 @@ pub const ASSET_REWARD_RATE: Map<&AssetInfo, Decimal> = Map::new("asset_reward_r
 pub const USER_ASSET_REWARD_RATE: Map<(Addr, &AssetInfo), Decimal> =
     Map::new("user_asset_reward_rate");
 pub const UNCLAIMED_REWARDS: Map<(Addr, &AssetInfo), Uint128> = Map::new("unclaimed_rewards");
+// Exact sum of the unclaimed rewards and of the rewards accrued on every position, kept
+// up to date as rewards are distributed, settled and paid out
+pub const REWARD_LIABILITIES: Item<Decimal> = Item::new("reward_liabilities");
+// Reward asset x/distribution withdrew on native delegation changes, which is distributed
+// along with the rewards collected by the next reward update
+pub const WITHDRAWN_REWARDS: Item<Uint128> = Item::new("withdrawn_rewards");
 
+// Role transfers proposed by the current holder (or governance), keyed by the role name
+pub const PENDING_ROLE_TRANSFERS: Map<&str, PendingRoleTransfer> =
+    Map::new("pending_role_transfers");
+
+// Permission registry keyed by (capability, address)
+pub const CAPABILITIES: Map<(&str, &Addr), Empty> = Map::new("capabilities");
+
+pub const GLOBAL_PAUSE: Item<bool> = Item::new("global_pause");
+pub const REWARD_UPDATES_PAUSE: Item<bool> = Item::new("reward_updates_pause");
+pub const ASSET_PAUSES: Map<&AssetInfo, PausedActions> = Map::new("asset_pauses");
+
+// DAO using the hub as its voting module, governance when unset
+pub const VOTING_DAO: Item<Addr> = Item::new("voting_dao");
+// Snapshotted like the balances, so past voting power uses the weights of its height
+pub const VOTING_WEIGHTS: SnapshotItem<Vec<AssetVotingWeight>> = SnapshotItem::new(
+    "voting_weights",
+    "voting_weights__checkpoints",
+    "voting_weights__changelog",
+    Strategy::EveryBlock,
+);
+
+// Contracts notified of staked balance changes along with the gas limit of their calls
+pub const HOOKS: Map<&Addr, u64> = Map::new("hooks");
+
+// Origin chain sender of the ibc-hooks intermediate addresses that staked with the hub
+pub const REMOTE_IDENTITIES: Map<&Addr, IbcOrigin> = Map::new("remote_identities");
+// In-flight IBC transfers keyed by (source channel, packet sequence)
+pub const IBC_TRANSFERS: Map<(&str, u64), IbcTransfer> = Map::new("ibc_transfers");
+pub const TEMP_IBC_TRANSFER: Item<(String, IbcTransfer)> = Item::new("temp_ibc_transfer");
 pub const TEMP_BALANCE: Item<Uint128> = Item::new("temp_balance");
+pub const TEMP_STAKING_BALANCE: Item<Uint128> = Item::new("temp_staking_balance");
+pub const TEMP_KEEPER: Item<Addr> = Item::new("temp_keeper");
@@ -1,14 +1,16 @@
 use crate::contract::execute;
 use crate::error::ContractError;
-use crate::state::{CONFIG, VALIDATORS};
+use crate::query::query;
+use crate::state::{CONFIG, DELEGATIONS};
 use crate::tests::helpers::{
-    alliance_delegate, alliance_redelegate, alliance_undelegate, setup_contract,
+    alliance_delegate, alliance_redelegate, alliance_undelegate, query_delegations, setup_contract,
 };
 use alliance_protocol::alliance_protocol::{
-    AllianceDelegateMsg, AllianceDelegation, AllianceUndelegateMsg, Config, ExecuteMsg,
+    AllianceDelegateMsg, AllianceDelegation, AllianceUndelegateMsg, Config, ExecuteMsg, QueryMsg,
+    Unbonding, ValidatorDelegation, DEFAULT_UNBONDING_PERIOD,
 };
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{Binary, CosmosMsg, StdResult, SubMsg, Uint128};
+use cosmwasm_std::{from_json, Binary, CosmosMsg, StdResult, SubMsg, Uint128};
 use std::collections::HashSet;
 use terra_proto_rs::alliance::alliance::{MsgDelegate, MsgRedelegate};
 use terra_proto_rs::cosmos::base::v1beta1::Coin;
@@ -67,10 +69,18 @@ fn test_alliance_delegate() {
         ]
     );
 
-    let validators = VALIDATORS.load(deps.as_ref().storage).unwrap();
     assert_eq!(
-        validators,
-        HashSet::from(["validator1".to_string(), "validator2".to_string()])
+        query_delegations(deps.as_ref()),
+        vec![
+            ValidatorDelegation {
+                delegated: Uint128::new(100),
+                ..ValidatorDelegation::new("validator1".to_string())
+            },
+            ValidatorDelegation {
+                delegated: Uint128::new(400),
+                ..ValidatorDelegation::new("validator2".to_string())
+            },
+        ]
     );
 }
 
@@ -134,6 +144,10 @@ fn test_alliance_undelegate() {
             })
         })
         .unwrap();
+    alliance_delegate(
+        deps.as_mut(),
+        vec![("validator1", 100), ("validator2", 500)],
+    );
 
     let res = alliance_undelegate(
         deps.as_mut(),
@@ -174,6 +188,54 @@ fn test_alliance_undelegate() {
             }),
         ]
     );
+
+    // The undelegations stay in the ledger until they complete
+    let completes_at = mock_env().block.time.plus_seconds(DEFAULT_UNBONDING_PERIOD);
+    assert_eq!(
+        query_delegations(deps.as_ref()),
+        vec![
+            ValidatorDelegation {
+                undelegating: vec![Unbonding {
+                    amount: Uint128::new(100),
+                    completes_at,
+                }],
+                ..ValidatorDelegation::new("validator1".to_string())
+            },
+            ValidatorDelegation {
+                delegated: Uint128::new(100),
+                undelegating: vec![Unbonding {
+                    amount: Uint128::new(400),
+                    completes_at,
+                }],
+                ..ValidatorDelegation::new("validator2".to_string())
+            },
+        ]
+    );
+    let validators: HashSet<String> =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::Validators {}).unwrap()).unwrap();
+    assert_eq!(validators, HashSet::from(["validator2".to_string()]));
+
+    // validator1 is pruned from the ledger once its undelegation completed
+    let mut env = mock_env();
+    env.block.time = completes_at;
+    assert_eq!(
+        from_json::<Vec<ValidatorDelegation>>(
+            query(deps.as_ref(), env.clone(), QueryMsg::Delegations {}).unwrap()
+        )
+        .unwrap(),
+        vec![ValidatorDelegation {
+            delegated: Uint128::new(100),
+            ..ValidatorDelegation::new("validator2".to_string())
+        }]
+    );
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("user", &[]),
+        ExecuteMsg::UpdateRewards {},
+    )
+    .unwrap();
+    assert!(!DELEGATIONS.has(deps.as_ref().storage, "validator1"));
 }
 
 #[test]
@@ -219,6 +281,43 @@ fn test_alliance_undelegation_invalid() {
     )
     .unwrap_err();
     assert_eq!(err, ContractError::EmptyDelegation {});
+
+    // Undelegations above the recorded delegation are refused
+    alliance_delegate(deps.as_mut(), vec![("validator1", 100)]);
+    let msg = AllianceUndelegateMsg {
+        undelegations: vec![AllianceDelegation {
+            validator: "validator1".to_string(),
+            amount: Uint128::new(101),
+        }],
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller", &[]),
+        ExecuteMsg::AllianceUndelegate(msg),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientDelegation("validator1".to_string())
+    );
+    let msg = AllianceUndelegateMsg {
+        undelegations: vec![AllianceDelegation {
+            validator: "validator2".to_string(),
+            amount: Uint128::new(1),
+        }],
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller", &[]),
+        ExecuteMsg::AllianceUndelegate(msg),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientDelegation("validator2".to_string())
+    );
 }
 
 #[test]
@@ -236,6 +335,10 @@ fn test_alliance_redelegate() {
             })
         })
         .unwrap();
+    alliance_delegate(
+        deps.as_mut(),
+        vec![("validator1", 100), ("validator2", 400)],
+    );
 
     let res = alliance_redelegate(
         deps.as_mut(),
@@ -281,9 +384,35 @@ fn test_alliance_redelegate() {
             }),
         ]
     );
-    let validators = VALIDATORS.load(deps.as_ref().storage).unwrap();
+    let validators: HashSet<String> =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::Validators {}).unwrap()).unwrap();
     assert_eq!(
         validators,
         HashSet::from(["validator2".to_string(), "validator3".to_string()])
     );
+    let completes_at = mock_env().block.time.plus_seconds(DEFAULT_UNBONDING_PERIOD);
+    assert_eq!(
+        query_delegations(deps.as_ref()),
+        vec![
+            ValidatorDelegation {
+                redelegating: vec![Unbonding {
+                    amount: Uint128::new(100),
+                    completes_at,
+                }],
+                ..ValidatorDelegation::new("validator1".to_string())
+            },
+            ValidatorDelegation {
+                delegated: Uint128::new(100),
+                redelegating: vec![Unbonding {
+                    amount: Uint128::new(400),
+                    completes_at,
+                }],
+                ..ValidatorDelegation::new("validator2".to_string())
+            },
+            ValidatorDelegation {
+                delegated: Uint128::new(400),
+                ..ValidatorDelegation::new("validator3".to_string())
+            },
+        ]
+    );
 }
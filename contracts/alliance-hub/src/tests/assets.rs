@@ -2,7 +2,7 @@ use crate::contract::execute;
 use crate::error::ContractError;
 use crate::query::query;
 use crate::state::WHITELIST;
-use crate::tests::helpers::{remove_assets, setup_contract, whitelist_assets};
+use crate::tests::helpers::{hub_event, remove_assets, setup_contract, whitelist_assets};
 use alliance_protocol::alliance_protocol::{ExecuteMsg, QueryMsg, WhitelistedAssetsResponse};
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
 use cosmwasm_std::{from_json, Response};
@@ -22,11 +22,16 @@ fn test_whitelist_assets() {
     );
     assert_eq!(
         res,
-        Response::default().add_attributes(vec![
-            ("action", "whitelist_assets"),
-            ("chain_id", "chain-1"),
-            ("assets", "native:asset1")
-        ])
+        Response::default()
+            .add_attributes(vec![
+                ("action", "whitelist_assets"),
+                ("chain_id", "chain-1"),
+                ("assets", "native:asset1")
+            ])
+            .add_event(hub_event(
+                "asset_whitelisted",
+                vec![("asset", "native:asset1"), ("chain_id", "chain-1")]
+            ))
     );
 
     let res = whitelist_assets(
@@ -41,11 +46,22 @@ fn test_whitelist_assets() {
     );
     assert_eq!(
         res,
-        Response::default().add_attributes(vec![
-            ("action", "whitelist_assets"),
-            ("chain_id", "chain-1"),
-            ("assets", "native:asset2,native:asset3")
-        ])
+        Response::default()
+            .add_attributes(vec![
+                ("action", "whitelist_assets"),
+                ("chain_id", "chain-1"),
+                ("assets", "native:asset2,native:asset3")
+            ])
+            .add_events(vec![
+                hub_event(
+                    "asset_whitelisted",
+                    vec![("asset", "native:asset2"), ("chain_id", "chain-1")]
+                ),
+                hub_event(
+                    "asset_whitelisted",
+                    vec![("asset", "native:asset3"), ("chain_id", "chain-1")]
+                ),
+            ])
     );
 
     let chain_id = WHITELIST
@@ -57,7 +73,7 @@ fn test_whitelist_assets() {
     assert_eq!(chain_id, "chain-1".to_string());
 
     let res: WhitelistedAssetsResponse =
-        from_json(&query(deps.as_ref(), mock_env(), QueryMsg::WhitelistedAssets {}).unwrap())
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::WhitelistedAssets {}).unwrap())
             .unwrap();
     assert_eq!(
         res,
@@ -107,10 +123,12 @@ fn test_remove_assets() {
     let response = remove_assets(deps.as_mut(), vec![AssetInfo::Native("asset1".to_string())]);
     assert_eq!(
         response,
-        Response::default().add_attributes(vec![
-            ("action", "remove_assets"),
-            ("assets", "native:asset1")
-        ])
+        Response::default()
+            .add_attributes(vec![
+                ("action", "remove_assets"),
+                ("assets", "native:asset1")
+            ])
+            .add_event(hub_event("asset_removed", vec![("asset", "native:asset1")]))
     );
 
     WHITELIST
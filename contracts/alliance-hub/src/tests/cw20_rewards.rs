@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use alliance_protocol::alliance_protocol::{
+    AssetDistribution, Config, Cw20HookMsg, ExecuteMsg, MigrateMsg, QueryMsg,
+};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockQuerier};
+use cosmwasm_std::{
+    coin, from_json, to_json_binary, Addr, ContractResult, CosmosMsg, Decimal, Storage, SubMsg,
+    SystemResult, Uint128, WasmMsg, WasmQuery,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_asset_v3::AssetInfo;
+
+use crate::contract::execute;
+use crate::error::ContractError;
+use crate::migrations::migrate_reward_asset;
+use crate::query::query;
+use crate::state::{ASSET_REWARD_DISTRIBUTION, CONFIG, TEMP_BALANCE};
+use crate::tests::helpers::{
+    claim_rewards, set_alliance_asset, setup_contract, stake, whitelist_assets,
+};
+
+fn set_cw20_balance(querier: &mut MockQuerier, balance: u128) {
+    querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, .. } if contract_addr == "reward_token" => {
+            SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&BalanceResponse {
+                    balance: Uint128::new(balance),
+                })
+                .unwrap(),
+            ))
+        }
+        _ => panic!("unexpected query"),
+    });
+}
+
+fn cw20_update_rewards(amount: u128) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "keeper".to_string(),
+        amount: Uint128::new(amount),
+        msg: to_json_binary(&Cw20HookMsg::UpdateRewards {}).unwrap(),
+    })
+}
+
+#[test]
+fn test_update_reward_asset() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    let reward_token = AssetInfo::Cw20(Addr::unchecked("reward_token"));
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller", &[]),
+        ExecuteMsg::UpdateRewardAsset(reward_token.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::UpdateRewardAsset(AssetInfo::native("")),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidRewardAsset("native:".to_string())
+    );
+
+    // Rewards owed in uluna have to be claimed first
+    set_alliance_asset(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("aWHALE")])]),
+    );
+    stake(deps.as_mut(), "user1", 1000, "aWHALE");
+    ASSET_REWARD_DISTRIBUTION
+        .save(
+            deps.as_mut().storage,
+            &vec![AssetDistribution {
+                asset: AssetInfo::native("aWHALE"),
+                distribution: Decimal::percent(100),
+            }],
+        )
+        .unwrap();
+    deps.querier
+        .update_balance("cosmos2contract", vec![coin(100, "uluna")]);
+    TEMP_BALANCE
+        .save(deps.as_mut().storage, &Uint128::zero())
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("cosmos2contract", &[]),
+        ExecuteMsg::UpdateRewardsCallback {},
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::UpdateRewardAsset(reward_token.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::RewardLiabilitiesOutstanding(Uint128::new(100))
+    );
+
+    claim_rewards(deps.as_mut(), "user1", "aWHALE");
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::UpdateRewardAsset(reward_token.clone()),
+    )
+    .unwrap();
+    let config: Config =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.reward_asset, reward_token);
+}
+
+#[test]
+fn test_cw20_rewards() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("aWHALE")])]),
+    );
+    stake(deps.as_mut(), "user1", 1000, "aWHALE");
+    ASSET_REWARD_DISTRIBUTION
+        .save(
+            deps.as_mut().storage,
+            &vec![AssetDistribution {
+                asset: AssetInfo::native("aWHALE"),
+                distribution: Decimal::percent(100),
+            }],
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::UpdateRewardAsset(AssetInfo::Cw20(Addr::unchecked("reward_token"))),
+    )
+    .unwrap();
+
+    // Only the reward token can be sent along with an update
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("other_token", &[]),
+        cw20_update_rewards(100),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidRewardAsset("other_token".to_string())
+    );
+
+    // The 100 tokens sent with the update are distributed with the claimed rewards
+    set_cw20_balance(&mut deps.querier, 1000);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("reward_token", &[]),
+        cw20_update_rewards(100),
+    )
+    .unwrap();
+    assert_eq!(
+        TEMP_BALANCE.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(900)
+    );
+
+    set_cw20_balance(&mut deps.querier, 1500);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("cosmos2contract", &[]),
+        ExecuteMsg::UpdateRewardsCallback {},
+    )
+    .unwrap();
+
+    let res = claim_rewards(deps.as_mut(), "user1", "aWHALE");
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "reward_token".to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user1".to_string(),
+                amount: Uint128::new(600),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+}
+
+#[test]
+fn test_migrate_reward_denom() {
+    let mut deps = mock_dependencies();
+    deps.storage.set(
+        b"config",
+        br#"{"governance":"gov","controller":"controller","oracle":"oracle","operator":"operator","last_reward_update_timestamp":"0","alliance_token_denom":"ualliance","alliance_token_supply":"1000","reward_denom":"uluna"}"#,
+    );
+
+    migrate_reward_asset(deps.as_mut(), &MigrateMsg::default()).unwrap();
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(config.reward_asset, AssetInfo::native("uluna"));
+    assert_eq!(config.alliance_token_supply, Uint128::new(1000));
+
+    // Already migrated configs are kept
+    migrate_reward_asset(deps.as_mut(), &MigrateMsg::default()).unwrap();
+    assert_eq!(CONFIG.load(deps.as_ref().storage).unwrap(), config);
+}
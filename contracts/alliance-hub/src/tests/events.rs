@@ -0,0 +1,253 @@
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{DepsMut, Event, Response};
+
+use alliance_protocol::alliance_protocol::{Capability, ExecuteMsg, PauseScope, Role};
+
+use crate::contract::execute;
+use crate::tests::helpers::{
+    alliance_delegate, alliance_redelegate, alliance_undelegate, asset_distribution_1, hub_event,
+    set_alliance_asset, setup_contract,
+};
+use crate::token_factory::CustomExecuteMsg;
+
+fn execute_events(sender: &str, deps: DepsMut, msg: ExecuteMsg) -> Vec<Event> {
+    let res: Response<CustomExecuteMsg> =
+        execute(deps, mock_env(), mock_info(sender, &[]), msg).unwrap();
+    res.events
+}
+
+#[test]
+fn test_delegation_changed_events() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+
+    let res = alliance_delegate(
+        deps.as_mut(),
+        vec![("validator1", 100), ("validator2", 400)],
+    );
+    assert_eq!(
+        res.events,
+        vec![
+            hub_event(
+                "delegation_changed",
+                vec![
+                    ("kind", "delegate"),
+                    ("validator", "validator1"),
+                    ("amount", "100"),
+                    ("delegated", "100"),
+                ]
+            ),
+            hub_event(
+                "delegation_changed",
+                vec![
+                    ("kind", "delegate"),
+                    ("validator", "validator2"),
+                    ("amount", "400"),
+                    ("delegated", "400"),
+                ]
+            ),
+        ]
+    );
+
+    let res = alliance_redelegate(deps.as_mut(), vec![("validator2", "validator1", 150)]);
+    assert_eq!(
+        res.events,
+        vec![hub_event(
+            "delegation_changed",
+            vec![
+                ("kind", "redelegate"),
+                ("validator", "validator2"),
+                ("dst_validator", "validator1"),
+                ("amount", "150"),
+                ("delegated", "250"),
+                ("dst_delegated", "250"),
+            ]
+        )]
+    );
+
+    let res = alliance_undelegate(deps.as_mut(), vec![("validator1", 250)]);
+    assert_eq!(
+        res.events,
+        vec![hub_event(
+            "delegation_changed",
+            vec![
+                ("kind", "undelegate"),
+                ("validator", "validator1"),
+                ("amount", "250"),
+                ("delegated", "0"),
+            ]
+        )]
+    );
+}
+
+#[test]
+fn test_distribution_updated_event() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        ExecuteMsg::SetAssetRewardDistribution(asset_distribution_1()),
+    )
+    .unwrap();
+    assert_eq!(
+        res.events,
+        vec![hub_event(
+            "distribution_updated",
+            vec![(
+                "distribution",
+                r#"[{"asset":{"native":"aWHALE"},"distribution":"0.5"},{"asset":{"native":"bWHALE"},"distribution":"0.5"}]"#
+            )]
+        )]
+    );
+}
+
+#[test]
+fn test_role_transfer_events() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    let expiry = mock_env()
+        .block
+        .time
+        .plus_seconds(100)
+        .seconds()
+        .to_string();
+
+    let events = execute_events(
+        "gov",
+        deps.as_mut(),
+        ExecuteMsg::ProposeRoleTransfer {
+            role: Role::Operator,
+            new_address: "operator2".to_string(),
+            expires_in: 100,
+        },
+    );
+    assert_eq!(
+        events,
+        vec![hub_event(
+            "role_transfer",
+            vec![
+                ("kind", "propose"),
+                ("role", "operator"),
+                ("new_address", "operator2"),
+                ("expiry", &expiry),
+            ]
+        )]
+    );
+
+    let events = execute_events(
+        "operator2",
+        deps.as_mut(),
+        ExecuteMsg::AcceptRoleTransfer {
+            role: Role::Operator,
+        },
+    );
+    assert_eq!(
+        events,
+        vec![hub_event(
+            "role_transfer",
+            vec![
+                ("kind", "accept"),
+                ("role", "operator"),
+                ("previous_address", "operator"),
+                ("new_address", "operator2"),
+            ]
+        )]
+    );
+
+    execute_events(
+        "gov",
+        deps.as_mut(),
+        ExecuteMsg::ProposeRoleTransfer {
+            role: Role::Oracle,
+            new_address: "oracle2".to_string(),
+            expires_in: 100,
+        },
+    );
+    let events = execute_events(
+        "gov",
+        deps.as_mut(),
+        ExecuteMsg::CancelRoleTransfer { role: Role::Oracle },
+    );
+    assert_eq!(
+        events,
+        vec![hub_event(
+            "role_transfer",
+            vec![("kind", "cancel"), ("role", "oracle")]
+        )]
+    );
+}
+
+#[test]
+fn test_capability_and_pause_events() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+
+    let events = execute_events(
+        "gov",
+        deps.as_mut(),
+        ExecuteMsg::GrantCapability {
+            address: "keeper".to_string(),
+            capability: Capability::Pause,
+        },
+    );
+    assert_eq!(
+        events,
+        vec![hub_event(
+            "capability_changed",
+            vec![
+                ("address", "keeper"),
+                ("capability", "pause"),
+                ("granted", "true"),
+            ]
+        )]
+    );
+
+    let events = execute_events(
+        "keeper",
+        deps.as_mut(),
+        ExecuteMsg::Pause(PauseScope::Global {}),
+    );
+    assert_eq!(
+        events,
+        vec![hub_event(
+            "pause_changed",
+            vec![("scope", "global"), ("paused", "true")]
+        )]
+    );
+    let events = execute_events(
+        "gov",
+        deps.as_mut(),
+        ExecuteMsg::Unpause(PauseScope::Global {}),
+    );
+    assert_eq!(
+        events,
+        vec![hub_event(
+            "pause_changed",
+            vec![("scope", "global"), ("paused", "false")]
+        )]
+    );
+
+    let events = execute_events(
+        "gov",
+        deps.as_mut(),
+        ExecuteMsg::RevokeCapability {
+            address: "keeper".to_string(),
+            capability: Capability::Pause,
+        },
+    );
+    assert_eq!(
+        events,
+        vec![hub_event(
+            "capability_changed",
+            vec![
+                ("address", "keeper"),
+                ("capability", "pause"),
+                ("granted", "false"),
+            ]
+        )]
+    );
+}
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use cosmwasm_std::testing::{mock_env, mock_info};
 use cosmwasm_std::{
-    coin, from_json, to_json_binary, Decimal, Deps, DepsMut, Response, StdResult, Uint128,
+    coin, from_json, to_json_binary, Decimal, Deps, DepsMut, Event, Response, StdResult, Uint128,
 };
 use cw20::Cw20ReceiveMsg;
 use cw_asset_v3::{Asset, AssetInfo};
@@ -12,6 +12,7 @@ use alliance_protocol::alliance_protocol::{
     AllPendingRewardsQuery, AllianceDelegateMsg, AllianceDelegation, AllianceRedelegateMsg,
     AllianceRedelegation, AllianceUndelegateMsg, AssetDistribution, AssetQuery, Config,
     Cw20HookMsg, ExecuteMsg, InstantiateMsg, PendingRewardsRes, QueryMsg, StakedBalanceRes,
+    ValidatorDelegation,
 };
 
 use crate::contract::{execute, instantiate};
@@ -21,6 +22,12 @@ use crate::token_factory::CustomExecuteMsg;
 
 pub const DENOM: &str = "token_factory/token";
 
+pub fn hub_event(name: &str, attributes: Vec<(&str, &str)>) -> Event {
+    Event::new(format!("alliance_hub/{}", name))
+        .add_attribute("schema_version", "1")
+        .add_attributes(attributes)
+}
+
 pub fn setup_contract(deps: DepsMut) -> Response<CustomExecuteMsg> {
     let info = mock_info("admin", &[]);
     let env = mock_env();
@@ -31,7 +38,11 @@ pub fn setup_contract(deps: DepsMut) -> Response<CustomExecuteMsg> {
         oracle: "oracle".to_string(),
         operator: "operator".to_string(),
         alliance_token_denom: "ualliance".to_string(),
-        reward_denom: "uluna".to_string(),
+        reward_asset: AssetInfo::native("uluna"),
+        alliance_token_metadata: None,
+        token_factory_backend: None,
+        staking_backend: None,
+        unbonding_period: None,
     };
     instantiate(deps, env, info, init_msg).unwrap()
 }
@@ -48,7 +59,10 @@ pub fn set_alliance_asset(deps: DepsMut) {
         .unwrap();
 }
 
-pub fn whitelist_assets(deps: DepsMut, assets: HashMap<ChainId, Vec<AssetInfo>>) -> Response {
+pub fn whitelist_assets(
+    deps: DepsMut,
+    assets: HashMap<ChainId, Vec<AssetInfo>>,
+) -> Response<CustomExecuteMsg> {
     let info = mock_info("gov", &[]);
     let env = mock_env();
 
@@ -56,7 +70,7 @@ pub fn whitelist_assets(deps: DepsMut, assets: HashMap<ChainId, Vec<AssetInfo>>)
     execute(deps, env, info, msg).unwrap()
 }
 
-pub fn remove_assets(deps: DepsMut, assets: Vec<AssetInfo>) -> Response {
+pub fn remove_assets(deps: DepsMut, assets: Vec<AssetInfo>) -> Response<CustomExecuteMsg> {
     let info = mock_info("gov", &[]);
     let env = mock_env();
 
@@ -64,14 +78,22 @@ pub fn remove_assets(deps: DepsMut, assets: Vec<AssetInfo>) -> Response {
     execute(deps, env, info, msg).unwrap()
 }
 
-pub fn stake(deps: DepsMut, user: &str, amount: u128, denom: &str) -> Response {
+pub fn stake(deps: DepsMut, user: &str, amount: u128, denom: &str) -> Response<CustomExecuteMsg> {
     let info = mock_info(user, &[coin(amount, denom)]);
     let env = mock_env();
-    let msg = ExecuteMsg::Stake {};
+    let msg = ExecuteMsg::Stake {
+        recipient: None,
+        origin: None,
+    };
     execute(deps, env, info, msg).unwrap()
 }
 
-pub fn stake_cw20(deps: DepsMut, user: &str, amount: u128, denom: &str) -> Response {
+pub fn stake_cw20(
+    deps: DepsMut,
+    user: &str,
+    amount: u128,
+    denom: &str,
+) -> Response<CustomExecuteMsg> {
     let info = mock_info(denom, &[]);
     let env = mock_env();
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
@@ -82,21 +104,29 @@ pub fn stake_cw20(deps: DepsMut, user: &str, amount: u128, denom: &str) -> Respo
     execute(deps, env, info, msg).unwrap()
 }
 
-pub fn unstake(deps: DepsMut, user: &str, amount: u128, denom: &str) -> Response {
+pub fn unstake(deps: DepsMut, user: &str, amount: u128, denom: &str) -> Response<CustomExecuteMsg> {
     let info = mock_info(user, &[]);
     let env = mock_env();
     let msg = ExecuteMsg::Unstake(Asset::native(denom, amount));
     execute(deps, env, info, msg).unwrap()
 }
 
-pub fn unstake_cw20(deps: DepsMut, user: &str, amount: u128, denom: &str) -> Response {
+pub fn unstake_cw20(
+    deps: DepsMut,
+    user: &str,
+    amount: u128,
+    denom: &str,
+) -> Response<CustomExecuteMsg> {
     let info = mock_info(user, &[]);
     let env = mock_env();
     let msg = ExecuteMsg::Unstake(Asset::cw20(deps.api.addr_validate(denom).unwrap(), amount));
     execute(deps, env, info, msg).unwrap()
 }
 
-pub fn alliance_delegate(deps: DepsMut, delegations: Vec<(&str, u128)>) -> Response {
+pub fn alliance_delegate(
+    deps: DepsMut,
+    delegations: Vec<(&str, u128)>,
+) -> Response<CustomExecuteMsg> {
     let info = mock_info("controller", &[]);
     let env = mock_env();
     let delegations: Vec<AllianceDelegation> = delegations
@@ -110,7 +140,10 @@ pub fn alliance_delegate(deps: DepsMut, delegations: Vec<(&str, u128)>) -> Respo
     execute(deps, env, info, msg).unwrap()
 }
 
-pub fn alliance_undelegate(deps: DepsMut, delegations: Vec<(&str, u128)>) -> Response {
+pub fn alliance_undelegate(
+    deps: DepsMut,
+    delegations: Vec<(&str, u128)>,
+) -> Response<CustomExecuteMsg> {
     let info = mock_info("controller", &[]);
     let env = mock_env();
     let delegations: Vec<AllianceDelegation> = delegations
@@ -126,7 +159,10 @@ pub fn alliance_undelegate(deps: DepsMut, delegations: Vec<(&str, u128)>) -> Res
     execute(deps, env, info, msg).unwrap()
 }
 
-pub fn alliance_redelegate(deps: DepsMut, redelegations: Vec<(&str, &str, u128)>) -> Response {
+pub fn alliance_redelegate(
+    deps: DepsMut,
+    redelegations: Vec<(&str, &str, u128)>,
+) -> Response<CustomExecuteMsg> {
     let info = mock_info("controller", &[]);
     let env = mock_env();
     let redelegations: Vec<AllianceRedelegation> = redelegations
@@ -141,7 +177,7 @@ pub fn alliance_redelegate(deps: DepsMut, redelegations: Vec<(&str, &str, u128)>
     execute(deps, env, info, msg).unwrap()
 }
 
-pub fn claim_rewards(deps: DepsMut, user: &str, denom: &str) -> Response {
+pub fn claim_rewards(deps: DepsMut, user: &str, denom: &str) -> Response<CustomExecuteMsg> {
     let info = mock_info(user, &[]);
     let env = mock_env();
     let msg = ExecuteMsg::ClaimRewards(AssetInfo::Native(denom.to_string()));
@@ -150,7 +186,7 @@ pub fn claim_rewards(deps: DepsMut, user: &str, denom: &str) -> Response {
 
 pub fn query_rewards(deps: Deps, user: &str, denom: &str) -> PendingRewardsRes {
     from_json(
-        &query(
+        query(
             deps,
             mock_env(),
             QueryMsg::PendingRewards(AssetQuery {
@@ -165,7 +201,7 @@ pub fn query_rewards(deps: Deps, user: &str, denom: &str) -> PendingRewardsRes {
 
 pub fn query_all_rewards(deps: Deps, user: &str) -> Vec<PendingRewardsRes> {
     from_json(
-        &query(
+        query(
             deps,
             mock_env(),
             QueryMsg::AllPendingRewards(AllPendingRewardsQuery {
@@ -178,11 +214,15 @@ pub fn query_all_rewards(deps: Deps, user: &str) -> Vec<PendingRewardsRes> {
 }
 
 pub fn query_all_staked_balances(deps: Deps) -> Vec<StakedBalanceRes> {
-    from_json(&query(deps, mock_env(), QueryMsg::TotalStakedBalances {}).unwrap()).unwrap()
+    from_json(query(deps, mock_env(), QueryMsg::TotalStakedBalances {}).unwrap()).unwrap()
+}
+
+pub fn query_delegations(deps: Deps) -> Vec<ValidatorDelegation> {
+    from_json(query(deps, mock_env(), QueryMsg::Delegations {}).unwrap()).unwrap()
 }
 
 pub fn query_asset_reward_distribution(deps: Deps) -> Vec<AssetDistribution> {
-    from_json(&query(deps, mock_env(), QueryMsg::RewardDistribution {}).unwrap()).unwrap()
+    from_json(query(deps, mock_env(), QueryMsg::RewardDistribution {}).unwrap()).unwrap()
 }
 
 #[inline]
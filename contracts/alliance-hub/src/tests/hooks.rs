@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use alliance_protocol::alliance_protocol::{
+    ExecuteMsg, HookRes, IbcDestination, IbcLifecycleComplete, IbcTransfer, IbcTransferKind,
+    QueryMsg, StakeChangedHookMsg, SudoMsg,
+};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{from_json, Addr, DepsMut, Reply, Response, SubMsg, SubMsgResult, Uint128};
+use cw_asset_v3::{Asset, AssetInfo};
+
+use crate::contract::{execute, reply, sudo};
+use crate::error::ContractError;
+use crate::query::query;
+use crate::state::IBC_TRANSFERS;
+use crate::tests::helpers::{setup_contract, stake, unstake, whitelist_assets};
+use crate::token_factory::CustomExecuteMsg;
+
+fn update_hook(
+    deps: DepsMut,
+    sender: &str,
+    msg: ExecuteMsg,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    execute(deps, mock_env(), mock_info(sender, &[]), msg)
+}
+
+fn hook_msg(hook: &str, old: u128, new: u128, gas_limit: u64) -> SubMsg<CustomExecuteMsg> {
+    let msg = StakeChangedHookMsg {
+        user: Addr::unchecked("user1"),
+        asset: AssetInfo::native("asset1"),
+        old: Uint128::new(old),
+        new: Uint128::new(new),
+    }
+    .into_cosmos_msg(hook)
+    .unwrap();
+    SubMsg::reply_on_error(msg, 4).with_gas_limit(gas_limit)
+}
+
+#[test]
+fn test_update_hooks() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+
+    let add_hook = |addr: &str, gas_limit: u64| ExecuteMsg::AddHook {
+        addr: addr.to_string(),
+        gas_limit,
+    };
+    let err = update_hook(deps.as_mut(), "controller", add_hook("hook1", 100_000)).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = update_hook(deps.as_mut(), "gov", add_hook("hook1", 0)).unwrap_err();
+    assert_eq!(err, ContractError::InvalidHookGasLimit {});
+
+    update_hook(deps.as_mut(), "gov", add_hook("hook1", 100_000)).unwrap();
+    update_hook(deps.as_mut(), "gov", add_hook("hook2", 200_000)).unwrap();
+    let err = update_hook(deps.as_mut(), "gov", add_hook("hook1", 100_000)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::HookAlreadyRegistered("hook1".to_string())
+    );
+
+    let hooks: Vec<HookRes> =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::Hooks {}).unwrap()).unwrap();
+    assert_eq!(
+        hooks,
+        vec![
+            HookRes {
+                addr: Addr::unchecked("hook1"),
+                gas_limit: 100_000,
+            },
+            HookRes {
+                addr: Addr::unchecked("hook2"),
+                gas_limit: 200_000,
+            },
+        ]
+    );
+
+    let remove_hook = |addr: &str| ExecuteMsg::RemoveHook {
+        addr: addr.to_string(),
+    };
+    update_hook(deps.as_mut(), "gov", remove_hook("hook1")).unwrap();
+    let err = update_hook(deps.as_mut(), "gov", remove_hook("hook1")).unwrap_err();
+    assert_eq!(err, ContractError::HookNotRegistered("hook1".to_string()));
+
+    let hooks: Vec<HookRes> =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::Hooks {}).unwrap()).unwrap();
+    assert_eq!(
+        hooks,
+        vec![HookRes {
+            addr: Addr::unchecked("hook2"),
+            gas_limit: 200_000,
+        }]
+    );
+}
+
+#[test]
+fn test_stake_changed_hooks() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("asset1")])]),
+    );
+
+    // Nothing to notify without hooks
+    let res = stake(deps.as_mut(), "user1", 100, "asset1");
+    assert!(res.messages.is_empty());
+
+    for (hook, gas_limit) in [("hook1", 100_000), ("hook2", 200_000)] {
+        update_hook(
+            deps.as_mut(),
+            "gov",
+            ExecuteMsg::AddHook {
+                addr: hook.to_string(),
+                gas_limit,
+            },
+        )
+        .unwrap();
+    }
+
+    let res = stake(deps.as_mut(), "user1", 50, "asset1");
+    assert_eq!(
+        res.messages,
+        vec![
+            hook_msg("hook1", 100, 150, 100_000),
+            hook_msg("hook2", 100, 150, 200_000),
+        ]
+    );
+
+    let res = unstake(deps.as_mut(), "user1", 150, "asset1");
+    assert_eq!(
+        res.messages[..2],
+        [
+            hook_msg("hook1", 150, 0, 100_000),
+            hook_msg("hook2", 150, 0, 200_000),
+        ]
+    );
+}
+
+#[test]
+fn test_ibc_refund_stake_changed_hooks() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("asset1")])]),
+    );
+    stake(deps.as_mut(), "user1", 100, "asset1");
+    update_hook(
+        deps.as_mut(),
+        "gov",
+        ExecuteMsg::AddHook {
+            addr: "hook1".to_string(),
+            gas_limit: 100_000,
+        },
+    )
+    .unwrap();
+
+    let res = update_hook(
+        deps.as_mut(),
+        "user1",
+        ExecuteMsg::UnstakeOverIbc {
+            asset: Asset::native("asset1", 60u128),
+            destination: IbcDestination {
+                channel_id: "channel-1".to_string(),
+                receiver: "osmo1receiver".to_string(),
+                timeout_seconds: 600,
+            },
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages[0], hook_msg("hook1", 100, 40, 100_000));
+    IBC_TRANSFERS
+        .save(
+            deps.as_mut().storage,
+            ("channel-1", 7),
+            &IbcTransfer {
+                user: Addr::unchecked("user1"),
+                asset: AssetInfo::native("asset1"),
+                amount: Uint128::new(60),
+                kind: IbcTransferKind::Unstake,
+            },
+        )
+        .unwrap();
+
+    // The refund stakes the funds again and notifies the hooks like a stake
+    let res = sudo(
+        deps.as_mut(),
+        mock_env(),
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcTimeout {
+            channel: "channel-1".to_string(),
+            sequence: 7,
+        }),
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![hook_msg("hook1", 40, 100, 100_000)]);
+}
+
+#[test]
+fn test_stake_hook_error() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+
+    // A failed hook does not revert the stake that triggered it
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 4,
+            result: SubMsgResult::Err("hook failed".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        Response::new().add_attributes(vec![
+            ("action", "stake_hook_error"),
+            ("error", "hook failed"),
+        ])
+    );
+}
@@ -0,0 +1,284 @@
+use crate::contract::execute;
+use crate::error::ContractError;
+use crate::query::query;
+use crate::state::{BALANCES, CONFIG, UNCLAIMED_REWARDS};
+use crate::tests::helpers::{claim_rewards, setup_contract, unstake, whitelist_assets};
+use crate::token_factory::CustomExecuteMsg;
+use alliance_protocol::alliance_protocol::{ExecuteMsg, IbcOrigin, QueryMsg};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{coin, from_json, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, SubMsg, Uint128};
+use cw_asset_v3::{Asset, AssetInfo};
+use std::collections::HashMap;
+use terra_proto_rs::cosmos::base::v1beta1::Coin;
+use terra_proto_rs::ibc::applications::transfer::v1::MsgTransfer;
+use terra_proto_rs::traits::Message;
+
+// ibc-hooks sender of osmo1remoteuser over channel-0 on a chain with the terra prefix
+const INTERMEDIATE_SENDER: &str =
+    "terra19ts8ynyqhwn9h7q962u7ln4sayzcxt48fkvmct5r0zfjgnfasnlq0nxmgj";
+
+fn terra_env() -> Env {
+    let mut env = mock_env();
+    env.contract.address = Addr::unchecked("terra1hubcontract");
+    env
+}
+
+fn origin() -> IbcOrigin {
+    IbcOrigin {
+        channel_id: "channel-0".to_string(),
+        sender: "osmo1remoteuser".to_string(),
+    }
+}
+
+fn stake_through_ibc_hooks(deps: DepsMut, amount: u128) {
+    execute(
+        deps,
+        terra_env(),
+        mock_info(INTERMEDIATE_SENDER, &[coin(amount, "asset1")]),
+        ExecuteMsg::Stake {
+            recipient: None,
+            origin: Some(origin()),
+        },
+    )
+    .unwrap();
+}
+
+// Transfer of the hub at "cosmos2contract" back to osmo1remoteuser over channel-0
+fn origin_transfer(amount: u128, denom: &str) -> SubMsg<CustomExecuteMsg> {
+    let msg = MsgTransfer {
+        source_port: "transfer".to_string(),
+        source_channel: "channel-0".to_string(),
+        token: Some(Coin {
+            denom: denom.to_string(),
+            amount: amount.to_string(),
+        }),
+        sender: "cosmos2contract".to_string(),
+        receiver: "osmo1remoteuser".to_string(),
+        timeout_height: None,
+        timeout_timestamp: mock_env().block.time.plus_seconds(3600).nanos(),
+        memo: r#"{"ibc_callback":"cosmos2contract"}"#.to_string(),
+    };
+    SubMsg::reply_on_success(
+        CosmosMsg::Stargate {
+            type_url: "/ibc.applications.transfer.v1.MsgTransfer".to_string(),
+            value: Binary::from(msg.encode_to_vec()),
+        },
+        3,
+    )
+}
+
+fn query_balance(deps: Deps, user: &str) -> Uint128 {
+    BALANCES
+        .may_load(
+            deps.storage,
+            (Addr::unchecked(user), &AssetInfo::native("asset1")),
+        )
+        .unwrap()
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_stake_through_ibc_hooks() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("asset1")])]),
+    );
+
+    stake_through_ibc_hooks(deps.as_mut(), 100);
+    assert_eq!(
+        query_balance(deps.as_ref(), INTERMEDIATE_SENDER),
+        Uint128::new(100)
+    );
+
+    let identity: Option<IbcOrigin> = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RemoteIdentity {
+                address: INTERMEDIATE_SENDER.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(identity, Some(origin()));
+
+    // Later hook calls from the origin chain execute as the same intermediate sender,
+    // which gets the unstaked funds back on the origin chain
+    let res = unstake(deps.as_mut(), INTERMEDIATE_SENDER, 100, "asset1");
+    assert_eq!(res.messages, vec![origin_transfer(100, "asset1")]);
+    assert_eq!(
+        query_balance(deps.as_ref(), INTERMEDIATE_SENDER),
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn test_claim_rewards_through_ibc_hooks() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("asset1")])]),
+    );
+    stake_through_ibc_hooks(deps.as_mut(), 100);
+    UNCLAIMED_REWARDS
+        .save(
+            deps.as_mut().storage,
+            (
+                Addr::unchecked(INTERMEDIATE_SENDER),
+                &AssetInfo::native("asset1"),
+            ),
+            &Uint128::new(50),
+        )
+        .unwrap();
+
+    let res = claim_rewards(deps.as_mut(), INTERMEDIATE_SENDER, "asset1");
+    assert_eq!(res.messages, vec![origin_transfer(50, "uluna")]);
+}
+
+#[test]
+fn test_stake_through_ibc_hooks_to_recipient() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("asset1")])]),
+    );
+
+    // The position would belong to an address without the origin identity
+    let err = execute(
+        deps.as_mut(),
+        terra_env(),
+        mock_info(INTERMEDIATE_SENDER, &[coin(100, "asset1")]),
+        ExecuteMsg::Stake {
+            recipient: Some("user1".to_string()),
+            origin: Some(origin()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidIbcOrigin(format!("{} cannot stake for user1", INTERMEDIATE_SENDER))
+    );
+
+    // The intermediate sender itself can still be named as the recipient
+    execute(
+        deps.as_mut(),
+        terra_env(),
+        mock_info(INTERMEDIATE_SENDER, &[coin(100, "asset1")]),
+        ExecuteMsg::Stake {
+            recipient: Some(INTERMEDIATE_SENDER.to_string()),
+            origin: Some(origin()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        query_balance(deps.as_ref(), INTERMEDIATE_SENDER),
+        Uint128::new(100)
+    );
+}
+
+#[test]
+fn test_cw20_rewards_of_remote_staker() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("asset1")])]),
+    );
+    stake_through_ibc_hooks(deps.as_mut(), 100);
+    UNCLAIMED_REWARDS
+        .save(
+            deps.as_mut().storage,
+            (
+                Addr::unchecked(INTERMEDIATE_SENDER),
+                &AssetInfo::native("asset1"),
+            ),
+            &Uint128::new(50),
+        )
+        .unwrap();
+    CONFIG
+        .update(
+            deps.as_mut().storage,
+            |mut config| -> Result<_, ContractError> {
+                config.reward_asset = AssetInfo::cw20(Addr::unchecked("reward_token"));
+                Ok(config)
+            },
+        )
+        .unwrap();
+
+    // Nobody could spend the cw20 rewards paid to the intermediate sender
+    let err = execute(
+        deps.as_mut(),
+        terra_env(),
+        mock_info(INTERMEDIATE_SENDER, &[]),
+        ExecuteMsg::ClaimRewards(AssetInfo::native("asset1")),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidIbcDestination(
+            "cw20:reward_token cannot be sent over IBC".to_string()
+        )
+    );
+}
+
+#[test]
+fn test_unstake_with_funds() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("asset1")])]),
+    );
+    stake_through_ibc_hooks(deps.as_mut(), 100);
+
+    // ibc-hooks attaches the transferred tokens to the unstake call
+    let err = execute(
+        deps.as_mut(),
+        terra_env(),
+        mock_info(INTERMEDIATE_SENDER, &[coin(1, "asset1")]),
+        ExecuteMsg::Unstake(Asset::native("asset1", 100u128)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::FundsNotAccepted {});
+
+    let err = execute(
+        deps.as_mut(),
+        terra_env(),
+        mock_info(INTERMEDIATE_SENDER, &[coin(1, "asset1")]),
+        ExecuteMsg::ClaimRewards(AssetInfo::native("asset1")),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::FundsNotAccepted {});
+}
+
+#[test]
+fn test_stake_with_invalid_ibc_origin() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("asset1")])]),
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        terra_env(),
+        mock_info("user1", &[coin(100, "asset1")]),
+        ExecuteMsg::Stake {
+            recipient: None,
+            origin: Some(origin()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidIbcOrigin(
+            "user1 is not the ibc-hooks sender of channel-0/osmo1remoteuser".to_string()
+        )
+    );
+}
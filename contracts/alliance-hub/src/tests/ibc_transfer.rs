@@ -0,0 +1,282 @@
+use crate::contract::{execute, reply, sudo};
+use crate::error::ContractError;
+use crate::state::{BALANCES, IBC_TRANSFERS, TOTAL_BALANCES, UNCLAIMED_REWARDS};
+use crate::tests::helpers::{hub_event, setup_contract, stake, whitelist_assets};
+use crate::token_factory::CustomExecuteMsg;
+use alliance_protocol::alliance_protocol::{
+    ExecuteMsg, IbcDestination, IbcLifecycleComplete, IbcTransfer, IbcTransferKind, SudoMsg,
+};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{
+    Addr, Binary, CosmosMsg, Deps, DepsMut, Reply, Response, SubMsg, SubMsgResponse, SubMsgResult,
+    Uint128,
+};
+use cw_asset_v3::{Asset, AssetInfo};
+use std::collections::HashMap;
+use terra_proto_rs::cosmos::base::v1beta1::Coin;
+use terra_proto_rs::ibc::applications::transfer::v1::{MsgTransfer, MsgTransferResponse};
+use terra_proto_rs::traits::Message;
+
+fn destination() -> IbcDestination {
+    IbcDestination {
+        channel_id: "channel-1".to_string(),
+        receiver: "osmo1receiver".to_string(),
+        timeout_seconds: 600,
+    }
+}
+
+fn transfer_reply(deps: DepsMut, sequence: u64) -> Response<CustomExecuteMsg> {
+    let data = MsgTransferResponse { sequence }.encode_to_vec();
+    reply(
+        deps,
+        mock_env(),
+        Reply {
+            id: 3,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(Binary::from(data)),
+            }),
+        },
+    )
+    .unwrap()
+}
+
+fn query_balance(deps: Deps, user: &str) -> Uint128 {
+    BALANCES
+        .may_load(
+            deps.storage,
+            (Addr::unchecked(user), &AssetInfo::native("asset1")),
+        )
+        .unwrap()
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_unstake_over_ibc() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("asset1")])]),
+    );
+    stake(deps.as_mut(), "user1", 100, "asset1");
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user1", &[]),
+        ExecuteMsg::UnstakeOverIbc {
+            asset: Asset::native("asset1", 60u128),
+            destination: destination(),
+        },
+    )
+    .unwrap();
+    let env = mock_env();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Stargate {
+                type_url: "/ibc.applications.transfer.v1.MsgTransfer".to_string(),
+                value: Binary::from(
+                    MsgTransfer {
+                        source_port: "transfer".to_string(),
+                        source_channel: "channel-1".to_string(),
+                        token: Some(Coin {
+                            denom: "asset1".to_string(),
+                            amount: "60".to_string(),
+                        }),
+                        sender: "cosmos2contract".to_string(),
+                        receiver: "osmo1receiver".to_string(),
+                        timeout_height: None,
+                        timeout_timestamp: env.block.time.plus_seconds(600).nanos(),
+                        memo: r#"{"ibc_callback":"cosmos2contract"}"#.to_string(),
+                    }
+                    .encode_to_vec()
+                ),
+            },
+            3,
+        )]
+    );
+    assert_eq!(query_balance(deps.as_ref(), "user1"), Uint128::new(40));
+
+    transfer_reply(deps.as_mut(), 7);
+    assert_eq!(
+        IBC_TRANSFERS
+            .load(deps.as_ref().storage, ("channel-1", 7))
+            .unwrap(),
+        IbcTransfer {
+            user: Addr::unchecked("user1"),
+            asset: AssetInfo::native("asset1"),
+            amount: Uint128::new(60),
+            kind: IbcTransferKind::Unstake,
+        }
+    );
+
+    // A timed out transfer is staked again
+    let res = sudo(
+        deps.as_mut(),
+        mock_env(),
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcTimeout {
+            channel: "channel-1".to_string(),
+            sequence: 7,
+        }),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        Response::new()
+            .add_attributes(vec![
+                ("action", "ibc_lifecycle_complete"),
+                ("channel_id", "channel-1"),
+                ("sequence", "7"),
+                ("result", "timeout"),
+                ("refund_user", "user1"),
+                ("refund_amount", "60"),
+            ])
+            .add_event(hub_event(
+                "ibc_refund",
+                vec![
+                    ("user", "user1"),
+                    ("asset", "native:asset1"),
+                    ("amount", "60"),
+                    ("kind", "unstake"),
+                ]
+            ))
+    );
+    assert_eq!(query_balance(deps.as_ref(), "user1"), Uint128::new(100));
+    assert_eq!(
+        TOTAL_BALANCES
+            .load(deps.as_ref().storage, &AssetInfo::native("asset1"))
+            .unwrap(),
+        Uint128::new(100)
+    );
+    assert!(!IBC_TRANSFERS.has(deps.as_ref().storage, ("channel-1", 7)));
+}
+
+#[test]
+fn test_claim_rewards_over_ibc() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("asset1")])]),
+    );
+    stake(deps.as_mut(), "user1", 100, "asset1");
+    UNCLAIMED_REWARDS
+        .save(
+            deps.as_mut().storage,
+            (Addr::unchecked("user1"), &AssetInfo::native("asset1")),
+            &Uint128::new(50),
+        )
+        .unwrap();
+
+    let claim = |deps: DepsMut| {
+        execute(
+            deps,
+            mock_env(),
+            mock_info("user1", &[]),
+            ExecuteMsg::ClaimRewardsOverIbc {
+                asset: AssetInfo::native("asset1"),
+                destination: destination(),
+            },
+        )
+        .unwrap()
+    };
+    let res = claim(deps.as_mut());
+    assert_eq!(res.messages.len(), 1);
+    transfer_reply(deps.as_mut(), 1);
+
+    // Delivered transfers are forgotten
+    let res = sudo(
+        deps.as_mut(),
+        mock_env(),
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcAck {
+            channel: "channel-1".to_string(),
+            sequence: 1,
+            ack: "AQ==".to_string(),
+            success: true,
+        }),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], ("result", "ack"));
+    assert!(!IBC_TRANSFERS.has(deps.as_ref().storage, ("channel-1", 1)));
+
+    UNCLAIMED_REWARDS
+        .save(
+            deps.as_mut().storage,
+            (Addr::unchecked("user1"), &AssetInfo::native("asset1")),
+            &Uint128::new(30),
+        )
+        .unwrap();
+    claim(deps.as_mut());
+    transfer_reply(deps.as_mut(), 2);
+
+    // Rejected transfers are credited back to the unclaimed rewards
+    sudo(
+        deps.as_mut(),
+        mock_env(),
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcAck {
+            channel: "channel-1".to_string(),
+            sequence: 2,
+            ack: "error".to_string(),
+            success: false,
+        }),
+    )
+    .unwrap();
+    assert_eq!(
+        UNCLAIMED_REWARDS
+            .load(
+                deps.as_ref().storage,
+                (Addr::unchecked("user1"), &AssetInfo::native("asset1")),
+            )
+            .unwrap(),
+        Uint128::new(30)
+    );
+}
+
+#[test]
+fn test_invalid_ibc_destination() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("asset1")])]),
+    );
+    stake(deps.as_mut(), "user1", 100, "asset1");
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user1", &[]),
+        ExecuteMsg::UnstakeOverIbc {
+            asset: Asset::native("asset1", 60u128),
+            destination: IbcDestination {
+                timeout_seconds: 0,
+                ..destination()
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidIbcDestination("timeout cannot be zero".to_string())
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user1", &[]),
+        ExecuteMsg::UnstakeOverIbc {
+            asset: Asset::native("asset1", 60u128),
+            destination: IbcDestination {
+                channel_id: "transfer".to_string(),
+                ..destination()
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidIbcDestination("invalid channel transfer".to_string())
+    );
+}
@@ -1,16 +1,20 @@
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
 use cosmwasm_std::{
-    from_json, Addr, Binary, CosmosMsg, Reply, Response, SubMsg, SubMsgResponse, SubMsgResult,
-    Timestamp, Uint128,
+    from_json, Addr, Binary, CosmosMsg, Decimal, Reply, Response, SubMsg, SubMsgResponse,
+    SubMsgResult, Timestamp, Uint128,
 };
+use cw_asset_v3::AssetInfo;
 use terra_proto_rs::traits::MessageExt;
 
-use alliance_protocol::alliance_protocol::ExecuteMsg::UpdateConfig;
-use alliance_protocol::alliance_protocol::{Config, QueryMsg};
+use alliance_protocol::alliance_protocol::{
+    AllianceTokenDenomUnit, AllianceTokenMetadata, Config, ExecuteMsg, InstantiateMsg, QueryMsg,
+    StakingBackend, TokenFactoryBackend, DEFAULT_UNBONDING_PERIOD,
+};
 
-use crate::contract::{execute, reply};
+use crate::contract::{execute, instantiate, reply};
+use crate::error::ContractError;
 use crate::query::query;
-use crate::tests::helpers::setup_contract;
+use crate::tests::helpers::{set_alliance_asset, setup_contract, DENOM};
 use crate::token_factory::{CustomExecuteMsg, DenomUnit, Metadata, TokenExecuteMsg};
 
 #[test]
@@ -42,10 +46,15 @@ fn test_setup_contract() {
             controller: Addr::unchecked("controller"),
             oracle: Addr::unchecked("oracle"),
             operator: Addr::unchecked("operator"),
-            reward_denom: "uluna".to_string(),
+            reward_asset: AssetInfo::native("uluna"),
             alliance_token_denom: "".to_string(),
             alliance_token_supply: Uint128::new(0),
             last_reward_update_timestamp: Timestamp::default(),
+            token_factory_backend: TokenFactoryBackend::Terra,
+            staking_backend: StakingBackend::Alliance,
+            reward_update_interval: 0,
+            keeper_bounty: Decimal::zero(),
+            unbonding_period: DEFAULT_UNBONDING_PERIOD,
         }
     );
 }
@@ -112,90 +121,171 @@ fn test_reply_create_token() {
             controller: Addr::unchecked("controller"),
             oracle: Addr::unchecked("oracle"),
             operator: Addr::unchecked("operator"),
-            reward_denom: "uluna".to_string(),
+            reward_asset: AssetInfo::native("uluna"),
             alliance_token_denom: "factory/cosmos2contract/ualliance".to_string(),
             alliance_token_supply: Uint128::new(1000000000000),
             last_reward_update_timestamp: Timestamp::default(),
+            token_factory_backend: TokenFactoryBackend::Terra,
+            staking_backend: StakingBackend::Alliance,
+            reward_update_interval: 0,
+            keeper_bounty: Decimal::zero(),
+            unbonding_period: DEFAULT_UNBONDING_PERIOD,
         }
     );
 }
 
+fn alliance_metadata() -> AllianceTokenMetadata {
+    AllianceTokenMetadata {
+        description: "Alliance token of the hub".to_string(),
+        name: "Hub Alliance".to_string(),
+        symbol: "HUBA".to_string(),
+        display: Some("huba".to_string()),
+        denom_units: vec![AllianceTokenDenomUnit {
+            denom: "huba".to_string(),
+            exponent: 6,
+            aliases: vec![],
+        }],
+    }
+}
+
+fn hub_metadata(denom: &str) -> Metadata {
+    Metadata {
+        description: "Alliance token of the hub".to_string(),
+        denom_units: vec![
+            DenomUnit {
+                denom: denom.to_string(),
+                exponent: 0,
+                aliases: vec![],
+            },
+            DenomUnit {
+                denom: "huba".to_string(),
+                exponent: 6,
+                aliases: vec![],
+            },
+        ],
+        base: denom.to_string(),
+        display: "huba".to_string(),
+        name: "Hub Alliance".to_string(),
+        symbol: "HUBA".to_string(),
+    }
+}
+
+fn instantiate_msg(alliance_token_metadata: Option<AllianceTokenMetadata>) -> InstantiateMsg {
+    InstantiateMsg {
+        governance: "gov".to_string(),
+        controller: "controller".to_string(),
+        oracle: "oracle".to_string(),
+        operator: "operator".to_string(),
+        alliance_token_denom: "ualliance".to_string(),
+        reward_asset: AssetInfo::native("uluna"),
+        alliance_token_metadata,
+        token_factory_backend: None,
+        staking_backend: None,
+        unbonding_period: None,
+    }
+}
+
 #[test]
-fn test_update_config() {
+fn test_reply_create_token_with_metadata() {
     let mut deps = mock_dependencies();
-    setup_contract(deps.as_mut());
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        instantiate_msg(Some(alliance_metadata())),
+    )
+    .unwrap();
 
-    let query_config = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
-    let Config {
-        governance,
-        controller,
-        oracle,
-        operator,
-        ..
-    } = from_json(&query_config).unwrap();
-
-    assert_eq!(governance, Addr::unchecked("gov"));
-    assert_eq!(controller, Addr::unchecked("controller"));
-    assert_eq!(oracle, Addr::unchecked("oracle"));
-    assert_eq!(operator, Addr::unchecked("operator"));
-
-    let msg = UpdateConfig {
-        governance: Some("new_gov".to_string()),
-        controller: Some("new_controller".to_string()),
-        oracle: Some("new_oracle".to_string()),
-        operator: Some("new_operator".to_string()),
+    let denom = "factory/cosmos2contract/ualliance";
+    let msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(Binary::from(String::from(denom).to_bytes().unwrap())),
+        }),
     };
+    let res = reply(deps.as_mut(), mock_env(), msg).unwrap();
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Custom(CustomExecuteMsg::Token(
+            TokenExecuteMsg::SetMetadata {
+                denom: denom.to_string(),
+                metadata: hub_metadata(denom),
+            }
+        )))
+    );
+}
 
-    let result = execute(
+#[test]
+fn test_instantiate_invalid_metadata() {
+    let mut deps = mock_dependencies();
+    let err = instantiate(
         deps.as_mut(),
         mock_env(),
-        mock_info("unauthorized", &[]),
-        msg.clone(),
+        mock_info("admin", &[]),
+        instantiate_msg(Some(AllianceTokenMetadata {
+            display: Some("alliance".to_string()),
+            ..alliance_metadata()
+        })),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidAllianceTokenMetadata(
+            "display alliance is not a denom unit".to_string()
+        )
     );
 
-    if result.is_ok() {
-        panic!("should be unauthorized")
-    }
-
-    let result = execute(
+    let err = instantiate(
         deps.as_mut(),
         mock_env(),
-        mock_info("operator", &[]),
-        msg.clone(),
+        mock_info("admin", &[]),
+        instantiate_msg(Some(AllianceTokenMetadata {
+            display: None,
+            denom_units: vec![AllianceTokenDenomUnit {
+                denom: "huba".to_string(),
+                exponent: 0,
+                aliases: vec![],
+            }],
+            ..alliance_metadata()
+        })),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidAllianceTokenMetadata("invalid denom unit huba".to_string())
     );
+}
 
-    if result.is_ok() {
-        panic!("should be unauthorized")
-    }
+#[test]
+fn test_update_alliance_token_metadata() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
 
-    let result = execute(
+    let err = execute(
         deps.as_mut(),
         mock_env(),
-        mock_info("controller", &[]),
-        msg.clone(),
-    );
-
-    if result.is_ok() {
-        panic!("should be unauthorized")
-    }
-
-    let result = execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg);
-
-    if result.is_err() {
-        panic!("should be fine")
-    }
+        mock_info("operator", &[]),
+        ExecuteMsg::UpdateAllianceTokenMetadata(alliance_metadata()),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
 
-    let query_config = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
-    let Config {
-        governance,
-        controller,
-        oracle,
-        operator,
-        ..
-    } = from_json(&query_config).unwrap();
-
-    assert_eq!(governance, Addr::unchecked("new_gov"));
-    assert_eq!(controller, Addr::unchecked("new_controller"));
-    assert_eq!(oracle, Addr::unchecked("new_oracle"));
-    assert_eq!(operator, Addr::unchecked("new_operator"));
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::UpdateAllianceTokenMetadata(alliance_metadata()),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Custom(CustomExecuteMsg::Token(
+            TokenExecuteMsg::SetMetadata {
+                denom: DENOM.to_string(),
+                metadata: hub_metadata(DENOM),
+            }
+        )))]
+    );
 }
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use alliance_protocol::alliance_protocol::{
+    AssetInvariantsRes, InvariantsCursor, InvariantsRes, QueryMsg, RewardInvariantsRes,
+};
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{coin, from_json, Decimal, Deps, Uint128};
+use cw_asset_v3::AssetInfo;
+
+use crate::query::query;
+use crate::state::{ASSET_REWARD_RATE, REWARD_LIABILITIES, TOTAL_BALANCES};
+use crate::tests::helpers::{setup_contract, stake, whitelist_assets};
+
+fn check_invariants(
+    deps: Deps,
+    start_after: Option<InvariantsCursor>,
+    limit: Option<u32>,
+) -> InvariantsRes {
+    from_json(
+        query(
+            deps,
+            mock_env(),
+            QueryMsg::CheckInvariants { start_after, limit },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+fn asset_invariants(
+    denom: &str,
+    total_balance: u128,
+    sum_of_balances: u128,
+    contract_balance: u128,
+    reward_liabilities: u128,
+    balances_match: Option<bool>,
+    solvent: bool,
+) -> AssetInvariantsRes {
+    AssetInvariantsRes {
+        asset: AssetInfo::native(denom),
+        total_balance: Uint128::new(total_balance),
+        sum_of_balances: Uint128::new(sum_of_balances),
+        contract_balance: Uint128::new(contract_balance),
+        reward_liabilities: Uint128::new(reward_liabilities),
+        balances_match,
+        solvent,
+    }
+}
+
+#[test]
+fn test_check_invariants() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([(
+            "chain-1".to_string(),
+            vec![AssetInfo::native("aWHALE"), AssetInfo::native("bWHALE")],
+        )]),
+    );
+    stake(deps.as_mut(), "user1", 100, "aWHALE");
+    stake(deps.as_mut(), "user1", 200, "bWHALE");
+    stake(deps.as_mut(), "user2", 300, "aWHALE");
+    ASSET_REWARD_RATE
+        .save(
+            deps.as_mut().storage,
+            &AssetInfo::native("aWHALE"),
+            &Decimal::percent(200),
+        )
+        .unwrap();
+    REWARD_LIABILITIES
+        .save(
+            deps.as_mut().storage,
+            &Decimal::from_atomics(800u128, 0).unwrap(),
+        )
+        .unwrap();
+    // Moves the pending rewards of user1 to the unclaimed rewards
+    stake(deps.as_mut(), "user1", 100, "aWHALE");
+    // bWHALE is short of 50 and its total balance drifted from the user balances
+    TOTAL_BALANCES
+        .save(
+            deps.as_mut().storage,
+            &AssetInfo::native("bWHALE"),
+            &Uint128::new(250),
+            mock_env().block.height,
+        )
+        .unwrap();
+    deps.querier.update_balance(
+        "cosmos2contract",
+        vec![
+            coin(500, "aWHALE"),
+            coin(150, "bWHALE"),
+            coin(1000, "uluna"),
+        ],
+    );
+
+    let res = check_invariants(deps.as_ref(), None, None);
+    assert_eq!(
+        res,
+        InvariantsRes {
+            assets: vec![
+                asset_invariants("aWHALE", 500, 500, 500, 800, Some(true), true),
+                asset_invariants("bWHALE", 250, 200, 150, 0, Some(false), false),
+            ],
+            rewards: RewardInvariantsRes {
+                reward_asset: AssetInfo::native("uluna"),
+                liabilities: Uint128::new(800),
+                balance: Uint128::new(1000),
+                surplus: Uint128::new(200),
+                deficit: Uint128::zero(),
+            },
+            next: None,
+        }
+    );
+
+    // Pages only sum their own positions, the reward figures come from storage
+    let page = check_invariants(deps.as_ref(), None, Some(2));
+    assert_eq!(
+        page.assets,
+        vec![
+            asset_invariants("aWHALE", 500, 200, 500, 200, None, true),
+            asset_invariants("bWHALE", 250, 200, 150, 0, None, false),
+        ]
+    );
+    assert_eq!(page.rewards, res.rewards);
+    let next = page.next.unwrap();
+    assert_eq!(
+        next,
+        InvariantsCursor {
+            address: "user1".to_string(),
+            asset: AssetInfo::native("bWHALE"),
+        }
+    );
+    let page = check_invariants(deps.as_ref(), Some(next), Some(2));
+    assert_eq!(
+        page.assets,
+        vec![
+            asset_invariants("aWHALE", 500, 300, 500, 600, None, true),
+            asset_invariants("bWHALE", 250, 0, 150, 0, None, false),
+        ]
+    );
+    assert_eq!(page.rewards, res.rewards);
+    assert_eq!(page.next, None);
+
+    // Rewards owed above the reward balance are reported as a deficit
+    deps.querier.update_balance(
+        "cosmos2contract",
+        vec![coin(500, "aWHALE"), coin(150, "bWHALE"), coin(300, "uluna")],
+    );
+    let res = check_invariants(deps.as_ref(), None, None);
+    assert_eq!(res.rewards.surplus, Uint128::zero());
+    assert_eq!(res.rewards.deficit, Uint128::new(500));
+    assert_eq!(res.rewards.balance, Uint128::new(300));
+}
+
+#[test]
+fn test_check_invariants_staked_reward_asset() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("uluna")])]),
+    );
+    stake(deps.as_mut(), "user1", 100, "uluna");
+    REWARD_LIABILITIES
+        .save(deps.as_mut().storage, &Decimal::percent(5050))
+        .unwrap();
+
+    // Covering the staked amount is not enough while rewards are owed in the same asset
+    deps.querier
+        .update_balance("cosmos2contract", vec![coin(150, "uluna")]);
+    let res = check_invariants(deps.as_ref(), None, None);
+    assert_eq!(
+        res.assets,
+        vec![asset_invariants(
+            "uluna",
+            100,
+            100,
+            150,
+            0,
+            Some(true),
+            false
+        )]
+    );
+    assert_eq!(res.rewards.liabilities, Uint128::new(51));
+    assert_eq!(res.rewards.deficit, Uint128::new(1));
+
+    deps.querier
+        .update_balance("cosmos2contract", vec![coin(151, "uluna")]);
+    let res = check_invariants(deps.as_ref(), None, None);
+    assert!(res.assets[0].solvent);
+}
@@ -0,0 +1,262 @@
+use alliance_protocol::alliance_oracle_types::ChainId;
+use alliance_protocol::alliance_protocol::{
+    AllianceDelegation, MigrateMsg, MigrationStepRes, QueryMsg, StakingBackend,
+};
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{from_json, Addr, Decimal, Deps, Response, Storage, Uint128};
+use cw2::{get_contract_version, set_contract_version};
+use cw_asset_v3::AssetInfo;
+use cw_storage_plus_016::{Item as Item016, Map as Map016};
+use std::collections::HashSet;
+
+use crate::contract::migrate;
+use crate::error::ContractError;
+use crate::query::query;
+use crate::state::{
+    ASSET_REWARD_RATE, BALANCES, CONFIG, REWARD_LIABILITIES, UNCLAIMED_REWARDS,
+    USER_ASSET_REWARD_RATE, WHITELIST,
+};
+
+const OLD_CONFIG: &[u8] = br#"{"governance":"gov","controller":"controller","oracle":"oracle","operator":"operator","last_reward_update_timestamp":"0","alliance_token_denom":"ualliance","alliance_token_supply":"1000","reward_denom":"uluna"}"#;
+
+fn set_old_state(storage: &mut dyn Storage, version: &str) {
+    set_contract_version(storage, "crates.io:terra-alliance-protocol", version).unwrap();
+    storage.set(b"config", OLD_CONFIG);
+}
+
+fn query_plan(deps: Deps, from_version: Option<&str>) -> Vec<String> {
+    let steps: Vec<MigrationStepRes> = from_json(
+        query(
+            deps,
+            mock_env(),
+            QueryMsg::MigrationPlan {
+                from_version: from_version.map(|v| v.to_string()),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    steps.into_iter().map(|step| step.name).collect()
+}
+
+#[test]
+fn test_migration_plan() {
+    let mut deps = mock_dependencies();
+    set_old_state(deps.as_mut().storage, "0.1.2");
+
+    assert_eq!(
+        query_plan(deps.as_ref(), None),
+        vec![
+            "cw_storage_plus_maps",
+            "reward_asset",
+            "role_capabilities",
+            "delegation_ledger",
+            "reward_liabilities"
+        ]
+    );
+    assert_eq!(
+        query_plan(deps.as_ref(), Some("0.1.3")),
+        vec![
+            "reward_asset",
+            "role_capabilities",
+            "delegation_ledger",
+            "reward_liabilities"
+        ]
+    );
+    assert!(query_plan(deps.as_ref(), Some("0.2.0")).is_empty());
+}
+
+#[test]
+fn test_migrate_skips_applied_steps() {
+    let mut deps = mock_dependencies();
+    set_old_state(deps.as_mut().storage, "0.1.3");
+    // Already stored with cw-storage-plus 1.2 keys, must not be rewritten
+    WHITELIST
+        .save(
+            deps.as_mut().storage,
+            &AssetInfo::native("aWHALE"),
+            &"chain-1".to_string(),
+        )
+        .unwrap();
+    let user = Addr::unchecked("user1");
+    let asset = AssetInfo::native("aWHALE");
+    let storage = deps.as_mut().storage;
+    BALANCES
+        .save(
+            storage,
+            (user.clone(), &asset),
+            &Uint128::new(100),
+            mock_env().block.height,
+        )
+        .unwrap();
+    ASSET_REWARD_RATE
+        .save(storage, &asset, &Decimal::percent(250))
+        .unwrap();
+    USER_ASSET_REWARD_RATE
+        .save(storage, (user.clone(), &asset), &Decimal::one())
+        .unwrap();
+    UNCLAIMED_REWARDS
+        .save(storage, (user, &asset), &Uint128::new(30))
+        .unwrap();
+
+    let res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            keeper_bounty: Some(Decimal::percent(5)),
+            reward_update_interval: Some(3600),
+            ..MigrateMsg::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        Response::new().add_attributes(vec![
+            ("action", "migrate"),
+            ("from_version", "0.1.3"),
+            ("to_version", "0.2.0"),
+            (
+                "steps",
+                "reward_asset,role_capabilities,delegation_ledger,reward_liabilities",
+            ),
+        ])
+    );
+
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(config.reward_asset, AssetInfo::native("uluna"));
+    assert_eq!(config.keeper_bounty, Decimal::percent(5));
+    assert_eq!(config.reward_update_interval, 3600);
+    // Accrued 150 on top of the 30 unclaimed
+    assert_eq!(
+        REWARD_LIABILITIES.load(deps.as_ref().storage).unwrap(),
+        Decimal::from_atomics(180u128, 0).unwrap()
+    );
+    assert_eq!(
+        WHITELIST
+            .load(deps.as_ref().storage, &AssetInfo::native("aWHALE"))
+            .unwrap(),
+        "chain-1"
+    );
+    assert_eq!(
+        get_contract_version(deps.as_ref().storage).unwrap().version,
+        "0.2.0"
+    );
+
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(cosmwasm_std::StdError::generic_err(
+            "Invalid contract version"
+        ))
+    );
+}
+
+#[test]
+fn test_migrate_invalid_params() {
+    let mut deps = mock_dependencies();
+    set_old_state(deps.as_mut().storage, "0.1.3");
+
+    let err = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            keeper_bounty: Some(Decimal::percent(50)),
+            ..MigrateMsg::default()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::KeeperBountyTooHigh(Decimal::percent(10))
+    );
+
+    // The bond denom cannot be the reward asset
+    let err = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            staking_backend: Some(StakingBackend::Native {
+                bond_denom: Some("uluna".to_string()),
+            }),
+            ..MigrateMsg::default()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidBondDenom("uluna".to_string()));
+}
+
+#[test]
+fn test_migrate_unsupported_legacy_asset() {
+    let mut deps = mock_dependencies();
+    set_old_state(deps.as_mut().storage, "0.1.2");
+    const OLD_WHITELIST: Map016<cw_asset_v2::AssetInfoKey, ChainId> = Map016::new("whitelist");
+    OLD_WHITELIST
+        .save(
+            deps.as_mut().storage,
+            cw_asset_v2::AssetInfoKey(b"cw1155:collection:token".to_vec()),
+            &"chain-1".to_string(),
+        )
+        .unwrap();
+
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnsupportedLegacyAsset("cw1155:collection:token".to_string())
+    );
+}
+
+#[test]
+fn test_migrate_requires_every_legacy_delegation() {
+    let mut deps = mock_dependencies();
+    set_old_state(deps.as_mut().storage, "0.1.3");
+    const OLD_VALIDATORS: Item016<HashSet<String>> = Item016::new("validators");
+    OLD_VALIDATORS
+        .save(
+            deps.as_mut().storage,
+            &HashSet::from([
+                "validator1".to_string(),
+                "validator2".to_string(),
+                "validator3".to_string(),
+            ]),
+        )
+        .unwrap();
+    let delegation = |validator: &str, amount: u128| AllianceDelegation {
+        validator: validator.to_string(),
+        amount: Uint128::new(amount),
+    };
+
+    let err = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            delegations: Some(vec![delegation("validator1", 100)]),
+            ..MigrateMsg::default()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MissingLegacyDelegations("validator2,validator3".to_string())
+    );
+
+    // Validators the hub fully left are listed with a zero amount
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            delegations: Some(vec![
+                delegation("validator1", 100),
+                delegation("validator2", 0),
+                delegation("validator3", 50),
+            ]),
+            ..MigrateMsg::default()
+        },
+    )
+    .unwrap();
+    let validators: HashSet<String> =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::Validators {}).unwrap()).unwrap();
+    assert_eq!(
+        validators,
+        HashSet::from(["validator1".to_string(), "validator3".to_string()])
+    );
+}
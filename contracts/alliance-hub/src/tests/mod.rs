@@ -1,6 +1,24 @@
 mod alliance;
 mod assets;
+mod cw20_rewards;
+mod events;
 mod helpers;
+mod hooks;
+mod ibc_hooks;
+mod ibc_transfer;
 mod instantiate;
+mod invariants;
+mod migrate;
+mod pause;
+mod permissions;
+mod rebalance;
 mod rewards;
+mod roles;
+mod snapshots;
 mod stake_unstake;
+mod staking;
+mod sudo;
+mod supply;
+mod sweep;
+mod token_factory;
+mod voting;
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{coin, from_json, Deps};
+use cw_asset_v3::{Asset, AssetInfo};
+
+use alliance_protocol::alliance_protocol::{
+    AssetPauseStatus, ExecuteMsg, PauseScope, PauseStatusRes, PausedActions, QueryMsg,
+};
+
+use crate::contract::execute;
+use crate::error::ContractError;
+use crate::query::query;
+use crate::tests::helpers::{set_alliance_asset, setup_contract, stake, unstake, whitelist_assets};
+
+fn query_pause_status(deps: Deps) -> PauseStatusRes {
+    from_json(query(deps, mock_env(), QueryMsg::PauseStatus {}).unwrap()).unwrap()
+}
+
+#[test]
+fn test_pause_asset_scope() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([(
+            "chain-1".to_string(),
+            vec![AssetInfo::native("aWHALE"), AssetInfo::native("bWHALE")],
+        )]),
+    );
+    stake(deps.as_mut(), "user1", 100, "aWHALE");
+
+    let scope = PauseScope::Stake(AssetInfo::native("aWHALE"));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user1", &[]),
+        ExecuteMsg::Pause(scope.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        ExecuteMsg::Pause(scope.clone()),
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user1", &[coin(100, "aWHALE")]),
+        ExecuteMsg::Stake {
+            recipient: None,
+            origin: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Paused("stake:native:aWHALE".to_string())
+    );
+
+    // Other actions and other assets are not affected
+    unstake(deps.as_mut(), "user1", 50, "aWHALE");
+    stake(deps.as_mut(), "user1", 100, "bWHALE");
+
+    assert_eq!(
+        query_pause_status(deps.as_ref()),
+        PauseStatusRes {
+            global: false,
+            reward_updates: false,
+            assets: vec![AssetPauseStatus {
+                asset: AssetInfo::native("aWHALE"),
+                paused: PausedActions {
+                    stake: true,
+                    unstake: false,
+                    claim_rewards: false,
+                },
+            }],
+        }
+    );
+
+    // Only governance can unpause
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        ExecuteMsg::Unpause(scope.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::Unpause(scope),
+    )
+    .unwrap();
+    stake(deps.as_mut(), "user1", 100, "aWHALE");
+    assert_eq!(
+        query_pause_status(deps.as_ref()),
+        PauseStatusRes {
+            global: false,
+            reward_updates: false,
+            assets: vec![],
+        }
+    );
+}
+
+#[test]
+fn test_pause_global_and_reward_updates() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("aWHALE")])]),
+    );
+    stake(deps.as_mut(), "user1", 100, "aWHALE");
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::Pause(PauseScope::RewardUpdates {}),
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user1", &[]),
+        ExecuteMsg::UpdateRewards {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Paused("reward_updates".to_string()));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        ExecuteMsg::Pause(PauseScope::Global {}),
+    )
+    .unwrap();
+    let global_err = ContractError::Paused("global".to_string());
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user1", &[]),
+        ExecuteMsg::Unstake(Asset::native("aWHALE", 100u128)),
+    )
+    .unwrap_err();
+    assert_eq!(err, global_err);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user1", &[]),
+        ExecuteMsg::ClaimRewards(AssetInfo::native("aWHALE")),
+    )
+    .unwrap_err();
+    assert_eq!(err, global_err);
+
+    let status = query_pause_status(deps.as_ref());
+    assert!(status.global);
+    assert!(status.reward_updates);
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::Unpause(PauseScope::Global {}),
+    )
+    .unwrap();
+    unstake(deps.as_mut(), "user1", 100, "aWHALE");
+}
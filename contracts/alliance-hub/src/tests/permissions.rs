@@ -0,0 +1,224 @@
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{from_json, Addr, Deps, Uint128};
+
+use alliance_protocol::alliance_protocol::{
+    AllianceDelegateMsg, AllianceDelegation, Capability, CapabilityHoldersRes, ExecuteMsg,
+    PauseScope, QueryMsg, Role,
+};
+
+use crate::contract::execute;
+use crate::error::ContractError;
+use crate::query::query;
+use crate::tests::helpers::{set_alliance_asset, setup_contract};
+
+fn query_holders(deps: Deps, capability: Capability) -> Vec<Addr> {
+    let res: Vec<CapabilityHoldersRes> =
+        from_json(query(deps, mock_env(), QueryMsg::CapabilityHolders {}).unwrap()).unwrap();
+    res.into_iter()
+        .find(|c| c.capability == capability)
+        .unwrap()
+        .holders
+}
+
+fn delegate_msg() -> ExecuteMsg {
+    ExecuteMsg::AllianceDelegate(AllianceDelegateMsg {
+        delegations: vec![AllianceDelegation {
+            validator: "validator1".to_string(),
+            amount: Uint128::new(100),
+        }],
+    })
+}
+
+#[test]
+fn test_default_grants() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+
+    let res: Vec<CapabilityHoldersRes> =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::CapabilityHolders {}).unwrap())
+            .unwrap();
+    let gov = Addr::unchecked("gov");
+    let controller = Addr::unchecked("controller");
+    let operator = Addr::unchecked("operator");
+    assert_eq!(
+        res,
+        vec![
+            CapabilityHoldersRes {
+                capability: Capability::Whitelist,
+                holders: vec![gov.clone()],
+            },
+            CapabilityHoldersRes {
+                capability: Capability::SetDistribution,
+                holders: vec![gov.clone(), operator.clone()],
+            },
+            CapabilityHoldersRes {
+                capability: Capability::Delegate,
+                holders: vec![controller.clone(), gov.clone()],
+            },
+            CapabilityHoldersRes {
+                capability: Capability::Redelegate,
+                holders: vec![controller, gov.clone()],
+            },
+            CapabilityHoldersRes {
+                capability: Capability::Pause,
+                holders: vec![gov.clone(), operator],
+            },
+            CapabilityHoldersRes {
+                capability: Capability::Config,
+                holders: vec![gov.clone()],
+            },
+            CapabilityHoldersRes {
+                capability: Capability::Sweep,
+                holders: vec![gov],
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_grant_and_revoke() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("keeper", &[]),
+        delegate_msg(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Only governance manages the registry
+    let grant = ExecuteMsg::GrantCapability {
+        address: "keeper".to_string(),
+        capability: Capability::Delegate,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller", &[]),
+        grant.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), grant).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("keeper", &[]),
+        delegate_msg(),
+    )
+    .unwrap();
+    assert!(query_holders(deps.as_ref(), Capability::Delegate).contains(&Addr::unchecked("keeper")));
+
+    // The grant does not extend to other capabilities
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("keeper", &[]),
+        ExecuteMsg::Pause(PauseScope::Global {}),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::RevokeCapability {
+            address: "controller".to_string(),
+            capability: Capability::Delegate,
+        },
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller", &[]),
+        delegate_msg(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn test_role_transfer_moves_grants() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+
+    // The handover moves what the controller holds, not the defaults of the role
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::RevokeCapability {
+            address: "controller".to_string(),
+            capability: Capability::Redelegate,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::GrantCapability {
+            address: "controller".to_string(),
+            capability: Capability::Pause,
+        },
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller", &[]),
+        ExecuteMsg::ProposeRoleTransfer {
+            role: Role::Controller,
+            new_address: "new_controller".to_string(),
+            expires_in: 100,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_controller", &[]),
+        ExecuteMsg::AcceptRoleTransfer {
+            role: Role::Controller,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_holders(deps.as_ref(), Capability::Redelegate),
+        vec![Addr::unchecked("gov")]
+    );
+    assert_eq!(
+        query_holders(deps.as_ref(), Capability::Delegate),
+        vec![Addr::unchecked("gov"), Addr::unchecked("new_controller")]
+    );
+    assert!(
+        !query_holders(deps.as_ref(), Capability::Pause).contains(&Addr::unchecked("controller"))
+    );
+    assert!(query_holders(deps.as_ref(), Capability::Pause)
+        .contains(&Addr::unchecked("new_controller")));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_controller", &[]),
+        delegate_msg(),
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller", &[]),
+        delegate_msg(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
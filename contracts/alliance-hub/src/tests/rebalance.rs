@@ -0,0 +1,292 @@
+use crate::contract::execute;
+use crate::error::ContractError;
+use crate::query::query;
+use crate::tests::helpers::{
+    alliance_delegate, alliance_redelegate, query_delegations, set_alliance_asset, setup_contract,
+    DENOM,
+};
+use crate::token_factory::CustomExecuteMsg;
+use alliance_protocol::alliance_protocol::{
+    AllianceDelegation, AllianceRedelegation, Capability, DelegationStrategy, ExecuteMsg, QueryMsg,
+    RebalanceAction, Unbonding, ValidatorDelegation, ValidatorWeight, DEFAULT_UNBONDING_PERIOD,
+};
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info,
+};
+use cosmwasm_std::{coin, from_json, Decimal, Deps, DepsMut, Env, Response, Uint128};
+
+fn strategy(validators: Vec<(&str, &str)>) -> DelegationStrategy {
+    DelegationStrategy {
+        validators: validators
+            .into_iter()
+            .map(|(validator, weight)| ValidatorWeight {
+                validator: validator.to_string(),
+                weight: weight.parse::<Decimal>().unwrap(),
+            })
+            .collect(),
+    }
+}
+
+fn set_strategy(
+    deps: DepsMut,
+    strategy: DelegationStrategy,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    execute(
+        deps,
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::SetDelegationStrategy(strategy),
+    )
+}
+
+fn rebalance(deps: DepsMut, env: Env) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    execute(
+        deps,
+        env,
+        mock_info("controller", &[]),
+        ExecuteMsg::RebalanceDelegations {},
+    )
+}
+
+fn query_plan(deps: Deps, env: Env) -> Vec<RebalanceAction> {
+    from_json(query(deps, env, QueryMsg::RebalancePlan {}).unwrap()).unwrap()
+}
+
+// Amounts currently delegated, leaving out validators only holding unbondings
+fn delegated(deps: Deps) -> Vec<(String, Uint128)> {
+    query_delegations(deps)
+        .into_iter()
+        .filter(|delegation| !delegation.delegated.is_zero())
+        .map(|delegation| (delegation.validator, delegation.delegated))
+        .collect()
+}
+
+#[test]
+fn test_set_delegation_strategy() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+
+    let err = set_strategy(
+        deps.as_mut(),
+        strategy(vec![("validator1", "0.5"), ("validator2", "0.4")]),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidDelegationStrategy {});
+
+    let err = set_strategy(
+        deps.as_mut(),
+        strategy(vec![("validator1", "0.5"), ("validator1", "0.5")]),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidDelegationStrategy {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller", &[]),
+        ExecuteMsg::SetDelegationStrategy(strategy(vec![("validator1", "1")])),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let new_strategy = strategy(vec![("validator1", "0.6"), ("validator2", "0.4")]);
+    set_strategy(deps.as_mut(), new_strategy.clone()).unwrap();
+    let res: DelegationStrategy =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::DelegationStrategy {}).unwrap())
+            .unwrap();
+    assert_eq!(res, new_strategy);
+}
+
+#[test]
+fn test_rebalance_requires_strategy() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+
+    let err = rebalance(deps.as_mut(), mock_env()).unwrap_err();
+    assert_eq!(err, ContractError::DelegationStrategyNotSet {});
+
+    set_strategy(deps.as_mut(), strategy(vec![("validator1", "1")])).unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user", &[]),
+        ExecuteMsg::RebalanceDelegations {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Rebalancing also delegates idle tokens
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::RevokeCapability {
+            address: "controller".to_string(),
+            capability: Capability::Delegate,
+        },
+    )
+    .unwrap();
+    let err = rebalance(deps.as_mut(), mock_env()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn test_rebalance_delegations() {
+    let mut deps = mock_dependencies_with_balance(&[coin(200, DENOM)]);
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+    alliance_delegate(
+        deps.as_mut(),
+        vec![("validator1", 600), ("validator2", 400)],
+    );
+    set_strategy(
+        deps.as_mut(),
+        strategy(vec![("validator2", "0.5"), ("validator3", "0.5")]),
+    )
+    .unwrap();
+
+    let plan = query_plan(deps.as_ref(), mock_env());
+    assert_eq!(
+        plan,
+        vec![
+            RebalanceAction::Redelegate(AllianceRedelegation {
+                src_validator: "validator1".to_string(),
+                dst_validator: "validator2".to_string(),
+                amount: Uint128::new(200),
+            }),
+            RebalanceAction::Redelegate(AllianceRedelegation {
+                src_validator: "validator1".to_string(),
+                dst_validator: "validator3".to_string(),
+                amount: Uint128::new(400),
+            }),
+            RebalanceAction::Delegate(AllianceDelegation {
+                validator: "validator3".to_string(),
+                amount: Uint128::new(200),
+            }),
+        ]
+    );
+
+    let res = rebalance(deps.as_mut(), mock_env()).unwrap();
+    assert_eq!(res.messages.len(), 3);
+    let completes_at = mock_env().block.time.plus_seconds(DEFAULT_UNBONDING_PERIOD);
+    assert_eq!(
+        query_delegations(deps.as_ref()),
+        vec![
+            ValidatorDelegation {
+                redelegating: vec![
+                    Unbonding {
+                        amount: Uint128::new(200),
+                        completes_at,
+                    },
+                    Unbonding {
+                        amount: Uint128::new(400),
+                        completes_at,
+                    },
+                ],
+                ..ValidatorDelegation::new("validator1".to_string())
+            },
+            ValidatorDelegation {
+                delegated: Uint128::new(600),
+                ..ValidatorDelegation::new("validator2".to_string())
+            },
+            ValidatorDelegation {
+                delegated: Uint128::new(600),
+                ..ValidatorDelegation::new("validator3".to_string())
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_rebalance_respects_redelegation_cooldown() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+    alliance_delegate(deps.as_mut(), vec![("validator1", 1000)]);
+    set_strategy(deps.as_mut(), strategy(vec![("validator2", "1")])).unwrap();
+    rebalance(deps.as_mut(), mock_env()).unwrap();
+
+    // validator2 received a redelegation and cannot redelegate it further yet, its stake
+    // waits for the cooldown instead of being undelegated
+    set_strategy(deps.as_mut(), strategy(vec![("validator3", "1")])).unwrap();
+    let plan = query_plan(deps.as_ref(), mock_env());
+    assert_eq!(plan, vec![]);
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(DEFAULT_UNBONDING_PERIOD);
+    let plan = query_plan(deps.as_ref(), env);
+    assert_eq!(
+        plan,
+        vec![RebalanceAction::Redelegate(AllianceRedelegation {
+            src_validator: "validator2".to_string(),
+            dst_validator: "validator3".to_string(),
+            amount: Uint128::new(1000),
+        })]
+    );
+}
+
+#[test]
+fn test_rebalance_delegates_idle_around_cooling_stake() {
+    let mut deps = mock_dependencies_with_balance(&[coin(200, DENOM)]);
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+    alliance_delegate(deps.as_mut(), vec![("validator1", 1000)]);
+    set_strategy(deps.as_mut(), strategy(vec![("validator2", "1")])).unwrap();
+    let mut env = mock_env();
+    rebalance(deps.as_mut(), env.clone()).unwrap();
+    assert_eq!(
+        delegated(deps.as_ref()),
+        vec![("validator2".to_string(), Uint128::new(1200))]
+    );
+
+    // Idle tokens go to the new validator while the cooling stake stays put
+    deps.querier
+        .update_balance(env.contract.address.clone(), vec![coin(300, DENOM)]);
+    set_strategy(deps.as_mut(), strategy(vec![("validator3", "1")])).unwrap();
+    let res = rebalance(deps.as_mut(), env.clone()).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        delegated(deps.as_ref()),
+        vec![
+            ("validator2".to_string(), Uint128::new(1200)),
+            ("validator3".to_string(), Uint128::new(300)),
+        ]
+    );
+
+    deps.querier
+        .update_balance(env.contract.address.clone(), vec![]);
+    env.block.time = env.block.time.plus_seconds(DEFAULT_UNBONDING_PERIOD);
+    assert_eq!(
+        query_plan(deps.as_ref(), env),
+        vec![RebalanceAction::Redelegate(AllianceRedelegation {
+            src_validator: "validator2".to_string(),
+            dst_validator: "validator3".to_string(),
+            amount: Uint128::new(1200),
+        })]
+    );
+}
+
+#[test]
+fn test_rebalance_respects_manual_redelegation() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+    alliance_delegate(deps.as_mut(), vec![("validator1", 1000)]);
+
+    // Redelegated before any strategy is set, validator2 is still locked as a source
+    alliance_redelegate(deps.as_mut(), vec![("validator1", "validator2", 1000)]);
+    set_strategy(deps.as_mut(), strategy(vec![("validator3", "1")])).unwrap();
+    assert_eq!(query_plan(deps.as_ref(), mock_env()), vec![]);
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(DEFAULT_UNBONDING_PERIOD);
+    assert_eq!(
+        query_plan(deps.as_ref(), env),
+        vec![RebalanceAction::Redelegate(AllianceRedelegation {
+            src_validator: "validator2".to_string(),
+            dst_validator: "validator3".to_string(),
+            amount: Uint128::new(1000),
+        })]
+    );
+}
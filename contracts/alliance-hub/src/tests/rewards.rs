@@ -1,27 +1,32 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 use cosmwasm_std::testing::{mock_dependencies_with_balance, mock_env, mock_info};
 use cosmwasm_std::{
-    coin, coins, to_json_binary, Addr, BankMsg, Binary, CosmosMsg, Decimal, Response, SubMsg,
-    Uint128, WasmMsg,
+    coin, coins, from_json, to_json_binary, Addr, BankMsg, Binary, CosmosMsg, Decimal, Deps,
+    DepsMut, Reply, Response, SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
 };
 use cw_asset_v3::AssetInfo;
 use terra_proto_rs::alliance::alliance::MsgClaimDelegationRewards;
 use terra_proto_rs::traits::Message;
 
-use alliance_protocol::alliance_protocol::{AssetDistribution, ExecuteMsg, PendingRewardsRes};
+use alliance_protocol::alliance_protocol::{
+    AssetDistribution, ClaimFailure, ExecuteMsg, PendingRewardsRes, QueryMsg,
+};
 
-use crate::contract::execute;
+use crate::contract::{execute, reply};
 use crate::error::ContractError;
+use crate::query::query;
 use crate::state::{
-    ASSET_REWARD_DISTRIBUTION, ASSET_REWARD_RATE, TEMP_BALANCE, TOTAL_BALANCES,
-    USER_ASSET_REWARD_RATE, VALIDATORS,
+    ASSET_REWARD_DISTRIBUTION, ASSET_REWARD_RATE, CONFIG, TEMP_BALANCE, TOTAL_BALANCES,
+    USER_ASSET_REWARD_RATE,
 };
 use crate::tests::helpers::{
-    asset_distribution_1, asset_distribution_2, asset_distribution_broken_1,
-    asset_distribution_broken_2, claim_rewards, query_all_rewards, query_asset_reward_distribution,
-    query_rewards, set_alliance_asset, setup_contract, stake, unstake, whitelist_assets, DENOM,
+    alliance_delegate, asset_distribution_1, asset_distribution_2, asset_distribution_broken_1,
+    asset_distribution_broken_2, claim_rewards, hub_event, query_all_rewards,
+    query_asset_reward_distribution, query_rewards, set_alliance_asset, setup_contract, stake,
+    unstake, whitelist_assets, DENOM,
 };
+use crate::token_factory::CustomExecuteMsg;
 
 #[test]
 fn test_update_rewards() {
@@ -29,12 +34,7 @@ fn test_update_rewards() {
     setup_contract(deps.as_mut());
     set_alliance_asset(deps.as_mut());
 
-    VALIDATORS
-        .save(
-            deps.as_mut().storage,
-            &HashSet::from(["validator1".to_string()]),
-        )
-        .unwrap();
+    alliance_delegate(deps.as_mut(), vec![("validator1", 100)]);
 
     let res = execute(
         deps.as_mut(),
@@ -46,7 +46,7 @@ fn test_update_rewards() {
     assert_eq!(
         res.messages,
         vec![
-            SubMsg::reply_on_error(
+            SubMsg::reply_always(
                 CosmosMsg::Stargate {
                     type_url: "/alliance.alliance.MsgClaimDelegationRewards".to_string(),
                     value: Binary::from(
@@ -77,12 +77,10 @@ fn test_update_rewards_with_funds_sent() {
     setup_contract(deps.as_mut());
     set_alliance_asset(deps.as_mut());
 
-    VALIDATORS
-        .save(
-            deps.as_mut().storage,
-            &HashSet::from(["validator1".to_string(), "validator2".to_string()]),
-        )
-        .unwrap();
+    alliance_delegate(
+        deps.as_mut(),
+        vec![("validator1", 100), ("validator2", 100)],
+    );
 
     deps.querier
         .update_balance("cosmos2contract", vec![coin(2000000, "uluna")]);
@@ -109,6 +107,7 @@ fn update_reward_callback() {
             deps.as_mut().storage,
             &AssetInfo::Native("aWHALE".to_string()),
             &Uint128::new(1000000),
+            mock_env().block.height,
         )
         .unwrap();
     TOTAL_BALANCES
@@ -116,6 +115,7 @@ fn update_reward_callback() {
             deps.as_mut().storage,
             &AssetInfo::Native("bWHALE".to_string()),
             &Uint128::new(100000),
+            mock_env().block.height,
         )
         .unwrap();
 
@@ -179,7 +179,54 @@ fn update_reward_callback() {
 
     assert_eq!(
         res,
-        Response::new().add_attributes(vec![("action", "update_rewards_callback")])
+        Response::new()
+            .add_attributes(vec![("action", "update_rewards_callback")])
+            .add_event(hub_event(
+                "rewards_distributed",
+                vec![
+                    ("asset", "native:aWHALE"),
+                    ("reward_asset", "native:uluna"),
+                    ("amount", "100000"),
+                    ("reward_index_delta", "0.1"),
+                    ("reward_index", "0.1")
+                ]
+            ))
+            .add_event(hub_event(
+                "rewards_distributed",
+                vec![
+                    ("asset", "native:bWHALE"),
+                    ("reward_asset", "native:uluna"),
+                    ("amount", "600000"),
+                    ("reward_index_delta", "6"),
+                    ("reward_index", "6")
+                ]
+            ))
+    );
+}
+
+#[test]
+fn test_update_reward_callback_balance_decreased() {
+    let mut deps = mock_dependencies_with_balance(&[coin(900000, "uluna")]);
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+    TEMP_BALANCE
+        .save(deps.as_mut().storage, &Uint128::new(1000000))
+        .unwrap();
+
+    // Rewards are never negative, the update fails instead of wrapping around
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("cosmos2contract", &[]),
+        ExecuteMsg::UpdateRewardsCallback {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::RewardBalanceDecreased {
+            previous: Uint128::new(1000000),
+            current: Uint128::new(900000),
+        }
     );
 }
 
@@ -254,6 +301,15 @@ fn claim_user_rewards() {
                 ("asset", "native:aWHALE"),
                 ("reward_amount", "100000"),
             ])
+            .add_event(hub_event(
+                "claim",
+                vec![
+                    ("user", "user1"),
+                    ("asset", "native:aWHALE"),
+                    ("reward_asset", "native:uluna"),
+                    ("amount", "100000")
+                ]
+            ))
             .add_message(CosmosMsg::Bank(BankMsg::Send {
                 to_address: "user1".to_string(),
                 amount: coins(100000, "uluna"),
@@ -300,12 +356,22 @@ fn claim_user_rewards() {
     let res = claim_rewards(deps.as_mut(), "user1", "aWHALE");
     assert_eq!(
         res,
-        Response::new().add_attributes(vec![
-            ("action", "claim_rewards"),
-            ("user", "user1"),
-            ("asset", "native:aWHALE"),
-            ("reward_amount", "0"),
-        ])
+        Response::new()
+            .add_attributes(vec![
+                ("action", "claim_rewards"),
+                ("user", "user1"),
+                ("asset", "native:aWHALE"),
+                ("reward_amount", "0"),
+            ])
+            .add_event(hub_event(
+                "claim",
+                vec![
+                    ("user", "user1"),
+                    ("asset", "native:aWHALE"),
+                    ("reward_asset", "native:uluna"),
+                    ("amount", "0")
+                ]
+            ))
     );
 
     // Update more rewards
@@ -331,6 +397,15 @@ fn claim_user_rewards() {
                 ("asset", "native:aWHALE"),
                 ("reward_amount", "10000"),
             ])
+            .add_event(hub_event(
+                "claim",
+                vec![
+                    ("user", "user1"),
+                    ("asset", "native:aWHALE"),
+                    ("reward_asset", "native:uluna"),
+                    ("amount", "10000")
+                ]
+            ))
             .add_message(CosmosMsg::Bank(BankMsg::Send {
                 to_address: "user1".to_string(),
                 amount: coins(10000, "uluna"),
@@ -391,6 +466,15 @@ fn claim_user_rewards_after_staking() {
                 ("asset", "native:aWHALE"),
                 ("reward_amount", "100000"),
             ])
+            .add_event(hub_event(
+                "claim",
+                vec![
+                    ("user", "user1"),
+                    ("asset", "native:aWHALE"),
+                    ("reward_asset", "native:uluna"),
+                    ("amount", "100000")
+                ]
+            ))
             .add_message(CosmosMsg::Bank(BankMsg::Send {
                 to_address: "user1".to_string(),
                 amount: coins(100000, "uluna"),
@@ -401,12 +485,22 @@ fn claim_user_rewards_after_staking() {
     let res = claim_rewards(deps.as_mut(), "user1", "aWHALE");
     assert_eq!(
         res,
-        Response::new().add_attributes(vec![
-            ("action", "claim_rewards"),
-            ("user", "user1"),
-            ("asset", "native:aWHALE"),
-            ("reward_amount", "0"),
-        ])
+        Response::new()
+            .add_attributes(vec![
+                ("action", "claim_rewards"),
+                ("user", "user1"),
+                ("asset", "native:aWHALE"),
+                ("reward_amount", "0"),
+            ])
+            .add_event(hub_event(
+                "claim",
+                vec![
+                    ("user", "user1"),
+                    ("asset", "native:aWHALE"),
+                    ("reward_asset", "native:uluna"),
+                    ("amount", "0")
+                ]
+            ))
     );
 }
 
@@ -650,3 +744,278 @@ fn test_set_asset_reward_distribution() {
 
     assert_eq!(err, ContractError::InvalidDistribution {});
 }
+
+fn update_rewards(deps: DepsMut) -> Response<CustomExecuteMsg> {
+    execute(
+        deps,
+        mock_env(),
+        mock_info("user", &[]),
+        ExecuteMsg::UpdateRewards {},
+    )
+    .unwrap()
+}
+
+fn claim_reply(deps: DepsMut, result: SubMsgResult) -> Response<CustomExecuteMsg> {
+    reply(deps, mock_env(), Reply { id: 2, result }).unwrap()
+}
+
+fn query_claim_failures(deps: Deps) -> Vec<ClaimFailure> {
+    from_json(query(deps, mock_env(), QueryMsg::ClaimFailures {}).unwrap()).unwrap()
+}
+
+#[test]
+fn test_claim_failures() {
+    let mut deps = mock_dependencies_with_balance(&[coin(1000000, "uluna")]);
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+    alliance_delegate(
+        deps.as_mut(),
+        vec![("validator1", 100), ("validator2", 100)],
+    );
+
+    for failures in 1..=3 {
+        let res = update_rewards(deps.as_mut());
+        assert_eq!(res.messages.len(), 3);
+        let res = claim_reply(
+            deps.as_mut(),
+            SubMsgResult::Err("no delegation".to_string()),
+        );
+        assert_eq!(
+            res.attributes,
+            vec![
+                ("action", "claim_reward_error"),
+                ("validator", "validator1"),
+                ("error", "no delegation"),
+                ("consecutive_failures", &failures.to_string()),
+                ("excluded", &(failures == 3).to_string()),
+            ]
+        );
+        claim_reply(
+            deps.as_mut(),
+            SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        );
+    }
+    assert_eq!(
+        query_claim_failures(deps.as_ref()),
+        vec![ClaimFailure {
+            validator: "validator1".to_string(),
+            consecutive_failures: 3,
+            last_error: "no delegation".to_string(),
+            excluded: true,
+        }]
+    );
+
+    // Excluded validators are skipped until they receive a new delegation
+    let res = update_rewards(deps.as_mut());
+    assert_eq!(res.messages.len(), 2);
+    claim_reply(deps.as_mut(), SubMsgResult::Err("slashed".to_string()));
+    assert_eq!(
+        query_claim_failures(deps.as_ref()),
+        vec![
+            ClaimFailure {
+                validator: "validator1".to_string(),
+                consecutive_failures: 3,
+                last_error: "no delegation".to_string(),
+                excluded: true,
+            },
+            ClaimFailure {
+                validator: "validator2".to_string(),
+                consecutive_failures: 1,
+                last_error: "slashed".to_string(),
+                excluded: false,
+            },
+        ]
+    );
+
+    alliance_delegate(deps.as_mut(), vec![("validator1", 100)]);
+    let res = update_rewards(deps.as_mut());
+    assert_eq!(res.messages.len(), 3);
+    assert_eq!(query_claim_failures(deps.as_ref()).len(), 1);
+}
+
+#[test]
+fn test_reset_claim_failures() {
+    let mut deps = mock_dependencies_with_balance(&[coin(1000000, "uluna")]);
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+    alliance_delegate(deps.as_mut(), vec![("validator1", 100)]);
+    for _ in 0..3 {
+        update_rewards(deps.as_mut());
+        claim_reply(
+            deps.as_mut(),
+            SubMsgResult::Err("no delegation".to_string()),
+        );
+    }
+    // Only the callback is left while validator1 is excluded
+    assert_eq!(update_rewards(deps.as_mut()).messages.len(), 1);
+
+    let reset = ExecuteMsg::ResetClaimFailures {
+        validator: "validator1".to_string(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user", &[]),
+        reset.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller", &[]),
+        reset,
+    )
+    .unwrap();
+    assert_eq!(query_claim_failures(deps.as_ref()), vec![]);
+    assert_eq!(update_rewards(deps.as_mut()).messages.len(), 2);
+}
+
+#[test]
+fn test_update_rewards_config() {
+    let mut deps = mock_dependencies_with_balance(&[coin(1000000, "uluna")]);
+    setup_contract(deps.as_mut());
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user", &[]),
+        ExecuteMsg::UpdateRewardsConfig {
+            reward_update_interval: Some(3600),
+            keeper_bounty: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::UpdateRewardsConfig {
+            reward_update_interval: None,
+            keeper_bounty: Some(Decimal::percent(11)),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::KeeperBountyTooHigh(Decimal::percent(10))
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::UpdateRewardsConfig {
+            reward_update_interval: Some(3600),
+            keeper_bounty: Some(Decimal::percent(1)),
+        },
+    )
+    .unwrap();
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(config.reward_update_interval, 3600);
+    assert_eq!(config.keeper_bounty, Decimal::percent(1));
+}
+
+#[test]
+fn test_update_rewards_interval_and_keeper_bounty() {
+    let mut deps = mock_dependencies_with_balance(&[coin(2000000, "uluna")]);
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::UpdateRewardsConfig {
+            reward_update_interval: Some(3600),
+            keeper_bounty: Some(Decimal::percent(1)),
+        },
+    )
+    .unwrap();
+    TOTAL_BALANCES
+        .save(
+            deps.as_mut().storage,
+            &AssetInfo::Native("aWHALE".to_string()),
+            &Uint128::new(1000000),
+            mock_env().block.height,
+        )
+        .unwrap();
+    ASSET_REWARD_DISTRIBUTION
+        .save(
+            deps.as_mut().storage,
+            &vec![AssetDistribution {
+                asset: AssetInfo::Native("aWHALE".to_string()),
+                distribution: Decimal::percent(100),
+            }],
+        )
+        .unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("keeper", &[]),
+        ExecuteMsg::UpdateRewards {},
+    )
+    .unwrap();
+    // 1000000 uluna of rewards were claimed by the submessages
+    TEMP_BALANCE
+        .save(deps.as_mut().storage, &Uint128::new(1000000))
+        .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("cosmos2contract", &[]),
+        ExecuteMsg::UpdateRewardsCallback {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "keeper".to_string(),
+            amount: coins(10000, "uluna"),
+        }))]
+    );
+    assert_eq!(
+        ASSET_REWARD_RATE
+            .load(
+                deps.as_ref().storage,
+                &AssetInfo::Native("aWHALE".to_string())
+            )
+            .unwrap(),
+        Decimal::percent(99)
+    );
+    assert_eq!(
+        CONFIG
+            .load(deps.as_ref().storage)
+            .unwrap()
+            .last_reward_update_timestamp,
+        mock_env().block.time
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("keeper", &[]),
+        ExecuteMsg::UpdateRewards {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::RewardUpdateTooSoon(mock_env().block.time.plus_seconds(3600))
+    );
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(3600);
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("keeper", &[]),
+        ExecuteMsg::UpdateRewards {},
+    )
+    .unwrap();
+}
@@ -0,0 +1,239 @@
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{from_json, Addr, Deps, Timestamp};
+
+use alliance_protocol::alliance_protocol::{
+    Config, ExecuteMsg, PendingRoleTransfer, QueryMsg, Role,
+};
+
+use crate::contract::execute;
+use crate::error::ContractError;
+use crate::query::query;
+use crate::tests::helpers::setup_contract;
+
+fn query_config(deps: Deps) -> Config {
+    from_json(query(deps, mock_env(), QueryMsg::Config {}).unwrap()).unwrap()
+}
+
+fn query_pending_transfers(deps: Deps) -> Vec<PendingRoleTransfer> {
+    from_json(query(deps, mock_env(), QueryMsg::PendingRoleTransfers {}).unwrap()).unwrap()
+}
+
+#[test]
+fn test_role_transfer() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+
+    let propose = |role: Role, new_address: &str| ExecuteMsg::ProposeRoleTransfer {
+        role,
+        new_address: new_address.to_string(),
+        expires_in: 100,
+    };
+
+    // Only the role holder or governance can propose
+    for sender in ["unauthorized", "operator", "oracle"] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(sender, &[]),
+            propose(Role::Controller, "new_controller"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller", &[]),
+        propose(Role::Controller, "new_controller"),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        propose(Role::Governance, "new_gov"),
+    )
+    .unwrap();
+
+    let expiry = mock_env().block.time.plus_seconds(100);
+    assert_eq!(
+        query_pending_transfers(deps.as_ref()),
+        vec![
+            PendingRoleTransfer {
+                role: Role::Controller,
+                proposer: Addr::unchecked("controller"),
+                new_address: Addr::unchecked("new_controller"),
+                expiry,
+            },
+            PendingRoleTransfer {
+                role: Role::Governance,
+                proposer: Addr::unchecked("gov"),
+                new_address: Addr::unchecked("new_gov"),
+                expiry,
+            },
+        ]
+    );
+
+    // Nothing changes until the new address accepts
+    let config = query_config(deps.as_ref());
+    assert_eq!(config.governance, Addr::unchecked("gov"));
+    assert_eq!(config.controller, Addr::unchecked("controller"));
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::AcceptRoleTransfer {
+            role: Role::Controller,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_controller", &[]),
+        ExecuteMsg::AcceptRoleTransfer {
+            role: Role::Controller,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_gov", &[]),
+        ExecuteMsg::AcceptRoleTransfer {
+            role: Role::Governance,
+        },
+    )
+    .unwrap();
+
+    let config = query_config(deps.as_ref());
+    assert_eq!(config.governance, Addr::unchecked("new_gov"));
+    assert_eq!(config.controller, Addr::unchecked("new_controller"));
+    assert_eq!(config.oracle, Addr::unchecked("oracle"));
+    assert_eq!(config.operator, Addr::unchecked("operator"));
+    assert!(query_pending_transfers(deps.as_ref()).is_empty());
+
+    // Accepting twice fails since the proposal was consumed
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_controller", &[]),
+        ExecuteMsg::AcceptRoleTransfer {
+            role: Role::Controller,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NoPendingRoleTransfer("controller".to_string())
+    );
+}
+
+#[test]
+fn test_role_transfer_expired() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::ProposeRoleTransfer {
+            role: Role::Operator,
+            new_address: "new_operator".to_string(),
+            expires_in: 100,
+        },
+    )
+    .unwrap();
+
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(mock_env().block.time.seconds() + 101);
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info("new_operator", &[]),
+        ExecuteMsg::AcceptRoleTransfer {
+            role: Role::Operator,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::RoleTransferExpired("operator".to_string())
+    );
+    assert_eq!(
+        query_config(deps.as_ref()).operator,
+        Addr::unchecked("operator")
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::ProposeRoleTransfer {
+            role: Role::Operator,
+            new_address: "new_operator".to_string(),
+            expires_in: 0,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidRoleTransferExpiry {});
+}
+
+#[test]
+fn test_cancel_role_transfer() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        ExecuteMsg::ProposeRoleTransfer {
+            role: Role::Operator,
+            new_address: "new_operator".to_string(),
+            expires_in: 100,
+        },
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller", &[]),
+        ExecuteMsg::CancelRoleTransfer {
+            role: Role::Operator,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        ExecuteMsg::CancelRoleTransfer {
+            role: Role::Operator,
+        },
+    )
+    .unwrap();
+    assert!(query_pending_transfers(deps.as_ref()).is_empty());
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_operator", &[]),
+        ExecuteMsg::AcceptRoleTransfer {
+            role: Role::Operator,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NoPendingRoleTransfer("operator".to_string())
+    );
+}
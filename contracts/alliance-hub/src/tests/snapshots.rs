@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+
+use alliance_protocol::alliance_protocol::{
+    ExecuteMsg, MigrateMsg, QueryMsg, StakedBalanceAtHeightRes,
+};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{coin, from_json, Addr, Deps, DepsMut, Env, Uint128};
+use cw2::set_contract_version;
+use cw_asset_v3::{Asset, AssetInfo};
+use cw_storage_plus_016::Map as Map016;
+
+use crate::contract::{execute, migrate};
+use crate::query::query;
+use crate::tests::helpers::{setup_contract, whitelist_assets};
+
+fn env_at(height: u64) -> Env {
+    let mut env = mock_env();
+    env.block.height = height;
+    env
+}
+
+fn stake_at(deps: DepsMut, height: u64, amount: u128) {
+    execute(
+        deps,
+        env_at(height),
+        mock_info("user1", &[coin(amount, "aWHALE")]),
+        ExecuteMsg::Stake {
+            recipient: None,
+            origin: None,
+        },
+    )
+    .unwrap();
+}
+
+fn balance_at(deps: Deps, height: u64) -> u128 {
+    let res: StakedBalanceAtHeightRes = from_json(
+        query(
+            deps,
+            mock_env(),
+            QueryMsg::StakedBalanceAtHeight {
+                address: "user1".to_string(),
+                asset: AssetInfo::native("aWHALE"),
+                height: Some(height),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    res.balance.u128()
+}
+
+fn total_at(deps: Deps, height: u64) -> u128 {
+    let res: StakedBalanceAtHeightRes = from_json(
+        query(
+            deps,
+            mock_env(),
+            QueryMsg::TotalStakedAtHeight {
+                asset: AssetInfo::native("aWHALE"),
+                height: Some(height),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    res.balance.u128()
+}
+
+#[test]
+fn test_staked_balance_at_height() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("aWHALE")])]),
+    );
+
+    stake_at(deps.as_mut(), 100, 1000);
+    stake_at(deps.as_mut(), 110, 500);
+    execute(
+        deps.as_mut(),
+        env_at(120),
+        mock_info("user1", &[]),
+        ExecuteMsg::Unstake(Asset::native("aWHALE", 1200u128)),
+    )
+    .unwrap();
+
+    // Balances are read at the start of the block
+    assert_eq!(balance_at(deps.as_ref(), 100), 0);
+    assert_eq!(balance_at(deps.as_ref(), 101), 1000);
+    assert_eq!(balance_at(deps.as_ref(), 110), 1000);
+    assert_eq!(balance_at(deps.as_ref(), 111), 1500);
+    assert_eq!(balance_at(deps.as_ref(), 121), 300);
+    assert_eq!(total_at(deps.as_ref(), 105), 1000);
+    assert_eq!(total_at(deps.as_ref(), 115), 1500);
+    assert_eq!(total_at(deps.as_ref(), 125), 300);
+
+    // The current block is used by default
+    let res: StakedBalanceAtHeightRes = from_json(
+        query(
+            deps.as_ref(),
+            env_at(130),
+            QueryMsg::TotalStakedAtHeight {
+                asset: AssetInfo::native("aWHALE"),
+                height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        StakedBalanceAtHeightRes {
+            asset: AssetInfo::native("aWHALE"),
+            balance: Uint128::new(300),
+            height: 130,
+        }
+    );
+}
+
+#[test]
+fn test_migrated_balances_are_seeded() {
+    let mut deps = mock_dependencies();
+    set_contract_version(
+        deps.as_mut().storage,
+        "crates.io:terra-alliance-protocol",
+        "0.1.2",
+    )
+    .unwrap();
+    deps.as_mut().storage.set(
+        b"config",
+        br#"{"governance":"gov","controller":"controller","oracle":"oracle","operator":"operator","last_reward_update_timestamp":"0","alliance_token_denom":"ualliance","alliance_token_supply":"1000","reward_denom":"uluna"}"#,
+    );
+    const OLD_BALANCES: Map016<(Addr, cw_asset_v2::AssetInfoKey), Uint128> =
+        Map016::new("balances");
+    const OLD_TOTAL_BALANCES: Map016<cw_asset_v2::AssetInfoKey, Uint128> =
+        Map016::new("total_balances");
+    let key = cw_asset_v2::AssetInfoKey(b"native:aWHALE".to_vec());
+    OLD_BALANCES
+        .save(
+            deps.as_mut().storage,
+            (Addr::unchecked("user1"), key.clone()),
+            &Uint128::new(1000),
+        )
+        .unwrap();
+    OLD_TOTAL_BALANCES
+        .save(deps.as_mut().storage, key, &Uint128::new(1000))
+        .unwrap();
+
+    migrate(deps.as_mut(), env_at(100), MigrateMsg::default()).unwrap();
+
+    // Balances staked before the migration count at earlier heights as well
+    assert_eq!(balance_at(deps.as_ref(), 50), 1000);
+    assert_eq!(total_at(deps.as_ref(), 50), 1000);
+    assert_eq!(balance_at(deps.as_ref(), 150), 1000);
+}
@@ -2,7 +2,7 @@ use crate::contract::execute;
 use crate::error::ContractError;
 use crate::state::{BALANCES, TOTAL_BALANCES};
 use crate::tests::helpers::{
-    query_all_staked_balances, setup_contract, stake, stake_cw20, unstake, unstake_cw20,
+    hub_event, query_all_staked_balances, setup_contract, stake, stake_cw20, unstake, unstake_cw20,
     whitelist_assets,
 };
 use alliance_protocol::alliance_protocol::{ExecuteMsg, StakedBalanceRes};
@@ -29,12 +29,23 @@ mod cw20_support {
         let res = stake_cw20(deps.as_mut(), "user1", 100, "asset1");
         assert_eq!(
             res,
-            Response::default().add_attributes(vec![
-                ("action", "stake"),
-                ("user", "user1"),
-                ("asset", "cw20:asset1"),
-                ("amount", "100"),
-            ])
+            Response::default()
+                .add_attributes(vec![
+                    ("action", "stake"),
+                    ("user", "user1"),
+                    ("asset", "cw20:asset1"),
+                    ("amount", "100"),
+                ])
+                .add_event(hub_event(
+                    "stake",
+                    vec![
+                        ("user", "user1"),
+                        ("asset", "cw20:asset1"),
+                        ("amount", "100"),
+                        ("balance", "100"),
+                        ("total_balance", "100")
+                    ]
+                ))
         );
 
         let balance = BALANCES
@@ -52,12 +63,23 @@ mod cw20_support {
         let res = stake_cw20(deps.as_mut(), "user1", 100, "asset1");
         assert_eq!(
             res,
-            Response::default().add_attributes(vec![
-                ("action", "stake"),
-                ("user", "user1"),
-                ("asset", "cw20:asset1"),
-                ("amount", "100"),
-            ])
+            Response::default()
+                .add_attributes(vec![
+                    ("action", "stake"),
+                    ("user", "user1"),
+                    ("asset", "cw20:asset1"),
+                    ("amount", "100"),
+                ])
+                .add_event(hub_event(
+                    "stake",
+                    vec![
+                        ("user", "user1"),
+                        ("asset", "cw20:asset1"),
+                        ("amount", "100"),
+                        ("balance", "200"),
+                        ("total_balance", "200")
+                    ]
+                ))
         );
         let balance = BALANCES
             .load(
@@ -103,12 +125,23 @@ mod cw20_support {
         let res = stake_cw20(deps.as_mut(), "user1", 100, "asset1");
         assert_eq!(
             res,
-            Response::default().add_attributes(vec![
-                ("action", "stake"),
-                ("user", "user1"),
-                ("asset", "cw20:asset1"),
-                ("amount", "100"),
-            ])
+            Response::default()
+                .add_attributes(vec![
+                    ("action", "stake"),
+                    ("user", "user1"),
+                    ("asset", "cw20:asset1"),
+                    ("amount", "100"),
+                ])
+                .add_event(hub_event(
+                    "stake",
+                    vec![
+                        ("user", "user1"),
+                        ("asset", "cw20:asset1"),
+                        ("amount", "100"),
+                        ("balance", "100"),
+                        ("total_balance", "100")
+                    ]
+                ))
         );
 
         let res = unstake_cw20(deps.as_mut(), "user1", 50, "asset1");
@@ -121,6 +154,16 @@ mod cw20_support {
                     ("asset", "cw20:asset1"),
                     ("amount", "50"),
                 ])
+                .add_event(hub_event(
+                    "unstake",
+                    vec![
+                        ("user", "user1"),
+                        ("asset", "cw20:asset1"),
+                        ("amount", "50"),
+                        ("balance", "50"),
+                        ("total_balance", "50")
+                    ]
+                ))
                 .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
                     contract_addr: "asset1".into(),
                     msg: to_json_binary(&cw20::Cw20ExecuteMsg::Transfer {
@@ -153,6 +196,16 @@ mod cw20_support {
                     ("asset", "cw20:asset1"),
                     ("amount", "50"),
                 ])
+                .add_event(hub_event(
+                    "unstake",
+                    vec![
+                        ("user", "user1"),
+                        ("asset", "cw20:asset1"),
+                        ("amount", "50"),
+                        ("balance", "0"),
+                        ("total_balance", "0")
+                    ]
+                ))
                 .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
                     contract_addr: "asset1".into(),
                     msg: to_json_binary(&cw20::Cw20ExecuteMsg::Transfer {
@@ -199,12 +252,23 @@ fn test_stake() {
     let res = stake(deps.as_mut(), "user1", 100, "asset1");
     assert_eq!(
         res,
-        Response::default().add_attributes(vec![
-            ("action", "stake"),
-            ("user", "user1"),
-            ("asset", "native:asset1"),
-            ("amount", "100"),
-        ])
+        Response::default()
+            .add_attributes(vec![
+                ("action", "stake"),
+                ("user", "user1"),
+                ("asset", "native:asset1"),
+                ("amount", "100"),
+            ])
+            .add_event(hub_event(
+                "stake",
+                vec![
+                    ("user", "user1"),
+                    ("asset", "native:asset1"),
+                    ("amount", "100"),
+                    ("balance", "100"),
+                    ("total_balance", "100")
+                ]
+            ))
     );
 
     let balance = BALANCES
@@ -222,12 +286,23 @@ fn test_stake() {
     let res = stake(deps.as_mut(), "user1", 100, "asset1");
     assert_eq!(
         res,
-        Response::default().add_attributes(vec![
-            ("action", "stake"),
-            ("user", "user1"),
-            ("asset", "native:asset1"),
-            ("amount", "100"),
-        ])
+        Response::default()
+            .add_attributes(vec![
+                ("action", "stake"),
+                ("user", "user1"),
+                ("asset", "native:asset1"),
+                ("amount", "100"),
+            ])
+            .add_event(hub_event(
+                "stake",
+                vec![
+                    ("user", "user1"),
+                    ("asset", "native:asset1"),
+                    ("amount", "100"),
+                    ("balance", "200"),
+                    ("total_balance", "200")
+                ]
+            ))
     );
     let balance = BALANCES
         .load(
@@ -270,25 +345,37 @@ fn test_stake_invalid() {
         )]),
     );
     // Stake an unwhitelisted asset
-    let msg = ExecuteMsg::Stake {};
+    let msg = ExecuteMsg::Stake {
+        recipient: None,
+        origin: None,
+    };
     let info = mock_info("user1", &[coin(100, "asset2")]);
     let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(err, ContractError::AssetNotWhitelisted {});
 
     // Stake multiple assets in a single call
-    let msg = ExecuteMsg::Stake {};
+    let msg = ExecuteMsg::Stake {
+        recipient: None,
+        origin: None,
+    };
     let info = mock_info("user1", &[coin(100, "asset1"), coin(100, "asset2")]);
     let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(err, ContractError::OnlySingleAssetAllowed {});
 
     // Stake nothing in a single call
-    let msg = ExecuteMsg::Stake {};
+    let msg = ExecuteMsg::Stake {
+        recipient: None,
+        origin: None,
+    };
     let info = mock_info("user1", &[]);
     let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(err, ContractError::OnlySingleAssetAllowed {});
 
     // Stake zero amount
-    let msg = ExecuteMsg::Stake {};
+    let msg = ExecuteMsg::Stake {
+        recipient: None,
+        origin: None,
+    };
     let info = mock_info("user1", &[coin(0, "asset1")]);
     let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(err, ContractError::AmountCannotBeZero {});
@@ -318,6 +405,16 @@ fn test_unstake() {
                 ("asset", "native:asset1"),
                 ("amount", "50"),
             ])
+            .add_event(hub_event(
+                "unstake",
+                vec![
+                    ("user", "user1"),
+                    ("asset", "native:asset1"),
+                    ("amount", "50"),
+                    ("balance", "50"),
+                    ("total_balance", "50")
+                ]
+            ))
             .add_message(CosmosMsg::Bank(BankMsg::Send {
                 to_address: "user1".into(),
                 amount: vec![coin(50, "asset1")],
@@ -345,6 +442,16 @@ fn test_unstake() {
                 ("asset", "native:asset1"),
                 ("amount", "50"),
             ])
+            .add_event(hub_event(
+                "unstake",
+                vec![
+                    ("user", "user1"),
+                    ("asset", "native:asset1"),
+                    ("amount", "50"),
+                    ("balance", "0"),
+                    ("total_balance", "0")
+                ]
+            ))
             .add_message(CosmosMsg::Bank(BankMsg::Send {
                 to_address: "user1".into(),
                 amount: vec![coin(50, "asset1")],
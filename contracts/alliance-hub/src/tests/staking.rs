@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use alliance_protocol::alliance_protocol::{
+    AssetDistribution, ExecuteMsg, InstantiateMsg, StakingBackend,
+};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{
+    attr, coin, CosmosMsg, Decimal, DepsMut, DistributionMsg, Reply, Response, StakingMsg, SubMsg,
+    SubMsgResponse, SubMsgResult, Uint128,
+};
+use cw_asset_v3::AssetInfo;
+
+use crate::contract::{execute, instantiate, reply};
+use crate::error::ContractError;
+use crate::state::ASSET_REWARD_DISTRIBUTION;
+use crate::tests::helpers::{
+    alliance_delegate, alliance_redelegate, alliance_undelegate, query_rewards, set_alliance_asset,
+    stake, whitelist_assets,
+};
+use crate::token_factory::CustomExecuteMsg;
+
+fn instantiate_native(
+    deps: DepsMut,
+    bond_denom: Option<String>,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    let msg = InstantiateMsg {
+        governance: "gov".to_string(),
+        controller: "controller".to_string(),
+        oracle: "oracle".to_string(),
+        operator: "operator".to_string(),
+        alliance_token_denom: "ualliance".to_string(),
+        reward_asset: AssetInfo::native("uluna"),
+        alliance_token_metadata: None,
+        token_factory_backend: None,
+        staking_backend: Some(StakingBackend::Native { bond_denom }),
+        unbonding_period: None,
+    };
+    instantiate(deps, mock_env(), mock_info("admin", &[]), msg)
+}
+
+fn setup_native_contract(deps: DepsMut, bond_denom: Option<String>) {
+    instantiate_native(deps, bond_denom).unwrap();
+}
+
+#[test]
+fn test_native_backend_delegations() {
+    let mut deps = mock_dependencies();
+    setup_native_contract(deps.as_mut(), Some("ustake".to_string()));
+    set_alliance_asset(deps.as_mut());
+
+    let res = alliance_delegate(deps.as_mut(), vec![("validator1", 100)]);
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Staking(StakingMsg::Delegate {
+                validator: "validator1".to_string(),
+                amount: coin(100, "ustake"),
+            }),
+            5
+        )]
+    );
+
+    let res = alliance_redelegate(deps.as_mut(), vec![("validator1", "validator2", 40)]);
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Staking(StakingMsg::Redelegate {
+                src_validator: "validator1".to_string(),
+                dst_validator: "validator2".to_string(),
+                amount: coin(40, "ustake"),
+            }),
+            5
+        )]
+    );
+
+    let res = alliance_undelegate(deps.as_mut(), vec![("validator2", 40)]);
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Staking(StakingMsg::Undelegate {
+                validator: "validator2".to_string(),
+                amount: coin(40, "ustake"),
+            }),
+            5
+        )]
+    );
+}
+
+#[test]
+fn test_native_backend_distributes_withdrawn_rewards() {
+    let mut deps = mock_dependencies();
+    setup_native_contract(deps.as_mut(), Some("ustake".to_string()));
+    set_alliance_asset(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("aWHALE")])]),
+    );
+    stake(deps.as_mut(), "user1", 100, "aWHALE");
+    ASSET_REWARD_DISTRIBUTION
+        .save(
+            deps.as_mut().storage,
+            &vec![AssetDistribution {
+                asset: AssetInfo::native("aWHALE"),
+                distribution: Decimal::one(),
+            }],
+        )
+        .unwrap();
+    alliance_delegate(deps.as_mut(), vec![("validator1", 100)]);
+
+    // x/distribution withdraws 40 uluna of rewards along with the redelegation
+    alliance_redelegate(deps.as_mut(), vec![("validator1", "validator2", 100)]);
+    deps.querier
+        .update_balance("cosmos2contract", vec![coin(40, "uluna")]);
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 5,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "withdrawn_rewards"), attr("amount", "40")]
+    );
+
+    // They are owed to the stakers, not swept
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::Sweep {
+            asset: AssetInfo::native("uluna"),
+            recipient: "treasury".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NothingToSweep("native:uluna".to_string())
+    );
+
+    // The next update distributes them with the 60 uluna it claims
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user", &[]),
+        ExecuteMsg::UpdateRewards {},
+    )
+    .unwrap();
+    deps.querier
+        .update_balance("cosmos2contract", vec![coin(100, "uluna")]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("cosmos2contract", &[]),
+        ExecuteMsg::UpdateRewardsCallback {},
+    )
+    .unwrap();
+    assert_eq!(
+        query_rewards(deps.as_ref(), "user1", "aWHALE").rewards,
+        Uint128::new(100)
+    );
+}
+
+#[test]
+fn test_native_backend_claims_rewards() {
+    let mut deps = mock_dependencies();
+    setup_native_contract(deps.as_mut(), Some("ustake".to_string()));
+    set_alliance_asset(deps.as_mut());
+    alliance_delegate(deps.as_mut(), vec![("validator1", 100)]);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user", &[]),
+        ExecuteMsg::UpdateRewards {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::reply_always(
+            CosmosMsg::Distribution(DistributionMsg::WithdrawDelegatorReward {
+                validator: "validator1".to_string(),
+            }),
+            2,
+        )
+    );
+}
+
+#[test]
+fn test_native_backend_rejects_owed_bond_denom() {
+    let mut deps = mock_dependencies();
+    for bond_denom in ["", "uluna"] {
+        let err = instantiate_native(deps.as_mut(), Some(bond_denom.to_string())).unwrap_err();
+        assert_eq!(err, ContractError::InvalidBondDenom(bond_denom.to_string()));
+    }
+    // x/staking cannot bond the alliance token the other backends fall back to
+    let err = instantiate_native(deps.as_mut(), None).unwrap_err();
+    assert_eq!(err, ContractError::InvalidBondDenom("".to_string()));
+
+    // Staked and reward assets keep out of the bond denom once set
+    setup_native_contract(deps.as_mut(), Some("ustake".to_string()));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::WhitelistAssets(HashMap::from([(
+            "chain-1".to_string(),
+            vec![AssetInfo::native("ustake")],
+        )])),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidBondDenom("ustake".to_string()));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::UpdateRewardAsset(AssetInfo::native("ustake")),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidBondDenom("ustake".to_string()));
+}
@@ -0,0 +1,98 @@
+use alliance_protocol::alliance_protocol::{AssetDistribution, ExecuteMsg, PauseScope, SudoMsg};
+use cosmwasm_std::testing::{mock_dependencies_with_balance, mock_env, mock_info};
+use cosmwasm_std::{
+    coin, to_json_binary, CosmosMsg, Decimal, DepsMut, Env, Response, SubMsg, Uint128, WasmMsg,
+};
+use cw_asset_v3::AssetInfo;
+
+use crate::contract::{execute, sudo};
+use crate::state::{ASSET_REWARD_DISTRIBUTION, TEMP_BALANCE, TEMP_KEEPER};
+use crate::tests::helpers::{alliance_delegate, set_alliance_asset, setup_contract};
+use crate::token_factory::CustomExecuteMsg;
+
+fn end_block(deps: DepsMut, env: Env) -> Response<CustomExecuteMsg> {
+    sudo(deps, env, SudoMsg::EndBlock {}).unwrap()
+}
+
+fn skipped() -> Response<CustomExecuteMsg> {
+    Response::new().add_attributes(vec![("action", "end_block"), ("reward_update", "skipped")])
+}
+
+fn set_distribution(deps: DepsMut) {
+    ASSET_REWARD_DISTRIBUTION
+        .save(
+            deps.storage,
+            &vec![AssetDistribution {
+                asset: AssetInfo::Native("aWHALE".to_string()),
+                distribution: Decimal::percent(100),
+            }],
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_end_block_harvests_rewards() {
+    let mut deps = mock_dependencies_with_balance(&[coin(1000000, "uluna")]);
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+    alliance_delegate(deps.as_mut(), vec![("validator1", 100)]);
+
+    // Nothing to distribute to yet
+    assert_eq!(end_block(deps.as_mut(), mock_env()), skipped());
+
+    set_distribution(deps.as_mut());
+    let res = sudo(deps.as_mut(), mock_env(), SudoMsg::ClockEndBlock {}).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "cosmos2contract".to_string(),
+            msg: to_json_binary(&ExecuteMsg::UpdateRewardsCallback {}).unwrap(),
+            funds: vec![],
+        }))
+    );
+    assert_eq!(
+        TEMP_BALANCE.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(1000000)
+    );
+    assert_eq!(TEMP_KEEPER.may_load(deps.as_ref().storage).unwrap(), None);
+}
+
+#[test]
+fn test_end_block_skips_when_not_due() {
+    let mut deps = mock_dependencies_with_balance(&[coin(1000000, "uluna")]);
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+    set_distribution(deps.as_mut());
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::UpdateRewardsConfig {
+            reward_update_interval: Some(3600),
+            keeper_bounty: None,
+        },
+    )
+    .unwrap();
+
+    end_block(deps.as_mut(), mock_env());
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("cosmos2contract", &[]),
+        ExecuteMsg::UpdateRewardsCallback {},
+    )
+    .unwrap();
+    assert_eq!(end_block(deps.as_mut(), mock_env()), skipped());
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        ExecuteMsg::Pause(PauseScope::RewardUpdates {}),
+    )
+    .unwrap();
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(3600);
+    assert_eq!(end_block(deps.as_mut(), env), skipped());
+}
@@ -0,0 +1,126 @@
+use crate::contract::execute;
+use crate::error::ContractError;
+use crate::query::query;
+use crate::tests::helpers::{
+    alliance_delegate, alliance_undelegate, set_alliance_asset, setup_contract, DENOM,
+};
+use crate::token_factory::{CustomExecuteMsg, TokenExecuteMsg};
+use alliance_protocol::alliance_protocol::{
+    Config, ExecuteMsg, QueryMsg, DEFAULT_UNBONDING_PERIOD,
+};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{from_json, CosmosMsg, Deps, SubMsg, Uint128};
+
+fn query_supply(deps: Deps) -> Uint128 {
+    let config: Config = from_json(query(deps, mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    config.alliance_token_supply
+}
+
+#[test]
+fn test_mint_alliance_tokens() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller", &[]),
+        ExecuteMsg::MintAllianceTokens {
+            amount: Uint128::new(100),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::MintAllianceTokens {
+            amount: Uint128::zero(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::AmountCannotBeZero {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::MintAllianceTokens {
+            amount: Uint128::new(100),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Custom(CustomExecuteMsg::Token(
+            TokenExecuteMsg::MintTokens {
+                denom: DENOM.to_string(),
+                amount: Uint128::new(100),
+                mint_to_address: "cosmos2contract".to_string(),
+            }
+        )))]
+    );
+    assert_eq!(query_supply(deps.as_ref()), Uint128::new(1000000000100));
+}
+
+#[test]
+fn test_burn_alliance_tokens() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+    alliance_delegate(deps.as_mut(), vec![("validator1", 400000000000)]);
+    // Undelegated tokens stay backed by the supply until they are back in the hub
+    alliance_undelegate(deps.as_mut(), vec![("validator1", 100000000000)]);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::BurnAllianceTokens {
+            amount: Uint128::new(600000000001),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::BurnExceedsUndelegatedSupply("600000000001".to_string())
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::BurnAllianceTokens {
+            amount: Uint128::new(600000000000),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Custom(CustomExecuteMsg::Token(
+            TokenExecuteMsg::BurnTokens {
+                denom: DENOM.to_string(),
+                amount: Uint128::new(600000000000),
+                burn_from_address: "cosmos2contract".to_string(),
+            }
+        )))]
+    );
+    assert_eq!(query_supply(deps.as_ref()), Uint128::new(400000000000));
+
+    // Only the delegated tokens are out once the undelegation completed
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(DEFAULT_UNBONDING_PERIOD);
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("gov", &[]),
+        ExecuteMsg::BurnAllianceTokens {
+            amount: Uint128::new(100000000000),
+        },
+    )
+    .unwrap();
+    assert_eq!(query_supply(deps.as_ref()), Uint128::new(300000000000));
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use alliance_protocol::alliance_protocol::{AssetDistribution, Capability, ExecuteMsg};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{coin, coins, BankMsg, CosmosMsg, Decimal, DepsMut, Response, Uint128};
+use cw_asset_v3::AssetInfo;
+
+use crate::contract::execute;
+use crate::error::ContractError;
+use crate::state::{ASSET_REWARD_DISTRIBUTION, TEMP_BALANCE};
+use crate::tests::helpers::{
+    claim_rewards, hub_event, set_alliance_asset, setup_contract, stake, unstake, whitelist_assets,
+    DENOM,
+};
+use crate::token_factory::CustomExecuteMsg;
+
+fn sweep(
+    deps: DepsMut,
+    sender: &str,
+    asset: AssetInfo,
+) -> Result<Response<CustomExecuteMsg>, ContractError> {
+    execute(
+        deps,
+        mock_env(),
+        mock_info(sender, &[]),
+        ExecuteMsg::Sweep {
+            asset,
+            recipient: "treasury".to_string(),
+        },
+    )
+}
+
+// Distributes the `amount` of uluna above the hub balance to aWHALE stakers
+fn distribute_rewards(deps: DepsMut, balance: u128, amount: u128) {
+    ASSET_REWARD_DISTRIBUTION
+        .save(
+            deps.storage,
+            &vec![AssetDistribution {
+                asset: AssetInfo::native("aWHALE"),
+                distribution: Decimal::one(),
+            }],
+        )
+        .unwrap();
+    TEMP_BALANCE
+        .save(deps.storage, &Uint128::new(balance - amount))
+        .unwrap();
+    execute(
+        deps,
+        mock_env(),
+        mock_info("cosmos2contract", &[]),
+        ExecuteMsg::UpdateRewardsCallback {},
+    )
+    .unwrap();
+}
+
+fn sweep_msg(amount: u128, denom: &str) -> CosmosMsg<CustomExecuteMsg> {
+    CosmosMsg::Bank(BankMsg::Send {
+        to_address: "treasury".to_string(),
+        amount: coins(amount, denom),
+    })
+}
+
+#[test]
+fn test_sweep() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("aWHALE")])]),
+    );
+    stake(deps.as_mut(), "user1", 100, "aWHALE");
+    deps.querier.update_balance(
+        "cosmos2contract",
+        vec![
+            coin(150, "aWHALE"),
+            coin(1000, "uluna"),
+            coin(30, "ustray"),
+            coin(1000, DENOM),
+        ],
+    );
+    distribute_rewards(deps.as_mut(), 1000, 200);
+
+    let err = sweep(deps.as_mut(), "controller", AssetInfo::native("ustray")).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = sweep(deps.as_mut(), "gov", AssetInfo::native(DENOM)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CannotSweepAsset(format!("native:{}", DENOM))
+    );
+
+    // Stray denoms are swept in full, by any holder of the sweep capability
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::GrantCapability {
+            address: "controller".to_string(),
+            capability: Capability::Sweep,
+        },
+    )
+    .unwrap();
+    let res = sweep(deps.as_mut(), "controller", AssetInfo::native("ustray")).unwrap();
+    assert_eq!(
+        res,
+        Response::new()
+            .add_attributes(vec![
+                ("action", "sweep"),
+                ("asset", "native:ustray"),
+                ("amount", "30"),
+                ("recipient", "treasury"),
+            ])
+            .add_event(hub_event(
+                "sweep",
+                vec![
+                    ("asset", "native:ustray"),
+                    ("amount", "30"),
+                    ("recipient", "treasury"),
+                ]
+            ))
+            .add_message(sweep_msg(30, "ustray"))
+    );
+
+    // Staked assets keep the total staked
+    let res = sweep(deps.as_mut(), "gov", AssetInfo::native("aWHALE")).unwrap();
+    assert_eq!(res.messages[0].msg, sweep_msg(50, "aWHALE"));
+
+    // The reward asset keeps the rewards owed to the stakers
+    let res = sweep(deps.as_mut(), "gov", AssetInfo::native("uluna")).unwrap();
+    assert_eq!(res.messages[0].msg, sweep_msg(800, "uluna"));
+
+    deps.querier.update_balance(
+        "cosmos2contract",
+        vec![coin(100, "aWHALE"), coin(200, "uluna")],
+    );
+    let err = sweep(deps.as_mut(), "gov", AssetInfo::native("aWHALE")).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NothingToSweep("native:aWHALE".to_string())
+    );
+    let err = sweep(deps.as_mut(), "gov", AssetInfo::native("uluna")).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NothingToSweep("native:uluna".to_string())
+    );
+}
+
+#[test]
+fn test_sweep_follows_reward_liabilities() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    set_alliance_asset(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([("chain-1".to_string(), vec![AssetInfo::native("aWHALE")])]),
+    );
+    stake(deps.as_mut(), "user1", 100, "aWHALE");
+    stake(deps.as_mut(), "user2", 200, "aWHALE");
+    deps.querier
+        .update_balance("cosmos2contract", vec![coin(1000, "uluna")]);
+    distribute_rewards(deps.as_mut(), 1000, 100);
+
+    // A third of a unit is owed to each staker, rounded up as a whole
+    let res = sweep(deps.as_mut(), "gov", AssetInfo::native("uluna")).unwrap();
+    assert_eq!(res.messages[0].msg, sweep_msg(900, "uluna"));
+
+    // user1 is paid 33 and forfeits the fraction left
+    claim_rewards(deps.as_mut(), "user1", "aWHALE");
+    deps.querier
+        .update_balance("cosmos2contract", vec![coin(967, "uluna")]);
+    let res = sweep(deps.as_mut(), "gov", AssetInfo::native("uluna")).unwrap();
+    assert_eq!(res.messages[0].msg, sweep_msg(900, "uluna"));
+
+    // The 66 settled on unstake stay owed until claimed
+    unstake(deps.as_mut(), "user2", 200, "aWHALE");
+    let res = sweep(deps.as_mut(), "gov", AssetInfo::native("uluna")).unwrap();
+    assert_eq!(res.messages[0].msg, sweep_msg(901, "uluna"));
+
+    claim_rewards(deps.as_mut(), "user2", "aWHALE");
+    deps.querier
+        .update_balance("cosmos2contract", vec![coin(901, "uluna")]);
+    let res = sweep(deps.as_mut(), "gov", AssetInfo::native("uluna")).unwrap();
+    assert_eq!(res.messages[0].msg, sweep_msg(901, "uluna"));
+}
@@ -0,0 +1,170 @@
+use alliance_protocol::alliance_protocol::{
+    Config, ExecuteMsg, InstantiateMsg, QueryMsg, TokenFactoryBackend,
+};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{
+    from_json, Binary, CosmosMsg, DepsMut, Reply, SubMsg, SubMsgResponse, SubMsgResult, Uint128,
+};
+use cw_asset_v3::AssetInfo;
+use terra_proto_rs::cosmos::base::v1beta1::Coin;
+use terra_proto_rs::traits::Message;
+
+use crate::contract::{execute, instantiate, reply};
+use crate::error::ContractError;
+use crate::query::query;
+use crate::token_factory::{MsgBurn, MsgCreateDenom, MsgCreateDenomResponse, MsgMint};
+
+fn setup_osmosis_contract(deps: DepsMut) {
+    let msg = InstantiateMsg {
+        governance: "gov".to_string(),
+        controller: "controller".to_string(),
+        oracle: "oracle".to_string(),
+        operator: "operator".to_string(),
+        alliance_token_denom: "ualliance".to_string(),
+        reward_asset: AssetInfo::native("uluna"),
+        alliance_token_metadata: None,
+        token_factory_backend: Some(TokenFactoryBackend::Osmosis),
+        staking_backend: None,
+        unbonding_period: None,
+    };
+    let res = instantiate(deps, mock_env(), mock_info("admin", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Stargate {
+                type_url: "/osmosis.tokenfactory.v1beta1.MsgCreateDenom".to_string(),
+                value: Binary::from(
+                    MsgCreateDenom {
+                        sender: "cosmos2contract".to_string(),
+                        subdenom: "ualliance".to_string(),
+                    }
+                    .encode_to_vec()
+                ),
+            },
+            1,
+        )]
+    );
+}
+
+fn create_denom_reply(data: Option<Binary>) -> Reply {
+    Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data,
+        }),
+    }
+}
+
+#[test]
+fn test_osmosis_backend_create_denom() {
+    let mut deps = mock_dependencies();
+    setup_osmosis_contract(deps.as_mut());
+
+    let denom = "factory/cosmos2contract/ualliance";
+    let data = MsgCreateDenomResponse {
+        new_token_denom: denom.to_string(),
+    }
+    .encode_to_vec();
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        create_denom_reply(Some(Binary::from(data))),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Stargate {
+            type_url: "/osmosis.tokenfactory.v1beta1.MsgMint".to_string(),
+            value: Binary::from(
+                MsgMint {
+                    sender: "cosmos2contract".to_string(),
+                    amount: Some(Coin {
+                        denom: denom.to_string(),
+                        amount: "1000000000000".to_string(),
+                    }),
+                    mint_to_address: "cosmos2contract".to_string(),
+                }
+                .encode_to_vec()
+            ),
+        })
+    );
+
+    let config: Config =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.alliance_token_denom, denom);
+    assert_eq!(config.token_factory_backend, TokenFactoryBackend::Osmosis);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        ExecuteMsg::BurnAllianceTokens {
+            amount: Uint128::new(100),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Stargate {
+            type_url: "/osmosis.tokenfactory.v1beta1.MsgBurn".to_string(),
+            value: Binary::from(
+                MsgBurn {
+                    sender: "cosmos2contract".to_string(),
+                    amount: Some(Coin {
+                        denom: denom.to_string(),
+                        amount: "100".to_string(),
+                    }),
+                    burn_from_address: "cosmos2contract".to_string(),
+                }
+                .encode_to_vec()
+            ),
+        })]
+    );
+}
+
+#[test]
+fn test_malformed_create_denom_reply() {
+    let mut deps = mock_dependencies();
+    setup_osmosis_contract(deps.as_mut());
+
+    let err = reply(deps.as_mut(), mock_env(), create_denom_reply(None)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidCreateDenomReply("missing response data".to_string())
+    );
+
+    let err = reply(
+        deps.as_mut(),
+        mock_env(),
+        create_denom_reply(Some(Binary::from(vec![0xff, 0xff]))),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::InvalidCreateDenomReply(_)));
+
+    let err = reply(
+        deps.as_mut(),
+        mock_env(),
+        create_denom_reply(Some(Binary::default())),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidCreateDenomReply("empty denom".to_string())
+    );
+
+    let err = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 1,
+            result: SubMsgResult::Err("denom exists".to_string()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidCreateDenomReply("denom exists".to_string())
+    );
+}
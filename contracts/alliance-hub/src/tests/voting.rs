@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use alliance_protocol::alliance_protocol::{
+    AssetVotingWeight, ExecuteMsg, InfoResponse, QueryMsg, TotalPowerAtHeightResponse,
+    VotingConfigRes, VotingPowerAtHeightResponse,
+};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{coin, from_json, Addr, Decimal, Deps, DepsMut, Env, Uint128};
+use cw_asset_v3::AssetInfo;
+
+use crate::contract::execute;
+use crate::error::ContractError;
+use crate::query::query;
+use crate::tests::helpers::{setup_contract, whitelist_assets};
+
+fn env_at(height: u64) -> Env {
+    let mut env = mock_env();
+    env.block.height = height;
+    env
+}
+
+fn weights(weights: &[(&str, u64)]) -> Vec<AssetVotingWeight> {
+    weights
+        .iter()
+        .map(|(denom, percent)| AssetVotingWeight {
+            asset: AssetInfo::native(*denom),
+            weight: Decimal::percent(*percent),
+        })
+        .collect()
+}
+
+fn set_weights(deps: DepsMut, height: u64, asset_weights: Vec<AssetVotingWeight>) {
+    execute(
+        deps,
+        env_at(height),
+        mock_info("gov", &[]),
+        ExecuteMsg::UpdateVotingConfig {
+            dao: None,
+            asset_weights: Some(asset_weights),
+        },
+    )
+    .unwrap();
+}
+
+fn stake_at(deps: DepsMut, height: u64, user: &str, amount: u128, denom: &str) {
+    execute(
+        deps,
+        env_at(height),
+        mock_info(user, &[coin(amount, denom)]),
+        ExecuteMsg::Stake {
+            recipient: None,
+            origin: None,
+        },
+    )
+    .unwrap();
+}
+
+fn voting_power(deps: Deps, user: &str, height: u64) -> u128 {
+    let res: VotingPowerAtHeightResponse = from_json(
+        query(
+            deps,
+            mock_env(),
+            QueryMsg::VotingPowerAtHeight {
+                address: user.to_string(),
+                height: Some(height),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    res.power.u128()
+}
+
+fn total_power(deps: Deps, height: u64) -> u128 {
+    let res: TotalPowerAtHeightResponse = from_json(
+        query(
+            deps,
+            mock_env(),
+            QueryMsg::TotalPowerAtHeight {
+                height: Some(height),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    res.power.u128()
+}
+
+#[test]
+fn test_update_voting_config() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([(
+            "chain-1".to_string(),
+            vec![AssetInfo::native("aWHALE"), AssetInfo::native("bWHALE")],
+        )]),
+    );
+
+    let update = |dao: Option<&str>, asset_weights| ExecuteMsg::UpdateVotingConfig {
+        dao: dao.map(|dao| dao.to_string()),
+        asset_weights,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller", &[]),
+        update(Some("dao"), None),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        update(None, Some(weights(&[("aWHALE", 100), ("aWHALE", 50)]))),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidVotingWeights("duplicate asset native:aWHALE".to_string())
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        update(None, Some(weights(&[("cWHALE", 100)]))),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::AssetNotWhitelisted {});
+
+    // The DAO defaults to governance
+    let dao: Addr = from_json(query(deps.as_ref(), mock_env(), QueryMsg::Dao {}).unwrap()).unwrap();
+    assert_eq!(dao, Addr::unchecked("gov"));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        update(
+            Some("dao"),
+            Some(weights(&[("aWHALE", 100), ("bWHALE", 50)])),
+        ),
+    )
+    .unwrap();
+    let config: VotingConfigRes =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::VotingConfig {}).unwrap()).unwrap();
+    assert_eq!(
+        config,
+        VotingConfigRes {
+            dao: Addr::unchecked("dao"),
+            asset_weights: weights(&[("aWHALE", 100), ("bWHALE", 50)]),
+        }
+    );
+    let dao: Addr = from_json(query(deps.as_ref(), mock_env(), QueryMsg::Dao {}).unwrap()).unwrap();
+    assert_eq!(dao, Addr::unchecked("dao"));
+
+    let info: InfoResponse =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::Info {}).unwrap()).unwrap();
+    assert_eq!(info.info.contract, "crates.io:terra-alliance-protocol");
+}
+
+#[test]
+fn test_voting_power_at_height() {
+    let mut deps = mock_dependencies();
+    setup_contract(deps.as_mut());
+    whitelist_assets(
+        deps.as_mut(),
+        HashMap::from([(
+            "chain-1".to_string(),
+            vec![
+                AssetInfo::native("aWHALE"),
+                AssetInfo::native("bWHALE"),
+                AssetInfo::native("cWHALE"),
+            ],
+        )]),
+    );
+
+    set_weights(
+        deps.as_mut(),
+        100,
+        weights(&[("aWHALE", 100), ("bWHALE", 50)]),
+    );
+    stake_at(deps.as_mut(), 110, "user1", 1000, "aWHALE");
+    stake_at(deps.as_mut(), 110, "user1", 1001, "bWHALE");
+    stake_at(deps.as_mut(), 110, "user1", 1000, "cWHALE");
+    stake_at(deps.as_mut(), 120, "user2", 3, "bWHALE");
+
+    // Assets without a weight do not count and weighted amounts are rounded down
+    assert_eq!(voting_power(deps.as_ref(), "user1", 110), 0);
+    assert_eq!(voting_power(deps.as_ref(), "user1", 111), 1500);
+    assert_eq!(voting_power(deps.as_ref(), "user2", 121), 1);
+    assert_eq!(total_power(deps.as_ref(), 111), 1500);
+    assert_eq!(total_power(deps.as_ref(), 121), 1502);
+
+    // Past voting power keeps the weights of its height
+    set_weights(deps.as_mut(), 130, weights(&[("cWHALE", 200)]));
+    assert_eq!(voting_power(deps.as_ref(), "user1", 125), 1500);
+    assert_eq!(voting_power(deps.as_ref(), "user1", 131), 2000);
+    assert_eq!(total_power(deps.as_ref(), 131), 2000);
+
+    let res: VotingPowerAtHeightResponse = from_json(
+        query(
+            deps.as_ref(),
+            env_at(140),
+            QueryMsg::VotingPowerAtHeight {
+                address: "user1".to_string(),
+                height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        VotingPowerAtHeightResponse {
+            power: Uint128::new(2000),
+            height: 140,
+        }
+    );
+}
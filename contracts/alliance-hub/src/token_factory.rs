@@ -1,5 +1,13 @@
+use alliance_protocol::alliance_protocol::{AllianceTokenMetadata, TokenFactoryBackend};
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{CustomMsg, Uint128};
+use cosmwasm_std::{Binary, CosmosMsg, CustomMsg, Uint128};
+use terra_proto_rs::cosmos::bank::v1beta1::{
+    DenomUnit as ProtoDenomUnit, Metadata as ProtoMetadata,
+};
+use terra_proto_rs::cosmos::base::v1beta1::Coin;
+use terra_proto_rs::traits::Message;
+
+use crate::error::ContractError;
 
 #[cw_serde]
 pub enum CustomExecuteMsg {
@@ -25,6 +33,29 @@ pub struct Metadata {
     pub symbol: String,
 }
 
+impl Metadata {
+    pub fn new(denom: &str, metadata: AllianceTokenMetadata) -> Self {
+        let mut denom_units = vec![DenomUnit {
+            denom: denom.to_string(),
+            exponent: 0,
+            aliases: vec![],
+        }];
+        denom_units.extend(metadata.denom_units.into_iter().map(|unit| DenomUnit {
+            denom: unit.denom,
+            exponent: unit.exponent,
+            aliases: unit.aliases,
+        }));
+        Metadata {
+            description: metadata.description,
+            denom_units,
+            base: denom.to_string(),
+            display: metadata.display.unwrap_or_else(|| denom.to_string()),
+            name: metadata.name,
+            symbol: metadata.symbol,
+        }
+    }
+}
+
 #[cw_serde]
 pub enum TokenExecuteMsg {
     CreateDenom {
@@ -45,3 +76,221 @@ pub enum TokenExecuteMsg {
         burn_from_address: String,
     },
 }
+
+// Messages of the osmosis.tokenfactory.v1beta1 module, which are not part of terra-proto-rs
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgCreateDenom {
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    #[prost(string, tag = "2")]
+    pub subdenom: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgCreateDenomResponse {
+    #[prost(string, tag = "1")]
+    pub new_token_denom: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgMint {
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    #[prost(message, optional, tag = "2")]
+    pub amount: Option<Coin>,
+    #[prost(string, tag = "3")]
+    pub mint_to_address: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgBurn {
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    #[prost(message, optional, tag = "2")]
+    pub amount: Option<Coin>,
+    #[prost(string, tag = "3")]
+    pub burn_from_address: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgSetDenomMetadata {
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    #[prost(message, optional, tag = "2")]
+    pub metadata: Option<ProtoMetadata>,
+}
+
+impl From<Metadata> for ProtoMetadata {
+    fn from(metadata: Metadata) -> Self {
+        ProtoMetadata {
+            description: metadata.description,
+            denom_units: metadata
+                .denom_units
+                .into_iter()
+                .map(|unit| ProtoDenomUnit {
+                    denom: unit.denom,
+                    exponent: unit.exponent,
+                    aliases: unit.aliases,
+                })
+                .collect(),
+            base: metadata.base,
+            display: metadata.display,
+            name: metadata.name,
+            symbol: metadata.symbol,
+            uri: String::new(),
+            uri_hash: String::new(),
+        }
+    }
+}
+
+// Builds the token factory messages of the alliance token. `sender` is the hub itself,
+// which the Stargate messages have to name explicitly.
+pub trait TokenFactory {
+    fn create_denom(&self, sender: &str, subdenom: &str) -> CosmosMsg<CustomExecuteMsg>;
+
+    fn mint(
+        &self,
+        sender: &str,
+        denom: &str,
+        amount: Uint128,
+        mint_to_address: &str,
+    ) -> CosmosMsg<CustomExecuteMsg>;
+
+    fn burn(
+        &self,
+        sender: &str,
+        denom: &str,
+        amount: Uint128,
+        burn_from_address: &str,
+    ) -> CosmosMsg<CustomExecuteMsg>;
+
+    fn set_metadata(
+        &self,
+        sender: &str,
+        denom: &str,
+        metadata: Metadata,
+    ) -> CosmosMsg<CustomExecuteMsg>;
+
+    // Reads the new denom from the data of the create denom reply
+    fn parse_create_denom_response(&self, data: Option<Binary>) -> Result<String, ContractError> {
+        let data = data.ok_or_else(|| {
+            ContractError::InvalidCreateDenomReply("missing response data".to_string())
+        })?;
+        let response = MsgCreateDenomResponse::decode(data.as_slice())
+            .map_err(|err| ContractError::InvalidCreateDenomReply(err.to_string()))?;
+        if response.new_token_denom.is_empty() {
+            return Err(ContractError::InvalidCreateDenomReply(
+                "empty denom".to_string(),
+            ));
+        }
+        Ok(response.new_token_denom)
+    }
+}
+
+impl TokenFactory for TokenFactoryBackend {
+    fn create_denom(&self, sender: &str, subdenom: &str) -> CosmosMsg<CustomExecuteMsg> {
+        match self {
+            TokenFactoryBackend::Terra => {
+                CosmosMsg::Custom(CustomExecuteMsg::Token(TokenExecuteMsg::CreateDenom {
+                    subdenom: subdenom.to_string(),
+                }))
+            }
+            TokenFactoryBackend::Osmosis => stargate_msg(
+                "/osmosis.tokenfactory.v1beta1.MsgCreateDenom",
+                MsgCreateDenom {
+                    sender: sender.to_string(),
+                    subdenom: subdenom.to_string(),
+                },
+            ),
+        }
+    }
+
+    fn mint(
+        &self,
+        sender: &str,
+        denom: &str,
+        amount: Uint128,
+        mint_to_address: &str,
+    ) -> CosmosMsg<CustomExecuteMsg> {
+        match self {
+            TokenFactoryBackend::Terra => {
+                CosmosMsg::Custom(CustomExecuteMsg::Token(TokenExecuteMsg::MintTokens {
+                    denom: denom.to_string(),
+                    amount,
+                    mint_to_address: mint_to_address.to_string(),
+                }))
+            }
+            TokenFactoryBackend::Osmosis => stargate_msg(
+                "/osmosis.tokenfactory.v1beta1.MsgMint",
+                MsgMint {
+                    sender: sender.to_string(),
+                    amount: Some(coin(denom, amount)),
+                    mint_to_address: mint_to_address.to_string(),
+                },
+            ),
+        }
+    }
+
+    fn burn(
+        &self,
+        sender: &str,
+        denom: &str,
+        amount: Uint128,
+        burn_from_address: &str,
+    ) -> CosmosMsg<CustomExecuteMsg> {
+        match self {
+            TokenFactoryBackend::Terra => {
+                CosmosMsg::Custom(CustomExecuteMsg::Token(TokenExecuteMsg::BurnTokens {
+                    denom: denom.to_string(),
+                    amount,
+                    burn_from_address: burn_from_address.to_string(),
+                }))
+            }
+            TokenFactoryBackend::Osmosis => stargate_msg(
+                "/osmosis.tokenfactory.v1beta1.MsgBurn",
+                MsgBurn {
+                    sender: sender.to_string(),
+                    amount: Some(coin(denom, amount)),
+                    burn_from_address: burn_from_address.to_string(),
+                },
+            ),
+        }
+    }
+
+    fn set_metadata(
+        &self,
+        sender: &str,
+        denom: &str,
+        metadata: Metadata,
+    ) -> CosmosMsg<CustomExecuteMsg> {
+        match self {
+            TokenFactoryBackend::Terra => {
+                CosmosMsg::Custom(CustomExecuteMsg::Token(TokenExecuteMsg::SetMetadata {
+                    denom: denom.to_string(),
+                    metadata,
+                }))
+            }
+            TokenFactoryBackend::Osmosis => stargate_msg(
+                "/osmosis.tokenfactory.v1beta1.MsgSetDenomMetadata",
+                MsgSetDenomMetadata {
+                    sender: sender.to_string(),
+                    metadata: Some(metadata.into()),
+                },
+            ),
+        }
+    }
+}
+
+pub(crate) fn coin(denom: &str, amount: Uint128) -> Coin {
+    Coin {
+        denom: denom.to_string(),
+        amount: amount.to_string(),
+    }
+}
+
+pub(crate) fn stargate_msg(type_url: &str, msg: impl Message) -> CosmosMsg<CustomExecuteMsg> {
+    CosmosMsg::Stargate {
+        type_url: type_url.to_string(),
+        value: Binary::from(msg.encode_to_vec()),
+    }
+}
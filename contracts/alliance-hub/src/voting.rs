@@ -0,0 +1,62 @@
+use alliance_protocol::alliance_protocol::AssetVotingWeight;
+use cosmwasm_std::{Addr, StdResult, Storage, Uint128};
+use std::collections::HashSet;
+
+use crate::error::ContractError;
+use crate::state::{BALANCES, CONFIG, TOTAL_BALANCES, VOTING_DAO, VOTING_WEIGHTS, WHITELIST};
+
+pub(crate) fn validate_asset_weights(
+    storage: &dyn Storage,
+    asset_weights: &[AssetVotingWeight],
+) -> Result<(), ContractError> {
+    let mut assets = HashSet::new();
+    for asset_weight in asset_weights {
+        if !assets.insert(asset_weight.asset.to_string()) {
+            return Err(ContractError::InvalidVotingWeights(format!(
+                "duplicate asset {}",
+                asset_weight.asset
+            )));
+        }
+        if !WHITELIST.has(storage, &asset_weight.asset) {
+            return Err(ContractError::AssetNotWhitelisted {});
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn dao(storage: &dyn Storage) -> StdResult<Addr> {
+    match VOTING_DAO.may_load(storage)? {
+        Some(dao) => Ok(dao),
+        None => Ok(CONFIG.load(storage)?.governance),
+    }
+}
+
+// Weighted amounts are rounded down per asset, so the total power is never less than
+// the sum of the voting power of the stakers.
+pub(crate) fn voting_power(storage: &dyn Storage, user: &Addr, height: u64) -> StdResult<Uint128> {
+    let asset_weights = VOTING_WEIGHTS
+        .may_load_at_height(storage, height)?
+        .unwrap_or_default();
+    asset_weights
+        .iter()
+        .try_fold(Uint128::zero(), |power, asset_weight| {
+            let balance = BALANCES
+                .may_load_at_height(storage, (user.clone(), &asset_weight.asset), height)?
+                .unwrap_or_default();
+            Ok(power + balance * asset_weight.weight)
+        })
+}
+
+pub(crate) fn total_power(storage: &dyn Storage, height: u64) -> StdResult<Uint128> {
+    let asset_weights = VOTING_WEIGHTS
+        .may_load_at_height(storage, height)?
+        .unwrap_or_default();
+    asset_weights
+        .iter()
+        .try_fold(Uint128::zero(), |power, asset_weight| {
+            let balance = TOTAL_BALANCES
+                .may_load_at_height(storage, &asset_weight.asset, height)?
+                .unwrap_or_default();
+            Ok(power + balance * asset_weight.weight)
+        })
+}
@@ -3,18 +3,20 @@ use std::env;
 
 use alliance_protocol::alliance_oracle_types::{
     AssetStaked, ChainId, ChainInfo, ChainsInfo, Config, EmissionsDistribution, ExecuteMsg, Expire,
-    InstantiateMsg, MigrateMsg, QueryMsg,
+    FeedersResponse, InstantiateMsg, MigrateMsg, QueryMsg, RoundStatusResponse,
 };
 use alliance_protocol::signed_decimal::{Sign, SignedDecimal};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    to_json_binary, Addr, Binary, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Response,
+    StdError, StdResult,
 };
 use cw2::set_contract_version;
 
 use crate::error::ContractError;
-use crate::state::{CHAINS_INFO, CONFIG, LUNA_INFO};
+use crate::feeders;
+use crate::state::{CHAINS_INFO, CONFIG, FEEDERS, LUNA_INFO, QUORUM, ROUND};
 use crate::utils;
 
 // version info for migration info
@@ -22,20 +24,40 @@ const CONTRACT_NAME: &str = "crates.io:terra-alliance-oracle";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    // Contracts deployed before feeders existed keep the controller as their only feeder
+    if QUORUM.may_load(deps.storage)?.is_none() {
+        let config = CONFIG.load(deps.storage)?;
+        FEEDERS.save(deps.storage, &config.controller_addr, &Empty {})?;
+        QUORUM.save(deps.storage, &1)?;
+        feeders::start_round(deps.storage, 1, env.block.time)?;
+    }
     Ok(Response::default())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _: Env,
+    env: Env,
     _: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     let controller_addr = deps.api.addr_validate(&msg.controller_addr)?;
 
+    // Without feeders the controller is the only one, otherwise a majority is required
+    let feeders = match msg.feeders {
+        Some(feeders) => feeders
+            .iter()
+            .map(|feeder| deps.api.addr_validate(feeder))
+            .collect::<StdResult<Vec<Addr>>>()?,
+        None => vec![controller_addr.clone()],
+    };
+    let quorum = msg.quorum.unwrap_or(feeders.len() as u32 / 2 + 1);
+    QUORUM.save(deps.storage, &quorum)?;
+    feeders::update_feeders(deps.storage, feeders, vec![], None)?;
+    feeders::start_round(deps.storage, 1, env.block.time)?;
+
     CONFIG.save(
         deps.storage,
         &Config {
@@ -47,7 +69,8 @@ pub fn instantiate(
     Ok(Response::new()
         .add_attribute("action", "instantiate")
         .add_attribute("data_expiry_seconds", msg.data_expiry_seconds.to_string())
-        .add_attribute("controller_addr", msg.controller_addr))
+        .add_attribute("controller_addr", msg.controller_addr)
+        .add_attribute("quorum", quorum.to_string()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -61,6 +84,11 @@ pub fn execute(
         ExecuteMsg::UpdateChainsInfo { chains_info } => {
             update_chains_info(deps, env, info, chains_info)
         }
+        ExecuteMsg::UpdateFeeders {
+            add,
+            remove,
+            quorum,
+        } => update_feeders(deps, info, add, remove, quorum),
     }
 }
 
@@ -71,7 +99,23 @@ fn update_chains_info(
     chains_info: ChainsInfo,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    utils::authorize_execution(config, info.sender)?;
+    if !FEEDERS.has(deps.storage, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let (round, chains_info) = feeders::submit(
+        deps.storage,
+        &info.sender,
+        chains_info,
+        env.block.time,
+        config.data_expiry_seconds,
+    )?;
+    let response = Response::new()
+        .add_attribute("action", "update_chains_info")
+        .add_attribute("round", round.to_string());
+    let chains_info = match chains_info {
+        Some(chains_info) => chains_info,
+        None => return Ok(response.add_attribute("finalized", "false")),
+    };
     let mut parsed_chains_info: Vec<ChainInfo> = vec![];
 
     for chain_info in &chains_info.protocols_info {
@@ -84,7 +128,32 @@ fn update_chains_info(
     LUNA_INFO.save(deps.storage, &luna_info)?;
     CHAINS_INFO.save(deps.storage, &parsed_chains_info)?;
 
-    Ok(Response::new().add_attribute("action", "update_chains_info"))
+    Ok(response.add_attribute("finalized", "true"))
+}
+
+fn update_feeders(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+    quorum: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    utils::authorize_execution(config, info.sender)?;
+    let validate = |feeders: &Vec<String>| {
+        feeders
+            .iter()
+            .map(|feeder| deps.api.addr_validate(feeder))
+            .collect::<StdResult<Vec<Addr>>>()
+    };
+    let (added, removed) = (validate(&add)?, validate(&remove)?);
+    feeders::update_feeders(deps.storage, added, removed, quorum)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_feeders")
+        .add_attribute("added", add.join(","))
+        .add_attribute("removed", remove.join(","))
+        .add_attribute("quorum", QUORUM.load(deps.storage)?.to_string()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -98,6 +167,46 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::QueryEmissionsDistributions(query) => {
             get_emissions_distribution_info(deps, env, query)?
         }
+        QueryMsg::QueryFeeders {} => get_feeders(deps)?,
+        QueryMsg::QueryFeederSubmissions { round } => get_feeder_submissions(deps, round)?,
+        QueryMsg::QueryRoundStatus {} => get_round_status(deps)?,
+    })
+}
+
+pub fn get_feeders(deps: Deps) -> StdResult<Binary> {
+    to_json_binary(&FeedersResponse {
+        feeders: feeders::feeders(deps.storage)?,
+        quorum: QUORUM.load(deps.storage)?,
+    })
+}
+
+pub fn get_feeder_submissions(deps: Deps, round: Option<u64>) -> StdResult<Binary> {
+    let round = match round {
+        Some(round) => round,
+        None => ROUND.load(deps.storage)?.id,
+    };
+    to_json_binary(&feeders::submissions(deps.storage, round)?)
+}
+
+pub fn get_round_status(deps: Deps) -> StdResult<Binary> {
+    let round = ROUND.load(deps.storage)?;
+    let cfg = CONFIG.load(deps.storage)?;
+    let submitted: Vec<Addr> = feeders::submissions(deps.storage, round.id)?
+        .into_iter()
+        .map(|submission| submission.feeder)
+        .collect();
+    let pending = feeders::feeders(deps.storage)?
+        .into_iter()
+        .filter(|feeder| !submitted.contains(feeder))
+        .collect();
+
+    to_json_binary(&RoundStatusResponse {
+        round: round.id,
+        started_at: round.started_at,
+        expires_at: round.started_at.plus_seconds(cfg.data_expiry_seconds),
+        quorum: QUORUM.load(deps.storage)?,
+        submitted,
+        pending,
     })
 }
 
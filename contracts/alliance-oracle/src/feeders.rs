@@ -0,0 +1,221 @@
+use alliance_protocol::alliance_oracle_types::{
+    BaseAlliance, ChainInfoMsg, ChainsInfo, FeederSubmission, LunaAlliance, NativeToken, Round,
+};
+use cosmwasm_std::{Addr, Decimal, Empty, Order, StdResult, Storage, Timestamp};
+
+use crate::error::ContractError;
+use crate::state::{FEEDERS, QUORUM, ROUND, SUBMISSIONS};
+
+pub fn feeders(storage: &dyn Storage) -> StdResult<Vec<Addr>> {
+    FEEDERS
+        .keys(storage, None, None, Order::Ascending)
+        .collect()
+}
+
+pub fn update_feeders(
+    storage: &mut dyn Storage,
+    add: Vec<Addr>,
+    remove: Vec<Addr>,
+    quorum: Option<u32>,
+) -> Result<(), ContractError> {
+    let mut updated = feeders(storage)?;
+    updated.extend(add.iter().cloned());
+    updated.retain(|feeder| !remove.contains(feeder));
+    updated.sort();
+    updated.dedup();
+
+    let quorum = match quorum {
+        Some(quorum) => quorum,
+        None => QUORUM.load(storage)?,
+    };
+    let count = updated.len() as u32;
+    if quorum == 0 || quorum > count {
+        return Err(ContractError::InvalidQuorum(count));
+    }
+
+    for feeder in add {
+        FEEDERS.save(storage, &feeder, &Empty {})?;
+    }
+    for feeder in remove {
+        FEEDERS.remove(storage, &feeder);
+    }
+    QUORUM.save(storage, &quorum)?;
+    Ok(())
+}
+
+// Starts round `id` and drops the submissions of the rounds before the previous one
+pub fn start_round(storage: &mut dyn Storage, id: u64, now: Timestamp) -> StdResult<Round> {
+    let round = Round {
+        id,
+        started_at: now,
+    };
+    ROUND.save(storage, &round)?;
+    if let Some(pruned) = id.checked_sub(2) {
+        let feeders = SUBMISSIONS
+            .prefix(pruned)
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<Addr>>>()?;
+        for feeder in feeders {
+            SUBMISSIONS.remove(storage, (pruned, &feeder));
+        }
+    }
+    Ok(round)
+}
+
+// Submissions of `round` by the current feeders
+pub fn submissions(storage: &dyn Storage, round: u64) -> StdResult<Vec<FeederSubmission>> {
+    SUBMISSIONS
+        .prefix(round)
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((feeder, _)) => FEEDERS.has(storage, feeder),
+            Err(_) => true,
+        })
+        .map(|item| Ok(item?.1))
+        .collect()
+}
+
+// Records the submission of `feeder` for the current round and returns the round along
+// with the aggregated data once the quorum is reached
+pub fn submit(
+    storage: &mut dyn Storage,
+    feeder: &Addr,
+    chains_info: ChainsInfo,
+    now: Timestamp,
+    data_expiry_seconds: u64,
+) -> StdResult<(u64, Option<ChainsInfo>)> {
+    let mut round = ROUND.load(storage)?;
+    if round.started_at.plus_seconds(data_expiry_seconds) < now {
+        round = start_round(storage, round.id + 1, now)?;
+    }
+    SUBMISSIONS.save(
+        storage,
+        (round.id, feeder),
+        &FeederSubmission {
+            feeder: feeder.clone(),
+            round: round.id,
+            chains_info,
+            submitted_at: now,
+        },
+    )?;
+
+    let quorum = QUORUM.load(storage)? as usize;
+    let submissions = submissions(storage, round.id)?;
+    if submissions.len() < quorum {
+        return Ok((round.id, None));
+    }
+    let submitted: Vec<&ChainsInfo> = submissions.iter().map(|s| &s.chains_info).collect();
+    start_round(storage, round.id + 1, now)?;
+    Ok((round.id, Some(aggregate(&submitted, quorum))))
+}
+
+// Median of every figure. Chains and alliances reported by fewer than `quorum` feeders
+// are left out. Feeders disagreeing on the native denom of a chain are not merged, only
+// the reports of the most reported denom count towards the quorum.
+fn aggregate(submissions: &[&ChainsInfo], quorum: usize) -> ChainsInfo {
+    let chains = group_by(
+        submissions.iter().flat_map(|s| s.protocols_info.iter()),
+        |chain| chain.chain_id.clone(),
+    );
+    let protocols_info = chains
+        .into_iter()
+        .filter_map(|(chain_id, reports)| {
+            let reports = group_by(reports.into_iter(), |chain| {
+                chain.native_token.denom.clone()
+            })
+            .into_iter()
+            .map(|(_, reports)| reports)
+            .reduce(|most, reports| {
+                if reports.len() > most.len() {
+                    reports
+                } else {
+                    most
+                }
+            })?;
+            (reports.len() >= quorum).then(|| aggregate_chain(chain_id, &reports, quorum))
+        })
+        .collect();
+
+    ChainsInfo {
+        luna_price: median(submissions.iter().map(|s| s.luna_price).collect()),
+        protocols_info,
+    }
+}
+
+fn aggregate_chain(chain_id: String, reports: &[&ChainInfoMsg], quorum: usize) -> ChainInfoMsg {
+    let luna_alliances = group_by(
+        reports.iter().flat_map(|r| r.luna_alliances.iter()),
+        |alliance| alliance.ibc_denom.clone(),
+    )
+    .into_iter()
+    .filter(|(_, alliances)| alliances.len() >= quorum)
+    .map(|(ibc_denom, alliances)| LunaAlliance {
+        ibc_denom,
+        normalized_reward_weight: median(
+            alliances
+                .iter()
+                .map(|a| a.normalized_reward_weight)
+                .collect(),
+        ),
+        annual_take_rate: median(alliances.iter().map(|a| a.annual_take_rate).collect()),
+        total_lsd_staked: median(alliances.iter().map(|a| a.total_lsd_staked).collect()),
+        rebase_factor: median(alliances.iter().map(|a| a.rebase_factor).collect()),
+    })
+    .collect();
+    let chain_alliances_on_phoenix = group_by(
+        reports
+            .iter()
+            .flat_map(|r| r.chain_alliances_on_phoenix.iter()),
+        |alliance| alliance.ibc_denom.clone(),
+    )
+    .into_iter()
+    .filter(|(_, alliances)| alliances.len() >= quorum)
+    .map(|(ibc_denom, alliances)| BaseAlliance {
+        ibc_denom,
+        rebase_factor: median(alliances.iter().map(|a| a.rebase_factor).collect()),
+    })
+    .collect();
+
+    ChainInfoMsg {
+        chain_id,
+        native_token: NativeToken {
+            denom: reports[0].native_token.denom.clone(),
+            token_price: median(reports.iter().map(|r| r.native_token.token_price).collect()),
+            annual_provisions: median(
+                reports
+                    .iter()
+                    .map(|r| r.native_token.annual_provisions)
+                    .collect(),
+            ),
+        },
+        luna_alliances,
+        chain_alliances_on_phoenix,
+    }
+}
+
+// Groups the items by key, keeping the order in which the keys are first seen
+fn group_by<'a, T>(
+    items: impl Iterator<Item = &'a T>,
+    key: impl Fn(&T) -> String,
+) -> Vec<(String, Vec<&'a T>)> {
+    let mut groups: Vec<(String, Vec<&'a T>)> = vec![];
+    for item in items {
+        let item_key = key(item);
+        match groups.iter_mut().find(|(k, _)| *k == item_key) {
+            Some((_, group)) => group.push(item),
+            None => groups.push((item_key, vec![item])),
+        }
+    }
+    groups
+}
+
+// The mean of the two middle values for an even number of values
+fn median(mut values: Vec<Decimal>) -> Decimal {
+    values.sort();
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) * Decimal::percent(50)
+    } else {
+        values[mid]
+    }
+}
@@ -1,5 +1,6 @@
 pub mod contract;
 mod error;
+mod feeders;
 pub mod state;
 #[cfg(test)]
 pub mod tests;
@@ -1,6 +1,15 @@
-use alliance_protocol::alliance_oracle_types::{ChainInfo, Config, LunaInfo};
-use cw_storage_plus_016::Item;
+use alliance_protocol::alliance_oracle_types::{
+    ChainInfo, Config, FeederSubmission, LunaInfo, Round,
+};
+use cosmwasm_std::{Addr, Empty};
+use cw_storage_plus_016::{Item, Map};
 
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const CHAINS_INFO: Item<Vec<ChainInfo>> = Item::new("chains_info");
 pub const LUNA_INFO: Item<LunaInfo> = Item::new("luna_info");
+
+pub const FEEDERS: Map<&Addr, Empty> = Map::new("feeders");
+pub const QUORUM: Item<u32> = Item::new("quorum");
+pub const ROUND: Item<Round> = Item::new("round");
+// Submissions keyed by (round, feeder), kept for the current and the previous round
+pub const SUBMISSIONS: Map<(u64, &Addr), FeederSubmission> = Map::new("submissions");
@@ -0,0 +1,312 @@
+use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
+use alliance_protocol::alliance_oracle_types::{
+    BaseAlliance, ChainInfo, ChainInfoMsg, ChainsInfo, ExecuteMsg, FeederSubmission,
+    FeedersResponse, InstantiateMsg, LunaAlliance, LunaInfo, NativeToken, QueryMsg,
+    RoundStatusResponse,
+};
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{from_json, Addr, Decimal, Empty, Env, OwnedDeps};
+
+fn setup_feeders() -> OwnedDeps<MockStorage, MockApi, MockQuerier, Empty> {
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        data_expiry_seconds: 60,
+        controller_addr: "controller_addr".to_string(),
+        feeders: Some(vec![
+            "feeder1".to_string(),
+            "feeder2".to_string(),
+            "feeder3".to_string(),
+        ]),
+        quorum: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+    deps
+}
+
+fn chains_info(price: u64, alliances: Vec<&str>) -> ChainsInfo {
+    ChainsInfo {
+        luna_price: Decimal::percent(price),
+        protocols_info: vec![ChainInfoMsg {
+            chain_id: "chain-1".to_string(),
+            native_token: NativeToken {
+                denom: "udenom".to_string(),
+                token_price: Decimal::percent(price * 2),
+                annual_provisions: Decimal::percent(price * 3),
+            },
+            luna_alliances: alliances
+                .iter()
+                .map(|ibc_denom| LunaAlliance {
+                    ibc_denom: ibc_denom.to_string(),
+                    normalized_reward_weight: Decimal::percent(10),
+                    annual_take_rate: Decimal::percent(1),
+                    total_lsd_staked: Decimal::percent(price * 100),
+                    rebase_factor: Decimal::percent(100 + price),
+                })
+                .collect(),
+            chain_alliances_on_phoenix: vec![BaseAlliance {
+                ibc_denom: "ibc/phoenix".to_string(),
+                rebase_factor: Decimal::percent(100 + price),
+            }],
+        }],
+    }
+}
+
+fn submit(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>,
+    env: Env,
+    feeder: &str,
+    chains_info: ChainsInfo,
+) -> Result<bool, ContractError> {
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(feeder, &[]),
+        ExecuteMsg::UpdateChainsInfo { chains_info },
+    )?;
+    Ok(res.attributes[2].value == "true")
+}
+
+fn round_status(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>) -> RoundStatusResponse {
+    from_json(query(deps.as_ref(), mock_env(), QueryMsg::QueryRoundStatus {}).unwrap()).unwrap()
+}
+
+#[test]
+fn test_quorum_saves_medians() {
+    let mut deps = setup_feeders();
+    let res: FeedersResponse =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::QueryFeeders {}).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        FeedersResponse {
+            feeders: vec![
+                Addr::unchecked("feeder1"),
+                Addr::unchecked("feeder2"),
+                Addr::unchecked("feeder3"),
+            ],
+            quorum: 2,
+        }
+    );
+
+    // Nothing is saved before the quorum is reached
+    let finalized = submit(
+        &mut deps,
+        mock_env(),
+        "feeder1",
+        chains_info(10, vec!["ibc/a", "ibc/b"]),
+    )
+    .unwrap();
+    assert!(!finalized);
+    query(deps.as_ref(), mock_env(), QueryMsg::QueryLunaInfo {}).unwrap_err();
+    let status = round_status(&deps);
+    assert_eq!(status.round, 1);
+    assert_eq!(status.submitted, vec![Addr::unchecked("feeder1")]);
+    assert_eq!(
+        status.pending,
+        vec![Addr::unchecked("feeder2"), Addr::unchecked("feeder3")]
+    );
+
+    let finalized = submit(
+        &mut deps,
+        mock_env(),
+        "feeder2",
+        chains_info(20, vec!["ibc/a"]),
+    )
+    .unwrap();
+    assert!(finalized);
+
+    let luna_info: LunaInfo =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::QueryLunaInfo {}).unwrap()).unwrap();
+    assert_eq!(luna_info.luna_price, Decimal::percent(15));
+    let chains: Vec<ChainInfo> =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::QueryChainsInfo {}).unwrap()).unwrap();
+    assert_eq!(chains.len(), 1);
+    assert_eq!(chains[0].native_token.token_price, Decimal::percent(30));
+    assert_eq!(
+        chains[0].native_token.annual_provisions,
+        Decimal::percent(45)
+    );
+    // Only one feeder reported ibc/b
+    assert_eq!(chains[0].luna_alliances.len(), 1);
+    assert_eq!(chains[0].luna_alliances[0].ibc_denom, "ibc/a");
+    assert_eq!(
+        chains[0].luna_alliances[0].rebase_factor,
+        Decimal::percent(115)
+    );
+    assert_eq!(
+        chains[0].chain_alliances_on_phoenix[0].rebase_factor,
+        Decimal::percent(115)
+    );
+
+    // The next round starts and the previous submissions can still be queried
+    let status = round_status(&deps);
+    assert_eq!(status.round, 2);
+    assert!(status.submitted.is_empty());
+    let submissions: Vec<FeederSubmission> = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::QueryFeederSubmissions { round: Some(1) },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(submissions.len(), 2);
+    assert_eq!(submissions[1].feeder, Addr::unchecked("feeder2"));
+}
+
+#[test]
+fn test_median_of_odd_submissions() {
+    let mut deps = setup_feeders();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller_addr", &[]),
+        ExecuteMsg::UpdateFeeders {
+            add: vec![],
+            remove: vec![],
+            quorum: Some(3),
+        },
+    )
+    .unwrap();
+
+    submit(&mut deps, mock_env(), "feeder1", chains_info(10, vec![])).unwrap();
+    submit(&mut deps, mock_env(), "feeder2", chains_info(1000, vec![])).unwrap();
+    // A resubmission replaces the previous one of the feeder
+    submit(&mut deps, mock_env(), "feeder2", chains_info(30, vec![])).unwrap();
+    assert_eq!(round_status(&deps).submitted.len(), 2);
+    submit(&mut deps, mock_env(), "feeder3", chains_info(20, vec![])).unwrap();
+
+    let luna_info: LunaInfo =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::QueryLunaInfo {}).unwrap()).unwrap();
+    assert_eq!(luna_info.luna_price, Decimal::percent(20));
+}
+
+#[test]
+fn test_unauthorized_feeder() {
+    let mut deps = setup_feeders();
+    let err = submit(
+        &mut deps,
+        mock_env(),
+        "controller_addr",
+        chains_info(10, vec![]),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn test_update_feeders() {
+    let mut deps = setup_feeders();
+    let msg = ExecuteMsg::UpdateFeeders {
+        add: vec!["feeder4".to_string()],
+        remove: vec!["feeder1".to_string(), "feeder2".to_string()],
+        quorum: None,
+    };
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("feeder1", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller_addr", &[]),
+        msg,
+    )
+    .unwrap();
+    let res: FeedersResponse =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::QueryFeeders {}).unwrap()).unwrap();
+    assert_eq!(
+        res.feeders,
+        vec![Addr::unchecked("feeder3"), Addr::unchecked("feeder4")]
+    );
+    assert_eq!(res.quorum, 2);
+
+    // The quorum cannot exceed the number of feeders
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller_addr", &[]),
+        ExecuteMsg::UpdateFeeders {
+            add: vec![],
+            remove: vec!["feeder4".to_string()],
+            quorum: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidQuorum(1));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("controller_addr", &[]),
+        ExecuteMsg::UpdateFeeders {
+            add: vec![],
+            remove: vec![],
+            quorum: Some(0),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidQuorum(2));
+
+    // Removed feeders can no longer submit
+    let err = submit(&mut deps, mock_env(), "feeder1", chains_info(10, vec![])).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn test_expired_round_restarts() {
+    let mut deps = setup_feeders();
+    submit(&mut deps, mock_env(), "feeder1", chains_info(10, vec![])).unwrap();
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(61);
+    let finalized = submit(&mut deps, env.clone(), "feeder2", chains_info(20, vec![])).unwrap();
+    assert!(!finalized);
+    let status = round_status(&deps);
+    assert_eq!(status.round, 2);
+    assert_eq!(status.started_at, env.block.time);
+    assert_eq!(status.expires_at, env.block.time.plus_seconds(60));
+    assert_eq!(status.submitted, vec![Addr::unchecked("feeder2")]);
+
+    assert!(submit(&mut deps, env.clone(), "feeder3", chains_info(30, vec![])).unwrap());
+    let luna_info: LunaInfo =
+        from_json(query(deps.as_ref(), env, QueryMsg::QueryLunaInfo {}).unwrap()).unwrap();
+    assert_eq!(luna_info.luna_price, Decimal::percent(25));
+}
+
+#[test]
+fn test_invalid_instantiate_quorum() {
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        data_expiry_seconds: 60,
+        controller_addr: "controller_addr".to_string(),
+        feeders: Some(vec!["feeder1".to_string()]),
+        quorum: Some(2),
+    };
+    let err = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidQuorum(1));
+}
+
+#[test]
+fn test_conflicting_denoms_are_not_merged() {
+    let mut deps = setup_feeders();
+    submit(&mut deps, mock_env(), "feeder1", chains_info(10, vec![])).unwrap();
+    let mut conflicting = chains_info(20, vec![]);
+    conflicting.protocols_info[0].native_token.denom = "uother".to_string();
+    assert!(submit(&mut deps, mock_env(), "feeder2", conflicting).unwrap());
+
+    let luna_info: LunaInfo =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::QueryLunaInfo {}).unwrap()).unwrap();
+    assert_eq!(luna_info.luna_price, Decimal::percent(15));
+    let chains: Vec<ChainInfo> =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::QueryChainsInfo {}).unwrap()).unwrap();
+    assert!(chains.is_empty());
+}
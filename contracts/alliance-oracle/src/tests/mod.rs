@@ -13,6 +13,7 @@ use cosmwasm_std::{
 use std::collections::HashMap;
 use std::str::FromStr;
 
+mod feeders;
 pub mod test_utils;
 
 #[test]
@@ -51,9 +52,11 @@ fn test_update_oracle_data() {
     // Create the controller_addr sender to successfully send the transaction to the contract
     let info = mock_info("controller_addr", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_eq!(1, res.attributes.len());
+    assert_eq!(3, res.attributes.len());
     assert_eq!("action", res.attributes[0].key);
     assert_eq!("update_chains_info", res.attributes[0].value);
+    assert_eq!("finalized", res.attributes[2].key);
+    assert_eq!("true", res.attributes[2].value);
 
     // Query the chains info to validate the data was stored correctly in the contract
     let res = query(deps.as_ref(), mock_env(), QueryMsg::QueryChainsInfo {}).unwrap();
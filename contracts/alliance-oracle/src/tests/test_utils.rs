@@ -12,6 +12,8 @@ pub fn setup_contract() -> OwnedDeps<MockStorage, MockApi, MockQuerier, Empty> {
     let msg = InstantiateMsg {
         data_expiry_seconds: 60,
         controller_addr: "controller_addr".to_string(),
+        feeders: None,
+        quorum: None,
     };
     let info = mock_info("creator", &[]);
     let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -0,0 +1,203 @@
+use anyhow::bail;
+use cosmwasm_std::{
+    coins, Addr, Api, BankMsg, Binary, BlockInfo, Coin, CustomQuery, Storage, Uint128,
+};
+use cw_multi_test::error::AnyResult;
+use cw_multi_test::{AppResponse, BankSudo, CosmosRouter, Stargate, SudoMsg};
+use cw_storage_plus_120::Map;
+use prost::Message;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use std::fmt::Debug;
+use terra_proto_rs::alliance::alliance::{
+    MsgClaimDelegationRewards, MsgDelegate, MsgRedelegate, MsgUndelegate,
+};
+
+use crate::token_factory::{self, ensure_signer, proto_coin};
+
+// Account holding the delegated tokens
+pub const ALLIANCE_MODULE_ACCOUNT: &str = "alliance_module";
+
+// Delegated amount and unclaimed rewards per (delegator, validator, denom)
+const DELEGATIONS: Map<(&Addr, &str, &str), Uint128> = Map::new("alliance_stand_in__delegations");
+const REWARDS: Map<(&Addr, &str, &str), Vec<Coin>> = Map::new("alliance_stand_in__rewards");
+
+// Stand-in for the alliance module and the osmosis token factory, which are both reached
+// through Stargate messages. Delegations take effect immediately, undelegated tokens are
+// returned without an unbonding period and rewards accrue only through `accrue_rewards`.
+#[derive(Default)]
+pub struct AllianceModule;
+
+impl Stargate for AllianceModule {
+    fn execute<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        sender: Addr,
+        type_url: String,
+        value: Binary,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        match type_url.as_str() {
+            "/alliance.alliance.MsgDelegate" => {
+                let msg = MsgDelegate::decode(value.as_slice())?;
+                ensure_signer(&sender, &msg.delegator_address)?;
+                let (denom, amount) = proto_coin(msg.amount)?;
+                router.execute(
+                    api,
+                    storage,
+                    block,
+                    sender.clone(),
+                    BankMsg::Send {
+                        to_address: ALLIANCE_MODULE_ACCOUNT.to_string(),
+                        amount: coins(amount.u128(), &denom),
+                    }
+                    .into(),
+                )?;
+                add_delegation(storage, &sender, &msg.validator_address, &denom, amount)?;
+                Ok(AppResponse::default())
+            }
+            "/alliance.alliance.MsgUndelegate" => {
+                let msg = MsgUndelegate::decode(value.as_slice())?;
+                ensure_signer(&sender, &msg.delegator_address)?;
+                let (denom, amount) = proto_coin(msg.amount)?;
+                remove_delegation(storage, &sender, &msg.validator_address, &denom, amount)?;
+                router.execute(
+                    api,
+                    storage,
+                    block,
+                    Addr::unchecked(ALLIANCE_MODULE_ACCOUNT),
+                    BankMsg::Send {
+                        to_address: sender.to_string(),
+                        amount: coins(amount.u128(), &denom),
+                    }
+                    .into(),
+                )
+            }
+            "/alliance.alliance.MsgRedelegate" => {
+                let msg = MsgRedelegate::decode(value.as_slice())?;
+                ensure_signer(&sender, &msg.delegator_address)?;
+                let (denom, amount) = proto_coin(msg.amount)?;
+                remove_delegation(storage, &sender, &msg.validator_src_address, &denom, amount)?;
+                add_delegation(storage, &sender, &msg.validator_dst_address, &denom, amount)?;
+                Ok(AppResponse::default())
+            }
+            "/alliance.alliance.MsgClaimDelegationRewards" => {
+                let msg = MsgClaimDelegationRewards::decode(value.as_slice())?;
+                ensure_signer(&sender, &msg.delegator_address)?;
+                let key = (&sender, msg.validator_address.as_str(), msg.denom.as_str());
+                if !DELEGATIONS.has(storage, key) {
+                    bail!(
+                        "no delegation of {} to {}",
+                        msg.denom,
+                        msg.validator_address
+                    );
+                }
+                let rewards = REWARDS.may_load(storage, key)?.unwrap_or_default();
+                REWARDS.remove(storage, key);
+                if rewards.is_empty() {
+                    return Ok(AppResponse::default());
+                }
+                router.sudo(
+                    api,
+                    storage,
+                    block,
+                    SudoMsg::Bank(BankSudo::Mint {
+                        to_address: sender.to_string(),
+                        amount: rewards,
+                    }),
+                )
+            }
+            _ => token_factory::execute_stargate(
+                api, storage, router, block, sender, &type_url, value,
+            ),
+        }
+    }
+}
+
+pub(crate) fn delegation(
+    storage: &dyn Storage,
+    delegator: &Addr,
+    validator: &str,
+    denom: &str,
+) -> AnyResult<Uint128> {
+    Ok(DELEGATIONS
+        .may_load(storage, (delegator, validator, denom))?
+        .unwrap_or_default())
+}
+
+// Adds `rewards` to the unclaimed rewards of an existing delegation
+pub(crate) fn accrue_rewards(
+    storage: &mut dyn Storage,
+    delegator: &Addr,
+    validator: &str,
+    denom: &str,
+    rewards: Vec<Coin>,
+) -> AnyResult<()> {
+    let key = (delegator, validator, denom);
+    if !DELEGATIONS.has(storage, key) {
+        bail!("no delegation of {} to {}", denom, validator);
+    }
+    let mut pending = REWARDS.may_load(storage, key)?.unwrap_or_default();
+    for reward in rewards
+        .into_iter()
+        .filter(|reward| !reward.amount.is_zero())
+    {
+        match pending.iter_mut().find(|coin| coin.denom == reward.denom) {
+            Some(coin) => coin.amount += reward.amount,
+            None => pending.push(reward),
+        }
+    }
+    REWARDS.save(storage, key, &pending)?;
+    Ok(())
+}
+
+fn add_delegation(
+    storage: &mut dyn Storage,
+    delegator: &Addr,
+    validator: &str,
+    denom: &str,
+    amount: Uint128,
+) -> AnyResult<()> {
+    if amount.is_zero() {
+        bail!("cannot delegate zero {}", denom);
+    }
+    let delegation = delegation(storage, delegator, validator, denom)?;
+    DELEGATIONS.save(
+        storage,
+        (delegator, validator, denom),
+        &(delegation + amount),
+    )?;
+    Ok(())
+}
+
+// Fully undelegated entries are kept, so their unclaimed rewards can still be claimed
+fn remove_delegation(
+    storage: &mut dyn Storage,
+    delegator: &Addr,
+    validator: &str,
+    denom: &str,
+    amount: Uint128,
+) -> AnyResult<()> {
+    let delegation = delegation(storage, delegator, validator, denom)?;
+    if amount.is_zero() || amount > delegation {
+        bail!(
+            "cannot undelegate {}{} from {}, delegated {}",
+            amount,
+            denom,
+            validator,
+            delegation
+        );
+    }
+    DELEGATIONS.save(
+        storage,
+        (delegator, validator, denom),
+        &(delegation - amount),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,99 @@
+pub mod alliance;
+#[cfg(test)]
+mod tests;
+pub mod token_factory;
+
+use alliance_hub::token_factory::{CustomExecuteMsg, Metadata};
+use alliance_protocol::alliance_protocol::InstantiateMsg;
+use alliance_protocol::helpers::AllianceHubContract;
+use cosmwasm_std::testing::{MockApi, MockStorage};
+use cosmwasm_std::{Addr, Coin, Empty, Uint128};
+use cw_multi_test::error::AnyResult;
+use cw_multi_test::{
+    App, AppBuilder, BankKeeper, Contract, ContractWrapper, DistributionKeeper, Executor,
+    GovFailingModule, IbcFailingModule, StakeKeeper, WasmKeeper,
+};
+
+pub use crate::alliance::{AllianceModule, ALLIANCE_MODULE_ACCOUNT};
+pub use crate::token_factory::TokenFactoryModule;
+
+pub type AllianceApp = App<
+    BankKeeper,
+    MockApi,
+    MockStorage,
+    TokenFactoryModule,
+    WasmKeeper<CustomExecuteMsg, Empty>,
+    StakeKeeper,
+    DistributionKeeper,
+    IbcFailingModule,
+    GovFailingModule,
+    AllianceModule,
+>;
+
+// App running the token factory bindings and the alliance module stand-ins, with the
+// given initial bank balances
+pub fn alliance_app(balances: Vec<(Addr, Vec<Coin>)>) -> AllianceApp {
+    AppBuilder::new_custom()
+        .with_custom(TokenFactoryModule)
+        .with_stargate(AllianceModule)
+        .build(|router, _, storage| {
+            for (address, coins) in balances {
+                router.bank.init_balance(storage, &address, coins).unwrap();
+            }
+        })
+}
+
+pub fn hub_contract() -> Box<dyn Contract<CustomExecuteMsg>> {
+    Box::new(
+        ContractWrapper::new(
+            alliance_hub::contract::execute,
+            alliance_hub::contract::instantiate,
+            alliance_hub::query::query,
+        )
+        .with_reply(alliance_hub::contract::reply)
+        .with_sudo(alliance_hub::contract::sudo)
+        .with_migrate_empty(alliance_hub::contract::migrate),
+    )
+}
+
+// Stores and instantiates the hub, which creates and mints the alliance token on the way
+pub fn instantiate_hub(
+    app: &mut AllianceApp,
+    sender: &Addr,
+    msg: &InstantiateMsg,
+) -> AnyResult<AllianceHubContract> {
+    let code_id = app.store_code(hub_contract());
+    let addr = app.instantiate_contract(
+        code_id,
+        sender.clone(),
+        msg,
+        &[],
+        "alliance-hub",
+        Some(sender.to_string()),
+    )?;
+    Ok(AllianceHubContract(addr))
+}
+
+pub fn delegation(
+    app: &AllianceApp,
+    delegator: &Addr,
+    validator: &str,
+    denom: &str,
+) -> AnyResult<Uint128> {
+    alliance::delegation(app.storage(), delegator, validator, denom)
+}
+
+// Accrues `rewards` to a delegation, to be minted to the delegator on its next claim
+pub fn accrue_rewards(
+    app: &mut AllianceApp,
+    delegator: &Addr,
+    validator: &str,
+    denom: &str,
+    rewards: Vec<Coin>,
+) -> AnyResult<()> {
+    alliance::accrue_rewards(app.storage_mut(), delegator, validator, denom, rewards)
+}
+
+pub fn denom_metadata(app: &AllianceApp, denom: &str) -> AnyResult<Option<Metadata>> {
+    token_factory::denom_metadata(app.storage(), denom)
+}
@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+
+use alliance_protocol::alliance_protocol::{
+    AllianceDelegateMsg, AllianceDelegation, AllianceRedelegateMsg, AllianceRedelegation,
+    AllianceUndelegateMsg, AssetDistribution, InstantiateMsg, TokenFactoryBackend,
+};
+use alliance_protocol::helpers::AllianceHubContract;
+use cosmwasm_std::{coin, coins, Addr, Decimal, Uint128};
+use cw_asset_v3::{Asset, AssetInfo};
+use cw_multi_test::Executor;
+
+use crate::{
+    accrue_rewards, alliance_app, delegation, denom_metadata, instantiate_hub, AllianceApp,
+    ALLIANCE_MODULE_ACCOUNT,
+};
+
+fn instantiate_msg(token_factory_backend: Option<TokenFactoryBackend>) -> InstantiateMsg {
+    InstantiateMsg {
+        governance: "gov".to_string(),
+        controller: "controller".to_string(),
+        oracle: "oracle".to_string(),
+        operator: "operator".to_string(),
+        alliance_token_denom: "ualliance".to_string(),
+        reward_asset: AssetInfo::native("uluna"),
+        alliance_token_metadata: None,
+        token_factory_backend,
+        staking_backend: None,
+        unbonding_period: None,
+    }
+}
+
+fn setup(token_factory_backend: Option<TokenFactoryBackend>) -> (AllianceApp, AllianceHubContract) {
+    let mut app = alliance_app(vec![(Addr::unchecked("user1"), coins(1000, "aWHALE"))]);
+    let hub = instantiate_hub(
+        &mut app,
+        &Addr::unchecked("admin"),
+        &instantiate_msg(token_factory_backend),
+    )
+    .unwrap();
+    (app, hub)
+}
+
+fn delegate(app: &mut AllianceApp, hub: &AllianceHubContract, validator: &str, amount: u128) {
+    let msg = hub
+        .alliance_delegate(AllianceDelegateMsg {
+            delegations: vec![AllianceDelegation {
+                validator: validator.to_string(),
+                amount: Uint128::new(amount),
+            }],
+        })
+        .unwrap();
+    app.execute(Addr::unchecked("controller"), msg.into())
+        .unwrap();
+}
+
+#[test]
+fn test_instantiate_creates_alliance_token() {
+    for backend in [TokenFactoryBackend::Terra, TokenFactoryBackend::Osmosis] {
+        let (app, hub) = setup(Some(backend));
+        let config = hub.config(&app.wrap()).unwrap();
+        let denom = format!("factory/{}/ualliance", hub.addr());
+        assert_eq!(config.alliance_token_denom, denom);
+        assert_eq!(
+            app.wrap().query_balance(hub.addr(), &denom).unwrap(),
+            coin(1_000_000_000_000, &denom)
+        );
+        let metadata = denom_metadata(&app, &denom).unwrap().unwrap();
+        assert_eq!(metadata.base, denom);
+    }
+}
+
+#[test]
+fn test_delegations() {
+    let (mut app, hub) = setup(None);
+    let denom = hub.config(&app.wrap()).unwrap().alliance_token_denom;
+    delegate(&mut app, &hub, "validator1", 1000);
+
+    let msg = hub
+        .alliance_redelegate(AllianceRedelegateMsg {
+            redelegations: vec![AllianceRedelegation {
+                src_validator: "validator1".to_string(),
+                dst_validator: "validator2".to_string(),
+                amount: Uint128::new(400),
+            }],
+        })
+        .unwrap();
+    app.execute(Addr::unchecked("controller"), msg.into())
+        .unwrap();
+    let msg = hub
+        .alliance_undelegate(AllianceUndelegateMsg {
+            undelegations: vec![AllianceDelegation {
+                validator: "validator2".to_string(),
+                amount: Uint128::new(100),
+            }],
+        })
+        .unwrap();
+    app.execute(Addr::unchecked("controller"), msg.into())
+        .unwrap();
+
+    assert_eq!(
+        delegation(&app, &hub.addr(), "validator1", &denom).unwrap(),
+        Uint128::new(600)
+    );
+    assert_eq!(
+        delegation(&app, &hub.addr(), "validator2", &denom).unwrap(),
+        Uint128::new(300)
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(ALLIANCE_MODULE_ACCOUNT, &denom)
+            .unwrap(),
+        coin(900, &denom)
+    );
+
+    // The hub cannot undelegate more than it delegated
+    let msg = hub
+        .alliance_undelegate(AllianceUndelegateMsg {
+            undelegations: vec![AllianceDelegation {
+                validator: "validator2".to_string(),
+                amount: Uint128::new(301),
+            }],
+        })
+        .unwrap();
+    app.execute(Addr::unchecked("controller"), msg.into())
+        .unwrap_err();
+}
+
+#[test]
+fn test_stake_and_claim_rewards() {
+    let (mut app, hub) = setup(None);
+    let denom = hub.config(&app.wrap()).unwrap().alliance_token_denom;
+    let user = Addr::unchecked("user1");
+    let asset = AssetInfo::native("aWHALE");
+
+    let msg = hub
+        .whitelist_assets(HashMap::from([(
+            "chain-1".to_string(),
+            vec![asset.clone()],
+        )]))
+        .unwrap();
+    app.execute(Addr::unchecked("gov"), msg.into()).unwrap();
+    let msg = hub
+        .set_asset_reward_distribution(vec![AssetDistribution {
+            asset: asset.clone(),
+            distribution: Decimal::percent(100),
+        }])
+        .unwrap();
+    app.execute(Addr::unchecked("gov"), msg.into()).unwrap();
+    let msg = hub.stake(coin(1000, "aWHALE"), None, None).unwrap();
+    app.execute(user.clone(), msg.into()).unwrap();
+    delegate(&mut app, &hub, "validator1", 1000);
+
+    accrue_rewards(
+        &mut app,
+        &hub.addr(),
+        "validator1",
+        &denom,
+        coins(500, "uluna"),
+    )
+    .unwrap();
+    let msg = hub.update_rewards(vec![]).unwrap();
+    app.execute(Addr::unchecked("keeper"), msg.into()).unwrap();
+    assert_eq!(
+        hub.pending_rewards(&app.wrap(), &user, asset.clone())
+            .unwrap()
+            .rewards,
+        Uint128::new(500)
+    );
+
+    let msg = hub.claim_rewards(asset.clone()).unwrap();
+    app.execute(user.clone(), msg.into()).unwrap();
+    assert_eq!(
+        app.wrap().query_balance(&user, "uluna").unwrap(),
+        coin(500, "uluna")
+    );
+
+    let msg = hub.unstake(Asset::native("aWHALE", 1000u128)).unwrap();
+    app.execute(user.clone(), msg.into()).unwrap();
+    assert_eq!(
+        app.wrap().query_balance(&user, "aWHALE").unwrap(),
+        coin(1000, "aWHALE")
+    );
+}
+
+#[test]
+fn test_rewards_need_a_delegation() {
+    let (mut app, hub) = setup(None);
+    let denom = hub.config(&app.wrap()).unwrap().alliance_token_denom;
+    accrue_rewards(
+        &mut app,
+        &hub.addr(),
+        "validator1",
+        &denom,
+        coins(500, "uluna"),
+    )
+    .unwrap_err();
+}
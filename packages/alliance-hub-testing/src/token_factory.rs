@@ -0,0 +1,299 @@
+use alliance_hub::token_factory::{
+    CustomExecuteMsg, DenomUnit, Metadata, MsgBurn, MsgCreateDenom, MsgCreateDenomResponse,
+    MsgMint, MsgSetDenomMetadata, TokenExecuteMsg,
+};
+use anyhow::{anyhow, bail};
+use cosmwasm_std::{
+    coins, Addr, Api, BankMsg, Binary, BlockInfo, CustomQuery, Empty, Querier, Storage, Uint128,
+};
+use cw_multi_test::error::AnyResult;
+use cw_multi_test::{AppResponse, BankSudo, CosmosRouter, Module, SudoMsg};
+use cw_storage_plus_120::Map;
+use prost::Message;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use std::fmt::Debug;
+use terra_proto_rs::cosmos::base::v1beta1::Coin as ProtoCoin;
+
+// Admin and bank metadata of every denom created through the stand-in
+const DENOM_ADMINS: Map<&str, Addr> = Map::new("token_factory_stand_in__admins");
+const DENOM_METADATA: Map<&str, Metadata> = Map::new("token_factory_stand_in__metadata");
+
+// Stand-in for the Terra token factory bindings. Denoms are named `factory/<creator>/<subdenom>`
+// and only their creator can mint, burn or set their metadata.
+#[derive(Default)]
+pub struct TokenFactoryModule;
+
+impl Module for TokenFactoryModule {
+    type ExecT = CustomExecuteMsg;
+    type QueryT = Empty;
+    type SudoT = Empty;
+
+    fn execute<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        sender: Addr,
+        msg: CustomExecuteMsg,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        match msg {
+            CustomExecuteMsg::Token(TokenExecuteMsg::CreateDenom { subdenom }) => {
+                create_denom(storage, &sender, &subdenom)
+            }
+            CustomExecuteMsg::Token(TokenExecuteMsg::MintTokens {
+                denom,
+                amount,
+                mint_to_address,
+            }) => mint(
+                api,
+                storage,
+                router,
+                block,
+                &sender,
+                &denom,
+                amount,
+                &mint_to_address,
+            ),
+            CustomExecuteMsg::Token(TokenExecuteMsg::BurnTokens {
+                denom,
+                amount,
+                burn_from_address,
+            }) => burn(
+                api,
+                storage,
+                router,
+                block,
+                &sender,
+                &denom,
+                amount,
+                &burn_from_address,
+            ),
+            CustomExecuteMsg::Token(TokenExecuteMsg::SetMetadata { denom, metadata }) => {
+                set_metadata(storage, &sender, &denom, metadata)
+            }
+        }
+    }
+
+    fn query(
+        &self,
+        _api: &dyn Api,
+        _storage: &dyn Storage,
+        _querier: &dyn Querier,
+        _block: &BlockInfo,
+        request: Empty,
+    ) -> AnyResult<Binary> {
+        bail!("Unexpected custom query {:?}", request)
+    }
+
+    fn sudo<ExecC, QueryC>(
+        &self,
+        _api: &dyn Api,
+        _storage: &mut dyn Storage,
+        _router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        _block: &BlockInfo,
+        msg: Empty,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        bail!("Unexpected custom sudo {:?}", msg)
+    }
+}
+
+// Bank metadata set for `denom`, if any
+pub fn denom_metadata(storage: &dyn Storage, denom: &str) -> AnyResult<Option<Metadata>> {
+    Ok(DENOM_METADATA.may_load(storage, denom)?)
+}
+
+// Handles the osmosis.tokenfactory.v1beta1 Stargate messages with the same rules
+pub(crate) fn execute_stargate<ExecC, QueryC>(
+    api: &dyn Api,
+    storage: &mut dyn Storage,
+    router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+    block: &BlockInfo,
+    sender: Addr,
+    type_url: &str,
+    value: Binary,
+) -> AnyResult<AppResponse>
+where
+    ExecC: Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+    QueryC: CustomQuery + DeserializeOwned + 'static,
+{
+    match type_url {
+        "/osmosis.tokenfactory.v1beta1.MsgCreateDenom" => {
+            let msg = MsgCreateDenom::decode(value.as_slice())?;
+            ensure_signer(&sender, &msg.sender)?;
+            create_denom(storage, &sender, &msg.subdenom)
+        }
+        "/osmosis.tokenfactory.v1beta1.MsgMint" => {
+            let msg = MsgMint::decode(value.as_slice())?;
+            ensure_signer(&sender, &msg.sender)?;
+            let (denom, amount) = proto_coin(msg.amount)?;
+            mint(
+                api,
+                storage,
+                router,
+                block,
+                &sender,
+                &denom,
+                amount,
+                &msg.mint_to_address,
+            )
+        }
+        "/osmosis.tokenfactory.v1beta1.MsgBurn" => {
+            let msg = MsgBurn::decode(value.as_slice())?;
+            ensure_signer(&sender, &msg.sender)?;
+            let (denom, amount) = proto_coin(msg.amount)?;
+            burn(
+                api,
+                storage,
+                router,
+                block,
+                &sender,
+                &denom,
+                amount,
+                &msg.burn_from_address,
+            )
+        }
+        "/osmosis.tokenfactory.v1beta1.MsgSetDenomMetadata" => {
+            let msg = MsgSetDenomMetadata::decode(value.as_slice())?;
+            ensure_signer(&sender, &msg.sender)?;
+            let metadata = msg.metadata.ok_or_else(|| anyhow!("missing metadata"))?;
+            let metadata = Metadata {
+                description: metadata.description,
+                denom_units: metadata
+                    .denom_units
+                    .into_iter()
+                    .map(|unit| DenomUnit {
+                        denom: unit.denom,
+                        exponent: unit.exponent,
+                        aliases: unit.aliases,
+                    })
+                    .collect(),
+                base: metadata.base.clone(),
+                display: metadata.display,
+                name: metadata.name,
+                symbol: metadata.symbol,
+            };
+            set_metadata(storage, &sender, &metadata.base.clone(), metadata)
+        }
+        _ => bail!("Unexpected stargate message {}", type_url),
+    }
+}
+
+fn create_denom(
+    storage: &mut dyn Storage,
+    sender: &Addr,
+    subdenom: &str,
+) -> AnyResult<AppResponse> {
+    if subdenom.is_empty() {
+        bail!("subdenom cannot be empty");
+    }
+    let denom = format!("factory/{}/{}", sender, subdenom);
+    if DENOM_ADMINS.has(storage, &denom) {
+        bail!("denom {} already exists", denom);
+    }
+    DENOM_ADMINS.save(storage, &denom, sender)?;
+    let data = MsgCreateDenomResponse {
+        new_token_denom: denom,
+    }
+    .encode_to_vec();
+    Ok(AppResponse {
+        events: vec![],
+        data: Some(Binary::from(data)),
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn mint<ExecC, QueryC>(
+    api: &dyn Api,
+    storage: &mut dyn Storage,
+    router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+    block: &BlockInfo,
+    sender: &Addr,
+    denom: &str,
+    amount: Uint128,
+    mint_to_address: &str,
+) -> AnyResult<AppResponse>
+where
+    ExecC: Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+    QueryC: CustomQuery + DeserializeOwned + 'static,
+{
+    ensure_admin(storage, sender, denom)?;
+    router.sudo(
+        api,
+        storage,
+        block,
+        SudoMsg::Bank(BankSudo::Mint {
+            to_address: mint_to_address.to_string(),
+            amount: coins(amount.u128(), denom),
+        }),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn burn<ExecC, QueryC>(
+    api: &dyn Api,
+    storage: &mut dyn Storage,
+    router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+    block: &BlockInfo,
+    sender: &Addr,
+    denom: &str,
+    amount: Uint128,
+    burn_from_address: &str,
+) -> AnyResult<AppResponse>
+where
+    ExecC: Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+    QueryC: CustomQuery + DeserializeOwned + 'static,
+{
+    ensure_admin(storage, sender, denom)?;
+    router.execute(
+        api,
+        storage,
+        block,
+        Addr::unchecked(burn_from_address),
+        BankMsg::Burn {
+            amount: coins(amount.u128(), denom),
+        }
+        .into(),
+    )
+}
+
+fn set_metadata(
+    storage: &mut dyn Storage,
+    sender: &Addr,
+    denom: &str,
+    metadata: Metadata,
+) -> AnyResult<AppResponse> {
+    ensure_admin(storage, sender, denom)?;
+    DENOM_METADATA.save(storage, denom, &metadata)?;
+    Ok(AppResponse::default())
+}
+
+fn ensure_admin(storage: &dyn Storage, sender: &Addr, denom: &str) -> AnyResult<()> {
+    match DENOM_ADMINS.may_load(storage, denom)? {
+        Some(admin) if admin == sender => Ok(()),
+        Some(_) => bail!("{} is not the admin of {}", sender, denom),
+        None => bail!("denom {} does not exist", denom),
+    }
+}
+
+// Stargate messages name their signer, which has to be the account that sent them
+pub(crate) fn ensure_signer(sender: &Addr, signer: &str) -> AnyResult<()> {
+    if sender.as_str() != signer {
+        bail!("{} cannot sign for {}", sender, signer);
+    }
+    Ok(())
+}
+
+pub(crate) fn proto_coin(coin: Option<ProtoCoin>) -> AnyResult<(String, Uint128)> {
+    let coin = coin.ok_or_else(|| anyhow!("missing amount"))?;
+    Ok((coin.denom, coin.amount.parse()?))
+}
@@ -21,15 +21,60 @@ impl Expire for LunaInfo {
     }
 }
 
+// Without feeders the controller is the only feeder and every update is saved as is
 #[cw_serde]
 pub struct InstantiateMsg {
     pub controller_addr: String,
     pub data_expiry_seconds: u64,
+    pub feeders: Option<Vec<String>>,
+    pub quorum: Option<u32>,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    UpdateChainsInfo { chains_info: ChainsInfo },
+    // Submits the data of a feeder for the current round
+    UpdateChainsInfo {
+        chains_info: ChainsInfo,
+    },
+    // Controller only. The quorum must stay between one and the number of feeders.
+    UpdateFeeders {
+        add: Vec<String>,
+        remove: Vec<String>,
+        quorum: Option<u32>,
+    },
+}
+
+// Submissions are collected per round. Once `quorum` feeders have submitted, the medians
+// are saved and the next round starts. A round open for longer than the data expiry is
+// dropped and restarted by the next submission.
+#[cw_serde]
+pub struct Round {
+    pub id: u64,
+    pub started_at: Timestamp,
+}
+
+#[cw_serde]
+pub struct FeederSubmission {
+    pub feeder: Addr,
+    pub round: u64,
+    pub chains_info: ChainsInfo,
+    pub submitted_at: Timestamp,
+}
+
+#[cw_serde]
+pub struct FeedersResponse {
+    pub feeders: Vec<Addr>,
+    pub quorum: u32,
+}
+
+#[cw_serde]
+pub struct RoundStatusResponse {
+    pub round: u64,
+    pub started_at: Timestamp,
+    pub expires_at: Timestamp,
+    pub quorum: u32,
+    pub submitted: Vec<Addr>,
+    pub pending: Vec<Addr>,
 }
 
 #[cw_serde]
@@ -157,6 +202,13 @@ pub enum QueryMsg {
     QueryChainsInfoUnsafe {},
     #[returns(Vec<EmissionsDistribution>)]
     QueryEmissionsDistributions(HashMap<ChainId, Vec<AssetStaked>>),
+    #[returns(FeedersResponse)]
+    QueryFeeders {},
+    // Submissions of `round`, the current round by default
+    #[returns(Vec<FeederSubmission>)]
+    QueryFeederSubmissions { round: Option<u64> },
+    #[returns(RoundStatusResponse)]
+    QueryRoundStatus {},
 }
 
 #[cw_serde]
@@ -1,6 +1,9 @@
 use crate::alliance_oracle_types::ChainId;
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Decimal, Timestamp, Uint128};
+use cosmwasm_std::{
+    to_json_binary, Addr, CosmosMsg, Decimal, StdResult, Timestamp, Uint128, WasmMsg,
+};
+use cw2::ContractVersion;
 use cw20::Cw20ReceiveMsg;
 use cw_asset_v3::{Asset, AssetInfo};
 use std::collections::{HashMap, HashSet};
@@ -14,7 +17,75 @@ pub struct Config {
     pub last_reward_update_timestamp: Timestamp,
     pub alliance_token_denom: String,
     pub alliance_token_supply: Uint128,
-    pub reward_denom: String,
+    // Asset the rewards are collected and paid in, native or cw20
+    pub reward_asset: AssetInfo,
+    #[serde(default)]
+    pub token_factory_backend: TokenFactoryBackend,
+    #[serde(default)]
+    pub staking_backend: StakingBackend,
+    // Minimum number of seconds between two reward updates
+    #[serde(default)]
+    pub reward_update_interval: u64,
+    // Share of the collected rewards paid to the account triggering the update
+    #[serde(default)]
+    pub keeper_bounty: Decimal,
+    // Unbonding period of the staking module in seconds, after which undelegated and
+    // redelegated stake stops being tracked in the delegation ledger
+    #[serde(default = "default_unbonding_period")]
+    pub unbonding_period: u64,
+}
+
+// Unbonding period of x/staking and x/alliance on Terra
+pub const DEFAULT_UNBONDING_PERIOD: u64 = 21 * 24 * 60 * 60;
+
+fn default_unbonding_period() -> u64 {
+    DEFAULT_UNBONDING_PERIOD
+}
+
+// Module used to create and manage the alliance token denom
+#[cw_serde]
+#[derive(Default)]
+pub enum TokenFactoryBackend {
+    // Terra `CosmosMsg::Custom` token messages
+    #[default]
+    Terra,
+    // `osmosis.tokenfactory.v1beta1` Stargate messages
+    Osmosis,
+}
+
+// Module the hub delegates through and claims rewards from
+#[cw_serde]
+#[derive(Default)]
+pub enum StakingBackend {
+    // `alliance.alliance` Stargate messages delegating the alliance token
+    #[default]
+    Alliance,
+    // x/staking and x/distribution messages delegating `bond_denom`, which is required
+    // and cannot be the reward asset or a staked asset. Rewards x/distribution withdraws
+    // on every delegation change are distributed with the next reward update.
+    Native {
+        bond_denom: Option<String>,
+    },
+}
+
+impl Config {
+    pub fn role(&self, role: Role) -> &Addr {
+        match role {
+            Role::Governance => &self.governance,
+            Role::Controller => &self.controller,
+            Role::Oracle => &self.oracle,
+            Role::Operator => &self.operator,
+        }
+    }
+
+    pub fn set_role(&mut self, role: Role, address: Addr) {
+        match role {
+            Role::Governance => self.governance = address,
+            Role::Controller => self.controller = address,
+            Role::Oracle => self.oracle = address,
+            Role::Operator => self.operator = address,
+        }
+    }
 }
 
 #[cw_serde]
@@ -30,7 +101,43 @@ pub struct InstantiateMsg {
     pub alliance_token_denom: String,
     pub oracle: String,
     pub operator: String,
-    pub reward_denom: String,
+    pub reward_asset: AssetInfo,
+    pub alliance_token_metadata: Option<AllianceTokenMetadata>,
+    pub token_factory_backend: Option<TokenFactoryBackend>,
+    pub staking_backend: Option<StakingBackend>,
+    // Defaults to `DEFAULT_UNBONDING_PERIOD`
+    pub unbonding_period: Option<u64>,
+}
+
+#[cw_serde]
+pub struct AllianceTokenDenomUnit {
+    pub denom: String,
+    pub exponent: u32,
+    pub aliases: Vec<String>,
+}
+
+// Bank metadata of the alliance token. The base unit (the token factory denom with
+// exponent 0) is always registered, `denom_units` lists the additional units and
+// `display` defaults to the base denom.
+#[cw_serde]
+pub struct AllianceTokenMetadata {
+    pub description: String,
+    pub name: String,
+    pub symbol: String,
+    pub display: Option<String>,
+    pub denom_units: Vec<AllianceTokenDenomUnit>,
+}
+
+impl Default for AllianceTokenMetadata {
+    fn default() -> Self {
+        AllianceTokenMetadata {
+            description: "Staking token for the alliance protocol".to_string(),
+            name: "Alliance Token".to_string(),
+            symbol: "ALLIANCE".to_string(),
+            display: None,
+            denom_units: vec![],
+        }
+    }
 }
 
 #[cw_serde]
@@ -38,9 +145,26 @@ pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
 
     // Public functions
-    Stake {},
+    // Credits the sent funds to `recipient`, or to the sender. Transfers executed through
+    // ibc-hooks set `origin` to record the origin chain sender of the intermediate address,
+    // in which case the stake cannot be credited to another recipient.
+    Stake {
+        recipient: Option<String>,
+        origin: Option<IbcOrigin>,
+    },
+    // Funds of remote stakers are sent back to the sender of their origin chain, and assets
+    // that cannot be sent over IBC are rejected for them. Neither message accepts funds.
     Unstake(Asset),
     ClaimRewards(AssetInfo),
+    // Same as Unstake and ClaimRewards, but the funds are sent to `destination` over IBC
+    UnstakeOverIbc {
+        asset: Asset,
+        destination: IbcDestination,
+    },
+    ClaimRewardsOverIbc {
+        asset: AssetInfo,
+        destination: IbcDestination,
+    },
     UpdateRewards {},
 
     // Privileged functions
@@ -50,21 +174,277 @@ pub enum ExecuteMsg {
     AllianceDelegate(AllianceDelegateMsg),
     AllianceUndelegate(AllianceUndelegateMsg),
     AllianceRedelegate(AllianceRedelegateMsg),
+    SetDelegationStrategy(DelegationStrategy),
+    RebalanceDelegations {},
+    // Clears the claim failures of a validator, so reward updates claim it again
+    ResetClaimFailures {
+        validator: String,
+    },
+    MintAllianceTokens {
+        amount: Uint128,
+    },
+    BurnAllianceTokens {
+        amount: Uint128,
+    },
+    UpdateAllianceTokenMetadata(AllianceTokenMetadata),
+    UpdateRewardsConfig {
+        reward_update_interval: Option<u64>,
+        keeper_bounty: Option<Decimal>,
+    },
+    // Rejected while rewards are owed to the stakers in the current asset
+    UpdateRewardAsset(AssetInfo),
+    // Replaces the voting weights when set
+    UpdateVotingConfig {
+        dao: Option<String>,
+        asset_weights: Option<Vec<AssetVotingWeight>>,
+    },
     RebalanceEmissions {},
     RebalanceEmissionsCallback {},
     SetAssetRewardDistribution(Vec<AssetDistribution>),
-    UpdateConfig {
-        governance: Option<String>,
-        controller: Option<String>,
-        oracle: Option<String>,
-        operator: Option<String>,
+
+    // Role handover
+    ProposeRoleTransfer {
+        role: Role,
+        new_address: String,
+        expires_in: u64,
+    },
+    AcceptRoleTransfer {
+        role: Role,
+    },
+    CancelRoleTransfer {
+        role: Role,
+    },
+
+    // Circuit breaker
+    Pause(PauseScope),
+    Unpause(PauseScope),
+
+    // Permission registry
+    GrantCapability {
+        address: String,
+        capability: Capability,
+    },
+    RevokeCapability {
+        address: String,
+        capability: Capability,
+    },
+
+    // Contracts notified of every staked balance change, each call limited to `gas_limit`
+    AddHook {
+        addr: String,
+        gas_limit: u64,
+    },
+    RemoveHook {
+        addr: String,
+    },
+    // Sends `recipient` the balance of `asset` held above what the hub owes its stakers
+    Sweep {
+        asset: AssetInfo,
+        recipient: String,
     },
 }
 
+#[cw_serde]
+#[derive(Copy)]
+pub enum Capability {
+    Whitelist,
+    SetDistribution,
+    Delegate,
+    Redelegate,
+    Pause,
+    Config,
+    Sweep,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 7] = [
+        Capability::Whitelist,
+        Capability::SetDistribution,
+        Capability::Delegate,
+        Capability::Redelegate,
+        Capability::Pause,
+        Capability::Config,
+        Capability::Sweep,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Capability::Whitelist => "whitelist",
+            Capability::SetDistribution => "set_distribution",
+            Capability::Delegate => "delegate",
+            Capability::Redelegate => "redelegate",
+            Capability::Pause => "pause",
+            Capability::Config => "config",
+            Capability::Sweep => "sweep",
+        }
+    }
+}
+
+impl std::fmt::Display for Capability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cw_serde]
+pub enum PauseScope {
+    // Halts every user and controller action on the hub
+    Global {},
+    Stake(AssetInfo),
+    Unstake(AssetInfo),
+    ClaimRewards(AssetInfo),
+    RewardUpdates {},
+}
+
+impl std::fmt::Display for PauseScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PauseScope::Global {} => write!(f, "global"),
+            PauseScope::Stake(asset) => write!(f, "stake:{}", asset),
+            PauseScope::Unstake(asset) => write!(f, "unstake:{}", asset),
+            PauseScope::ClaimRewards(asset) => write!(f, "claim_rewards:{}", asset),
+            PauseScope::RewardUpdates {} => write!(f, "reward_updates"),
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct PausedActions {
+    pub stake: bool,
+    pub unstake: bool,
+    pub claim_rewards: bool,
+}
+
+impl PausedActions {
+    pub fn is_empty(&self) -> bool {
+        !(self.stake || self.unstake || self.claim_rewards)
+    }
+}
+
+#[cw_serde]
+#[derive(Copy)]
+pub enum Role {
+    Governance,
+    Controller,
+    Oracle,
+    Operator,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [
+        Role::Governance,
+        Role::Controller,
+        Role::Oracle,
+        Role::Operator,
+    ];
+
+    // Capabilities granted to the holder of the role, equivalent to the
+    // permissions the role had before the permission registry existed
+    pub fn default_capabilities(&self) -> &'static [Capability] {
+        match self {
+            Role::Governance => &Capability::ALL,
+            Role::Controller => &[Capability::Delegate, Capability::Redelegate],
+            Role::Oracle => &[],
+            Role::Operator => &[Capability::SetDistribution, Capability::Pause],
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Governance => "governance",
+            Role::Controller => "controller",
+            Role::Oracle => "oracle",
+            Role::Operator => "operator",
+        }
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cw_serde]
+pub struct PendingRoleTransfer {
+    pub role: Role,
+    pub proposer: Addr,
+    pub new_address: Addr,
+    pub expiry: Timestamp,
+}
+
 #[cw_serde]
 pub enum Cw20HookMsg {
     Stake {},
     Unstake(Asset),
+    // Sent by the cw20 reward token, the amount is added to the collected rewards
+    UpdateRewards {},
+}
+
+#[cw_serde]
+pub struct IbcOrigin {
+    pub channel_id: String,
+    pub sender: String,
+}
+
+#[cw_serde]
+pub struct IbcDestination {
+    pub channel_id: String,
+    pub receiver: String,
+    // Seconds after which the transfer times out and is refunded
+    pub timeout_seconds: u64,
+}
+
+#[cw_serde]
+pub enum IbcTransferKind {
+    Unstake,
+    ClaimRewards,
+}
+
+// Funds sent over IBC, refunded to `user` if the transfer fails. `asset` is the staked asset,
+// also for reward claims.
+#[cw_serde]
+pub struct IbcTransfer {
+    pub user: Addr,
+    pub asset: AssetInfo,
+    pub amount: Uint128,
+    pub kind: IbcTransferKind,
+}
+
+// Sent to the registered hooks after the staked balance of `user` changes from `old` to `new`:
+// on stake, on unstake (including UnstakeOverIbc) and when a failed or timed out
+// UnstakeOverIbc transfer is refunded to the staked balance
+#[cw_serde]
+pub struct StakeChangedHookMsg {
+    pub user: Addr,
+    pub asset: AssetInfo,
+    pub old: Uint128,
+    pub new: Uint128,
+}
+
+impl StakeChangedHookMsg {
+    pub fn into_cosmos_msg<C>(self, contract_addr: impl Into<String>) -> StdResult<CosmosMsg<C>> {
+        Ok(WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg: to_json_binary(&StakeChangedExecuteMsg::StakeChangedHook(self))?,
+            funds: vec![],
+        }
+        .into())
+    }
+}
+
+// Hook contracts must accept this variant in their ExecuteMsg
+#[cw_serde]
+pub enum StakeChangedExecuteMsg {
+    StakeChangedHook(StakeChangedHookMsg),
+}
+
+// Voting power granted per staked unit of `asset`
+#[cw_serde]
+pub struct AssetVotingWeight {
+    pub asset: AssetInfo,
+    pub weight: Decimal,
 }
 
 #[cw_serde]
@@ -83,6 +463,58 @@ pub struct AllianceUndelegateMsg {
     pub undelegations: Vec<AllianceDelegation>,
 }
 
+// Stake moved out of a validator that is still in its unbonding period
+#[cw_serde]
+pub struct Unbonding {
+    pub amount: Uint128,
+    pub completes_at: Timestamp,
+}
+
+// Ledger entry of the alliance token delegated by the hub to a validator.
+// `undelegating` and `redelegating` hold the undelegations and redelegations out of the
+// validator until they complete; the entry is pruned once nothing is delegated or in flight.
+#[cw_serde]
+pub struct ValidatorDelegation {
+    pub validator: String,
+    pub delegated: Uint128,
+    pub undelegating: Vec<Unbonding>,
+    pub redelegating: Vec<Unbonding>,
+}
+
+impl ValidatorDelegation {
+    pub fn new(validator: String) -> Self {
+        Self {
+            validator,
+            delegated: Uint128::zero(),
+            undelegating: vec![],
+            redelegating: vec![],
+        }
+    }
+
+    // Drops the unbondings completed at `now`
+    pub fn prune_unbondings(&mut self, now: Timestamp) {
+        self.undelegating
+            .retain(|unbonding| unbonding.completes_at > now);
+        self.redelegating
+            .retain(|unbonding| unbonding.completes_at > now);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.delegated.is_zero() && self.undelegating.is_empty() && self.redelegating.is_empty()
+    }
+}
+
+// Consecutive failed reward claims of a validator. Validators are `excluded` from
+// reward updates once the failures reach the hub threshold, until a new delegation
+// or `ResetClaimFailures` resets the record.
+#[cw_serde]
+pub struct ClaimFailure {
+    pub validator: String,
+    pub consecutive_failures: u32,
+    pub last_error: String,
+    pub excluded: bool,
+}
+
 #[cw_serde]
 pub struct AllianceRedelegation {
     pub src_validator: String,
@@ -95,15 +527,55 @@ pub struct AllianceRedelegateMsg {
     pub redelegations: Vec<AllianceRedelegation>,
 }
 
+#[cw_serde]
+pub struct ValidatorWeight {
+    pub validator: String,
+    pub weight: Decimal,
+}
+
+// Target distribution of the alliance token across validators. Validators that received
+// a redelegation cannot redelegate it further before the unbonding period elapsed.
+#[cw_serde]
+pub struct DelegationStrategy {
+    pub validators: Vec<ValidatorWeight>,
+}
+
+#[cw_serde]
+pub enum RebalanceAction {
+    Delegate(AllianceDelegation),
+    Undelegate(AllianceDelegation),
+    Redelegate(AllianceRedelegation),
+}
+
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
     #[returns(Config)]
     Config {},
 
-    #[returns(HashSet<Addr>)]
+    #[returns(HashSet<String>)]
     Validators {},
 
+    #[returns(Vec<ValidatorDelegation>)]
+    Delegations {},
+
+    // Steps a migration from `from_version` (the stored version by default) to this
+    // code would run
+    #[returns(Vec<MigrationStepRes>)]
+    MigrationPlan { from_version: Option<String> },
+
+    #[returns(Option<IbcOrigin>)]
+    RemoteIdentity { address: String },
+
+    #[returns(Vec<ClaimFailure>)]
+    ClaimFailures {},
+
+    #[returns(DelegationStrategy)]
+    DelegationStrategy {},
+
+    #[returns(Vec<RebalanceAction>)]
+    RebalancePlan {},
+
     #[returns(WhitelistedAssetsResponse)]
     WhitelistedAssets {},
 
@@ -124,6 +596,60 @@ pub enum QueryMsg {
 
     #[returns(Vec<StakedBalanceRes>)]
     TotalStakedBalances {},
+
+    // Balances at the start of block `height`, the current block by default
+    #[returns(StakedBalanceAtHeightRes)]
+    StakedBalanceAtHeight {
+        address: String,
+        asset: AssetInfo,
+        height: Option<u64>,
+    },
+
+    #[returns(StakedBalanceAtHeightRes)]
+    TotalStakedAtHeight {
+        asset: AssetInfo,
+        height: Option<u64>,
+    },
+
+    #[returns(Vec<PendingRoleTransfer>)]
+    PendingRoleTransfers {},
+
+    #[returns(PauseStatusRes)]
+    PauseStatus {},
+
+    #[returns(Vec<CapabilityHoldersRes>)]
+    CapabilityHolders {},
+
+    #[returns(Vec<HookRes>)]
+    Hooks {},
+
+    #[returns(VotingConfigRes)]
+    VotingConfig {},
+
+    // DAO DAO voting module interface. The voting power is the sum of the staked balances
+    // weighted by the voting weight of their asset, both read at the start of `height`.
+    #[returns(VotingPowerAtHeightResponse)]
+    VotingPowerAtHeight {
+        address: String,
+        height: Option<u64>,
+    },
+
+    #[returns(TotalPowerAtHeightResponse)]
+    TotalPowerAtHeight { height: Option<u64> },
+
+    #[returns(Addr)]
+    Dao {},
+
+    #[returns(InfoResponse)]
+    Info {},
+
+    // Scans `limit` stake positions per page. Pass the returned `next` cursor to resume;
+    // position sums only cover the page and are added up by the client.
+    #[returns(InvariantsRes)]
+    CheckInvariants {
+        start_after: Option<InvariantsCursor>,
+        limit: Option<u32>,
+    },
 }
 
 pub type WhitelistedAssetsResponse = HashMap<ChainId, Vec<AssetInfo>>;
@@ -144,8 +670,50 @@ pub struct AllPendingRewardsQuery {
     pub address: String,
 }
 
+// Messages sent by the chain scheduler (end blocker or clock module)
+#[cw_serde]
+pub enum SudoMsg {
+    EndBlock {},
+    ClockEndBlock {},
+    // Sent by ibc-hooks for transfers with an `ibc_callback` memo
+    IbcLifecycleComplete(IbcLifecycleComplete),
+}
+
+#[cw_serde]
+pub enum IbcLifecycleComplete {
+    IbcAck {
+        channel: String,
+        sequence: u64,
+        ack: String,
+        success: bool,
+    },
+    IbcTimeout {
+        channel: String,
+        sequence: u64,
+    },
+}
+
 #[cw_serde]
-pub struct MigrateMsg {}
+#[derive(Default)]
+pub struct MigrateMsg {
+    // Amounts delegated to each validator of the legacy validator set,
+    // used to seed the delegation ledger
+    pub delegations: Option<Vec<AllianceDelegation>>,
+    // Values for config fields added since the stored version, which otherwise keep
+    // their defaults
+    pub token_factory_backend: Option<TokenFactoryBackend>,
+    pub staking_backend: Option<StakingBackend>,
+    pub reward_update_interval: Option<u64>,
+    pub keeper_bounty: Option<Decimal>,
+    pub unbonding_period: Option<u64>,
+}
+
+#[cw_serde]
+pub struct MigrationStepRes {
+    // Version introducing the step
+    pub version: String,
+    pub name: String,
+}
 
 #[cw_serde]
 pub struct StakedBalanceRes {
@@ -153,6 +721,102 @@ pub struct StakedBalanceRes {
     pub balance: Uint128,
 }
 
+#[cw_serde]
+pub struct StakedBalanceAtHeightRes {
+    pub asset: AssetInfo,
+    pub balance: Uint128,
+    pub height: u64,
+}
+
+#[cw_serde]
+pub struct VotingConfigRes {
+    pub dao: Addr,
+    pub asset_weights: Vec<AssetVotingWeight>,
+}
+
+#[cw_serde]
+pub struct VotingPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
+
+#[cw_serde]
+pub struct TotalPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
+
+#[cw_serde]
+pub struct InfoResponse {
+    pub info: ContractVersion,
+}
+
+// Last stake position scanned
+#[cw_serde]
+pub struct InvariantsCursor {
+    pub address: String,
+    pub asset: AssetInfo,
+}
+
+#[cw_serde]
+pub struct InvariantsRes {
+    pub assets: Vec<AssetInvariantsRes>,
+    pub rewards: RewardInvariantsRes,
+    pub next: Option<InvariantsCursor>,
+}
+
+#[cw_serde]
+pub struct AssetInvariantsRes {
+    pub asset: AssetInfo,
+    pub total_balance: Uint128,
+    // Sum of the user balances in this page
+    pub sum_of_balances: Uint128,
+    pub contract_balance: Uint128,
+    // Pending and unclaimed rewards of the positions in this asset in this page
+    pub reward_liabilities: Uint128,
+    // Only set when a single page scanned every position
+    pub balances_match: Option<bool>,
+    // The hub holds at least the total staked amount, plus the rewards owed when this is
+    // also the reward asset
+    pub solvent: bool,
+}
+
+// `liabilities` are the rewards owed to every staker, rounded up. `balance` excludes the
+// amount staked when the reward asset is also a staked asset.
+#[cw_serde]
+pub struct RewardInvariantsRes {
+    pub reward_asset: AssetInfo,
+    pub liabilities: Uint128,
+    pub balance: Uint128,
+    pub surplus: Uint128,
+    pub deficit: Uint128,
+}
+
+#[cw_serde]
+pub struct CapabilityHoldersRes {
+    pub capability: Capability,
+    pub holders: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct HookRes {
+    pub addr: Addr,
+    pub gas_limit: u64,
+}
+
+#[cw_serde]
+pub struct AssetPauseStatus {
+    pub asset: AssetInfo,
+    pub paused: PausedActions,
+}
+
+#[cw_serde]
+pub struct PauseStatusRes {
+    pub global: bool,
+    pub reward_updates: bool,
+    pub assets: Vec<AssetPauseStatus>,
+}
+
 #[cw_serde]
 pub struct PendingRewardsRes {
     pub staked_asset: AssetInfo,
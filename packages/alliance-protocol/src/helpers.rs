@@ -0,0 +1,712 @@
+use std::collections::{HashMap, HashSet};
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_json_binary, Addr, Coin, CustomQuery, Decimal, QuerierWrapper, StdResult, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use cw_asset_v3::{Asset, AssetInfo};
+use serde::de::DeserializeOwned;
+
+use crate::alliance_oracle_types::{
+    AssetStaked, ChainId, ChainInfo, ChainsInfo, Config as OracleConfig, EmissionsDistribution,
+    ExecuteMsg as OracleExecuteMsg, FeederSubmission, FeedersResponse, LunaInfo,
+    QueryMsg as OracleQueryMsg, RoundStatusResponse,
+};
+use crate::alliance_protocol::{
+    AllPendingRewardsQuery, AllStakedBalancesQuery, AllianceDelegateMsg, AllianceRedelegateMsg,
+    AllianceTokenMetadata, AllianceUndelegateMsg, AssetDistribution, AssetQuery, AssetVotingWeight,
+    Capability, CapabilityHoldersRes, ClaimFailure, Config, Cw20HookMsg, DelegationStrategy,
+    ExecuteMsg, HookRes, IbcDestination, IbcOrigin, InfoResponse, InvariantsCursor, InvariantsRes,
+    MigrationStepRes, PauseScope, PauseStatusRes, PendingRewardsRes, PendingRoleTransfer, QueryMsg,
+    RebalanceAction, Role, StakedBalanceAtHeightRes, StakedBalanceRes, TotalPowerAtHeightResponse,
+    ValidatorDelegation, VotingConfigRes, VotingPowerAtHeightResponse, WhitelistedAssetsResponse,
+};
+
+// Typed messages and queries for the hub at the wrapped address. Messages are returned as
+// `WasmMsg`, which converts into a `CosmosMsg` of any custom message type.
+#[cw_serde]
+pub struct AllianceHubContract(pub Addr);
+
+impl AllianceHubContract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    pub fn call(&self, msg: &ExecuteMsg, funds: Vec<Coin>) -> StdResult<WasmMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.addr().into(),
+            msg: to_json_binary(msg)?,
+            funds,
+        })
+    }
+
+    // Sends `amount` of the cw20 `token` to the hub along with `hook`
+    pub fn send_cw20(
+        &self,
+        token: impl Into<String>,
+        amount: Uint128,
+        hook: &Cw20HookMsg,
+    ) -> StdResult<WasmMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: token.into(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Send {
+                contract: self.addr().into(),
+                amount,
+                msg: to_json_binary(hook)?,
+            })?,
+            funds: vec![],
+        })
+    }
+
+    pub fn stake(
+        &self,
+        funds: Coin,
+        recipient: Option<String>,
+        origin: Option<IbcOrigin>,
+    ) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::Stake { recipient, origin }, vec![funds])
+    }
+
+    pub fn stake_cw20(&self, token: impl Into<String>, amount: Uint128) -> StdResult<WasmMsg> {
+        self.send_cw20(token, amount, &Cw20HookMsg::Stake {})
+    }
+
+    pub fn unstake(&self, asset: Asset) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::Unstake(asset), vec![])
+    }
+
+    pub fn claim_rewards(&self, asset: AssetInfo) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::ClaimRewards(asset), vec![])
+    }
+
+    pub fn unstake_over_ibc(
+        &self,
+        asset: Asset,
+        destination: IbcDestination,
+    ) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::UnstakeOverIbc { asset, destination }, vec![])
+    }
+
+    pub fn claim_rewards_over_ibc(
+        &self,
+        asset: AssetInfo,
+        destination: IbcDestination,
+    ) -> StdResult<WasmMsg> {
+        self.call(
+            &ExecuteMsg::ClaimRewardsOverIbc { asset, destination },
+            vec![],
+        )
+    }
+
+    // `funds` are added to the rewards when the reward asset is native, cw20 reward tokens
+    // are sent with `send_cw20` and `Cw20HookMsg::UpdateRewards`
+    pub fn update_rewards(&self, funds: Vec<Coin>) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::UpdateRewards {}, funds)
+    }
+
+    pub fn whitelist_assets(&self, assets: HashMap<ChainId, Vec<AssetInfo>>) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::WhitelistAssets(assets), vec![])
+    }
+
+    pub fn remove_assets(&self, assets: Vec<AssetInfo>) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::RemoveAssets(assets), vec![])
+    }
+
+    pub fn alliance_delegate(&self, msg: AllianceDelegateMsg) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::AllianceDelegate(msg), vec![])
+    }
+
+    pub fn alliance_undelegate(&self, msg: AllianceUndelegateMsg) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::AllianceUndelegate(msg), vec![])
+    }
+
+    pub fn alliance_redelegate(&self, msg: AllianceRedelegateMsg) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::AllianceRedelegate(msg), vec![])
+    }
+
+    pub fn set_delegation_strategy(&self, strategy: DelegationStrategy) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::SetDelegationStrategy(strategy), vec![])
+    }
+
+    pub fn rebalance_delegations(&self) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::RebalanceDelegations {}, vec![])
+    }
+
+    pub fn reset_claim_failures(&self, validator: impl Into<String>) -> StdResult<WasmMsg> {
+        self.call(
+            &ExecuteMsg::ResetClaimFailures {
+                validator: validator.into(),
+            },
+            vec![],
+        )
+    }
+
+    pub fn mint_alliance_tokens(&self, amount: Uint128) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::MintAllianceTokens { amount }, vec![])
+    }
+
+    pub fn burn_alliance_tokens(&self, amount: Uint128) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::BurnAllianceTokens { amount }, vec![])
+    }
+
+    pub fn update_alliance_token_metadata(
+        &self,
+        metadata: AllianceTokenMetadata,
+    ) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::UpdateAllianceTokenMetadata(metadata), vec![])
+    }
+
+    pub fn update_rewards_config(
+        &self,
+        reward_update_interval: Option<u64>,
+        keeper_bounty: Option<Decimal>,
+    ) -> StdResult<WasmMsg> {
+        self.call(
+            &ExecuteMsg::UpdateRewardsConfig {
+                reward_update_interval,
+                keeper_bounty,
+            },
+            vec![],
+        )
+    }
+
+    pub fn update_reward_asset(&self, asset: AssetInfo) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::UpdateRewardAsset(asset), vec![])
+    }
+
+    pub fn update_voting_config(
+        &self,
+        dao: Option<String>,
+        asset_weights: Option<Vec<AssetVotingWeight>>,
+    ) -> StdResult<WasmMsg> {
+        self.call(
+            &ExecuteMsg::UpdateVotingConfig { dao, asset_weights },
+            vec![],
+        )
+    }
+
+    // RebalanceEmissions and its callback are disabled in the hub, so they have no helper
+    pub fn set_asset_reward_distribution(
+        &self,
+        distribution: Vec<AssetDistribution>,
+    ) -> StdResult<WasmMsg> {
+        self.call(
+            &ExecuteMsg::SetAssetRewardDistribution(distribution),
+            vec![],
+        )
+    }
+
+    pub fn propose_role_transfer(
+        &self,
+        role: Role,
+        new_address: impl Into<String>,
+        expires_in: u64,
+    ) -> StdResult<WasmMsg> {
+        self.call(
+            &ExecuteMsg::ProposeRoleTransfer {
+                role,
+                new_address: new_address.into(),
+                expires_in,
+            },
+            vec![],
+        )
+    }
+
+    pub fn accept_role_transfer(&self, role: Role) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::AcceptRoleTransfer { role }, vec![])
+    }
+
+    pub fn cancel_role_transfer(&self, role: Role) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::CancelRoleTransfer { role }, vec![])
+    }
+
+    pub fn pause(&self, scope: PauseScope) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::Pause(scope), vec![])
+    }
+
+    pub fn unpause(&self, scope: PauseScope) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::Unpause(scope), vec![])
+    }
+
+    pub fn grant_capability(
+        &self,
+        address: impl Into<String>,
+        capability: Capability,
+    ) -> StdResult<WasmMsg> {
+        self.call(
+            &ExecuteMsg::GrantCapability {
+                address: address.into(),
+                capability,
+            },
+            vec![],
+        )
+    }
+
+    pub fn revoke_capability(
+        &self,
+        address: impl Into<String>,
+        capability: Capability,
+    ) -> StdResult<WasmMsg> {
+        self.call(
+            &ExecuteMsg::RevokeCapability {
+                address: address.into(),
+                capability,
+            },
+            vec![],
+        )
+    }
+
+    pub fn add_hook(&self, addr: impl Into<String>, gas_limit: u64) -> StdResult<WasmMsg> {
+        self.call(
+            &ExecuteMsg::AddHook {
+                addr: addr.into(),
+                gas_limit,
+            },
+            vec![],
+        )
+    }
+
+    pub fn remove_hook(&self, addr: impl Into<String>) -> StdResult<WasmMsg> {
+        self.call(&ExecuteMsg::RemoveHook { addr: addr.into() }, vec![])
+    }
+
+    pub fn sweep(&self, asset: AssetInfo, recipient: impl Into<String>) -> StdResult<WasmMsg> {
+        self.call(
+            &ExecuteMsg::Sweep {
+                asset,
+                recipient: recipient.into(),
+            },
+            vec![],
+        )
+    }
+
+    pub fn query<T: DeserializeOwned, CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        msg: &QueryMsg,
+    ) -> StdResult<T> {
+        querier.query_wasm_smart(self.addr(), msg)
+    }
+
+    pub fn config<CQ: CustomQuery>(&self, querier: &QuerierWrapper<CQ>) -> StdResult<Config> {
+        self.query(querier, &QueryMsg::Config {})
+    }
+
+    pub fn validators<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<HashSet<String>> {
+        self.query(querier, &QueryMsg::Validators {})
+    }
+
+    pub fn delegations<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<Vec<ValidatorDelegation>> {
+        self.query(querier, &QueryMsg::Delegations {})
+    }
+
+    pub fn migration_plan<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        from_version: Option<String>,
+    ) -> StdResult<Vec<MigrationStepRes>> {
+        self.query(querier, &QueryMsg::MigrationPlan { from_version })
+    }
+
+    pub fn remote_identity<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        address: impl Into<String>,
+    ) -> StdResult<Option<IbcOrigin>> {
+        self.query(
+            querier,
+            &QueryMsg::RemoteIdentity {
+                address: address.into(),
+            },
+        )
+    }
+
+    pub fn claim_failures<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<Vec<ClaimFailure>> {
+        self.query(querier, &QueryMsg::ClaimFailures {})
+    }
+
+    pub fn delegation_strategy<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<DelegationStrategy> {
+        self.query(querier, &QueryMsg::DelegationStrategy {})
+    }
+
+    pub fn rebalance_plan<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<Vec<RebalanceAction>> {
+        self.query(querier, &QueryMsg::RebalancePlan {})
+    }
+
+    pub fn whitelisted_assets<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<WhitelistedAssetsResponse> {
+        self.query(querier, &QueryMsg::WhitelistedAssets {})
+    }
+
+    pub fn reward_distribution<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<Vec<AssetDistribution>> {
+        self.query(querier, &QueryMsg::RewardDistribution {})
+    }
+
+    pub fn staked_balance<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        address: impl Into<String>,
+        asset: AssetInfo,
+    ) -> StdResult<StakedBalanceRes> {
+        self.query(
+            querier,
+            &QueryMsg::StakedBalance(AssetQuery {
+                address: address.into(),
+                asset,
+            }),
+        )
+    }
+
+    pub fn pending_rewards<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        address: impl Into<String>,
+        asset: AssetInfo,
+    ) -> StdResult<PendingRewardsRes> {
+        self.query(
+            querier,
+            &QueryMsg::PendingRewards(AssetQuery {
+                address: address.into(),
+                asset,
+            }),
+        )
+    }
+
+    pub fn all_staked_balances<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        address: impl Into<String>,
+    ) -> StdResult<Vec<StakedBalanceRes>> {
+        self.query(
+            querier,
+            &QueryMsg::AllStakedBalances(AllStakedBalancesQuery {
+                address: address.into(),
+            }),
+        )
+    }
+
+    pub fn all_pending_rewards<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        address: impl Into<String>,
+    ) -> StdResult<Vec<PendingRewardsRes>> {
+        self.query(
+            querier,
+            &QueryMsg::AllPendingRewards(AllPendingRewardsQuery {
+                address: address.into(),
+            }),
+        )
+    }
+
+    pub fn total_staked_balances<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<Vec<StakedBalanceRes>> {
+        self.query(querier, &QueryMsg::TotalStakedBalances {})
+    }
+
+    pub fn staked_balance_at_height<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        address: impl Into<String>,
+        asset: AssetInfo,
+        height: Option<u64>,
+    ) -> StdResult<StakedBalanceAtHeightRes> {
+        self.query(
+            querier,
+            &QueryMsg::StakedBalanceAtHeight {
+                address: address.into(),
+                asset,
+                height,
+            },
+        )
+    }
+
+    pub fn total_staked_at_height<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        asset: AssetInfo,
+        height: Option<u64>,
+    ) -> StdResult<StakedBalanceAtHeightRes> {
+        self.query(querier, &QueryMsg::TotalStakedAtHeight { asset, height })
+    }
+
+    pub fn pending_role_transfers<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<Vec<PendingRoleTransfer>> {
+        self.query(querier, &QueryMsg::PendingRoleTransfers {})
+    }
+
+    pub fn pause_status<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<PauseStatusRes> {
+        self.query(querier, &QueryMsg::PauseStatus {})
+    }
+
+    pub fn capability_holders<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<Vec<CapabilityHoldersRes>> {
+        self.query(querier, &QueryMsg::CapabilityHolders {})
+    }
+
+    pub fn hooks<CQ: CustomQuery>(&self, querier: &QuerierWrapper<CQ>) -> StdResult<Vec<HookRes>> {
+        self.query(querier, &QueryMsg::Hooks {})
+    }
+
+    pub fn voting_config<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<VotingConfigRes> {
+        self.query(querier, &QueryMsg::VotingConfig {})
+    }
+
+    pub fn voting_power_at_height<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        address: impl Into<String>,
+        height: Option<u64>,
+    ) -> StdResult<VotingPowerAtHeightResponse> {
+        self.query(
+            querier,
+            &QueryMsg::VotingPowerAtHeight {
+                address: address.into(),
+                height,
+            },
+        )
+    }
+
+    pub fn total_power_at_height<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        height: Option<u64>,
+    ) -> StdResult<TotalPowerAtHeightResponse> {
+        self.query(querier, &QueryMsg::TotalPowerAtHeight { height })
+    }
+
+    pub fn dao<CQ: CustomQuery>(&self, querier: &QuerierWrapper<CQ>) -> StdResult<Addr> {
+        self.query(querier, &QueryMsg::Dao {})
+    }
+
+    pub fn info<CQ: CustomQuery>(&self, querier: &QuerierWrapper<CQ>) -> StdResult<InfoResponse> {
+        self.query(querier, &QueryMsg::Info {})
+    }
+
+    pub fn check_invariants<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        start_after: Option<InvariantsCursor>,
+        limit: Option<u32>,
+    ) -> StdResult<InvariantsRes> {
+        self.query(querier, &QueryMsg::CheckInvariants { start_after, limit })
+    }
+}
+
+#[cw_serde]
+pub struct AllianceOracleContract(pub Addr);
+
+impl AllianceOracleContract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    pub fn call(&self, msg: &OracleExecuteMsg) -> StdResult<WasmMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.addr().into(),
+            msg: to_json_binary(msg)?,
+            funds: vec![],
+        })
+    }
+
+    pub fn update_chains_info(&self, chains_info: ChainsInfo) -> StdResult<WasmMsg> {
+        self.call(&OracleExecuteMsg::UpdateChainsInfo { chains_info })
+    }
+
+    pub fn update_feeders(
+        &self,
+        add: Vec<String>,
+        remove: Vec<String>,
+        quorum: Option<u32>,
+    ) -> StdResult<WasmMsg> {
+        self.call(&OracleExecuteMsg::UpdateFeeders {
+            add,
+            remove,
+            quorum,
+        })
+    }
+
+    pub fn query<T: DeserializeOwned, CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        msg: &OracleQueryMsg,
+    ) -> StdResult<T> {
+        querier.query_wasm_smart(self.addr(), msg)
+    }
+
+    pub fn config<CQ: CustomQuery>(&self, querier: &QuerierWrapper<CQ>) -> StdResult<OracleConfig> {
+        self.query(querier, &OracleQueryMsg::QueryConfig {})
+    }
+
+    pub fn luna_info<CQ: CustomQuery>(&self, querier: &QuerierWrapper<CQ>) -> StdResult<LunaInfo> {
+        self.query(querier, &OracleQueryMsg::QueryLunaInfo {})
+    }
+
+    pub fn chain_info<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        chain_id: impl Into<ChainId>,
+    ) -> StdResult<ChainInfo> {
+        self.query(
+            querier,
+            &OracleQueryMsg::QueryChainInfo {
+                chain_id: chain_id.into(),
+            },
+        )
+    }
+
+    pub fn chains_info<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<Vec<ChainInfo>> {
+        self.query(querier, &OracleQueryMsg::QueryChainsInfo {})
+    }
+
+    // Same as `chains_info` without the expiry check of the stored data
+    pub fn chains_info_unsafe<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<Vec<ChainInfo>> {
+        self.query(querier, &OracleQueryMsg::QueryChainsInfoUnsafe {})
+    }
+
+    pub fn emissions_distributions<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        assets_staked: HashMap<ChainId, Vec<AssetStaked>>,
+    ) -> StdResult<Vec<EmissionsDistribution>> {
+        self.query(
+            querier,
+            &OracleQueryMsg::QueryEmissionsDistributions(assets_staked),
+        )
+    }
+
+    pub fn feeders<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<FeedersResponse> {
+        self.query(querier, &OracleQueryMsg::QueryFeeders {})
+    }
+
+    // Submissions of `round`, the current round when `None`
+    pub fn feeder_submissions<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+        round: Option<u64>,
+    ) -> StdResult<Vec<FeederSubmission>> {
+        self.query(querier, &OracleQueryMsg::QueryFeederSubmissions { round })
+    }
+
+    pub fn round_status<CQ: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<CQ>,
+    ) -> StdResult<RoundStatusResponse> {
+        self.query(querier, &OracleQueryMsg::QueryRoundStatus {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockQuerier;
+    use cosmwasm_std::{coin, from_json, ContractResult, Empty, SystemResult, WasmQuery};
+
+    #[test]
+    fn test_hub_messages() {
+        let hub = AllianceHubContract(Addr::unchecked("hub"));
+
+        let msg = hub.stake(coin(100, "aWHALE"), None, None).unwrap();
+        assert_eq!(
+            msg,
+            WasmMsg::Execute {
+                contract_addr: "hub".to_string(),
+                msg: to_json_binary(&ExecuteMsg::Stake {
+                    recipient: None,
+                    origin: None,
+                })
+                .unwrap(),
+                funds: vec![coin(100, "aWHALE")],
+            }
+        );
+
+        let msg = hub.stake_cw20("token", Uint128::new(100)).unwrap();
+        assert_eq!(
+            msg,
+            WasmMsg::Execute {
+                contract_addr: "token".to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Send {
+                    contract: "hub".to_string(),
+                    amount: Uint128::new(100),
+                    msg: to_json_binary(&Cw20HookMsg::Stake {}).unwrap(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_hub_queries() {
+        let hub = AllianceHubContract(Addr::unchecked("hub"));
+        let mut querier = MockQuerier::<Empty>::new(&[]);
+        querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "hub" => {
+                let res = match from_json(msg).unwrap() {
+                    QueryMsg::StakedBalance(query) => to_json_binary(&StakedBalanceRes {
+                        asset: query.asset,
+                        balance: Uint128::new(100),
+                    }),
+                    QueryMsg::Dao {} => to_json_binary(&Addr::unchecked("dao")),
+                    _ => panic!("unexpected query"),
+                };
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
+            _ => panic!("unexpected query"),
+        });
+        let querier = QuerierWrapper::<Empty>::new(&querier);
+
+        let res = hub
+            .staked_balance(&querier, "user1", AssetInfo::native("aWHALE"))
+            .unwrap();
+        assert_eq!(
+            res,
+            StakedBalanceRes {
+                asset: AssetInfo::native("aWHALE"),
+                balance: Uint128::new(100),
+            }
+        );
+        assert_eq!(hub.dao(&querier).unwrap(), Addr::unchecked("dao"));
+    }
+}
@@ -1,3 +1,4 @@
 pub mod alliance_oracle_types;
 pub mod alliance_protocol;
+pub mod helpers;
 pub mod signed_decimal;
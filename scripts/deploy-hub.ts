@@ -61,7 +61,7 @@ const init = async () => {
             "controller": accAddress,
             "governance": govAccountAddr,
             "oracle" : oracleAddress,
-            "reward_denom": "uluna",
+            "reward_asset": { "native": "uluna" },
         },
         Coins.fromString("10000000uluna"),
         "Create an Hub contract"